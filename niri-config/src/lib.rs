@@ -1,11 +1,15 @@
 #[macro_use]
 extern crate tracing;
 
+use std::collections::HashSet;
+use std::mem;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use bitflags::bitflags;
 use miette::{miette, Context, IntoDiagnostic, NarratableReportHandler};
+pub use niri_ipc::{Action, LayoutSwitchTarget, SizeChange, WorkspaceReferenceArg};
+use regex::Regex;
 use smithay::input::keyboard::keysyms::KEY_NoSymbol;
 use smithay::input::keyboard::xkb::{keysym_from_name, KEYSYM_CASE_INSENSITIVE};
 use smithay::input::keyboard::{Keysym, XkbConfig};
@@ -13,6 +17,8 @@ use smithay::reexports::input;
 
 #[derive(knuffel::Decode, Debug, PartialEq)]
 pub struct Config {
+    #[knuffel(children(name = "include"))]
+    pub includes: Vec<Include>,
     #[knuffel(child, default)]
     pub input: Input,
     #[knuffel(children(name = "output"))]
@@ -36,12 +42,25 @@ pub struct Config {
     #[knuffel(child, default)]
     pub hotkey_overlay: HotkeyOverlay,
     #[knuffel(child, default)]
+    pub animations: Animations,
+    #[knuffel(children(name = "window-rule"))]
+    pub window_rules: Vec<WindowRule>,
+    #[knuffel(children(name = "workspace"))]
+    pub workspaces: Vec<Workspace>,
+    #[knuffel(child, default)]
     pub binds: Binds,
     #[knuffel(child, default)]
     pub debug: DebugConfig,
 }
 
-// FIXME: Add other devices.
+/// Another config file to merge into this one.
+#[derive(knuffel::Decode, Debug, Clone, PartialEq, Eq)]
+pub struct Include {
+    /// Path relative to the including file, which may contain glob patterns.
+    #[knuffel(argument)]
+    pub path: String,
+}
+
 #[derive(knuffel::Decode, Debug, Default, PartialEq)]
 pub struct Input {
     #[knuffel(child, default)]
@@ -51,11 +70,34 @@ pub struct Input {
     #[knuffel(child, default)]
     pub mouse: Mouse,
     #[knuffel(child, default)]
+    pub trackpoint: Trackpoint,
+    #[knuffel(child, default)]
+    pub trackball: Trackball,
+    #[knuffel(child, default)]
     pub tablet: Tablet,
+    #[knuffel(children(name = "device"))]
+    pub devices: Vec<InputDevice>,
     #[knuffel(child)]
     pub disable_power_key_handling: bool,
+    #[knuffel(child)]
+    pub focus_follows_mouse: Option<FocusFollowsMouse>,
+    #[knuffel(child)]
+    pub warp_mouse_to_focus: bool,
+    #[knuffel(child)]
+    pub workspace_auto_back_and_forth: bool,
 }
 
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct FocusFollowsMouse {
+    /// Maximum amount of scrolling, in percent of the working area width, that focusing a window
+    /// under the pointer is allowed to cause.
+    #[knuffel(property, str)]
+    pub max_scroll_amount: Option<Percent>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Percent(pub f64);
+
 #[derive(knuffel::Decode, Debug, Default, PartialEq, Eq)]
 pub struct Keyboard {
     #[knuffel(child, default)]
@@ -116,31 +158,173 @@ pub enum TrackLayout {
     Window,
 }
 
-// FIXME: Add the rest of the settings.
 #[derive(knuffel::Decode, Debug, Default, PartialEq)]
 pub struct Touchpad {
+    #[knuffel(child)]
+    pub off: bool,
     #[knuffel(child)]
     pub tap: bool,
     #[knuffel(child)]
     pub dwt: bool,
     #[knuffel(child)]
+    pub dwtp: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub drag: Option<bool>,
+    #[knuffel(child)]
+    pub drag_lock: bool,
+    #[knuffel(child)]
     pub natural_scroll: bool,
+    #[knuffel(child, unwrap(argument, str))]
+    pub click_method: Option<ClickMethod>,
     #[knuffel(child, unwrap(argument), default)]
     pub accel_speed: f64,
     #[knuffel(child, unwrap(argument, str))]
     pub accel_profile: Option<AccelProfile>,
     #[knuffel(child, unwrap(argument, str))]
+    pub scroll_method: Option<ScrollMethod>,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_button: Option<u32>,
+    #[knuffel(child, unwrap(argument, str))]
     pub tap_button_map: Option<TapButtonMap>,
+    #[knuffel(child)]
+    pub left_handed: bool,
+    #[knuffel(child)]
+    pub disabled_on_external_mouse: bool,
+    #[knuffel(child)]
+    pub middle_emulation: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_factor: Option<f64>,
 }
 
 #[derive(knuffel::Decode, Debug, Default, PartialEq)]
 pub struct Mouse {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub natural_scroll: bool,
+    #[knuffel(child, unwrap(argument), default)]
+    pub accel_speed: f64,
+    #[knuffel(child, unwrap(argument, str))]
+    pub accel_profile: Option<AccelProfile>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub scroll_method: Option<ScrollMethod>,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_button: Option<u32>,
+    #[knuffel(child)]
+    pub left_handed: bool,
+    #[knuffel(child)]
+    pub middle_emulation: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_factor: Option<f64>,
+}
+
+#[derive(knuffel::Decode, Debug, Default, PartialEq)]
+pub struct Trackpoint {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub natural_scroll: bool,
+    #[knuffel(child, unwrap(argument), default)]
+    pub accel_speed: f64,
+    #[knuffel(child, unwrap(argument, str))]
+    pub accel_profile: Option<AccelProfile>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub scroll_method: Option<ScrollMethod>,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_button: Option<u32>,
+    #[knuffel(child)]
+    pub left_handed: bool,
+    #[knuffel(child)]
+    pub middle_emulation: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_factor: Option<f64>,
+}
+
+#[derive(knuffel::Decode, Debug, Default, PartialEq)]
+pub struct Trackball {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub natural_scroll: bool,
+    #[knuffel(child, unwrap(argument), default)]
+    pub accel_speed: f64,
+    #[knuffel(child, unwrap(argument, str))]
+    pub accel_profile: Option<AccelProfile>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub scroll_method: Option<ScrollMethod>,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_button: Option<u32>,
+    #[knuffel(child)]
+    pub left_handed: bool,
+    #[knuffel(child)]
+    pub middle_emulation: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_factor: Option<f64>,
+}
+
+/// Settings for a specific input device.
+///
+/// These replace the settings from the section for the type of the device.
+#[derive(knuffel::Decode, Debug, Default, PartialEq)]
+pub struct InputDevice {
+    /// Device name as reported by libinput.
+    #[knuffel(argument)]
+    pub name: String,
+    /// USB vendor ID to match, for telling apart devices with the same name.
+    #[knuffel(property)]
+    pub vendor: Option<u32>,
+    /// USB product ID to match.
+    #[knuffel(property)]
+    pub product: Option<u32>,
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child)]
+    pub tap: bool,
+    #[knuffel(child)]
+    pub dwt: bool,
+    #[knuffel(child)]
+    pub dwtp: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub drag: Option<bool>,
+    #[knuffel(child)]
+    pub drag_lock: bool,
     #[knuffel(child)]
     pub natural_scroll: bool,
+    #[knuffel(child, unwrap(argument, str))]
+    pub click_method: Option<ClickMethod>,
     #[knuffel(child, unwrap(argument), default)]
     pub accel_speed: f64,
     #[knuffel(child, unwrap(argument, str))]
     pub accel_profile: Option<AccelProfile>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub scroll_method: Option<ScrollMethod>,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_button: Option<u32>,
+    #[knuffel(child, unwrap(argument, str))]
+    pub tap_button_map: Option<TapButtonMap>,
+    #[knuffel(child)]
+    pub left_handed: bool,
+    #[knuffel(child)]
+    pub disabled_on_external_mouse: bool,
+    #[knuffel(child)]
+    pub middle_emulation: bool,
+    #[knuffel(child, unwrap(argument))]
+    pub scroll_factor: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickMethod {
+    Clickfinger,
+    ButtonAreas,
+}
+
+impl From<ClickMethod> for input::ClickMethod {
+    fn from(value: ClickMethod) -> Self {
+        match value {
+            ClickMethod::Clickfinger => Self::Clickfinger,
+            ClickMethod::ButtonAreas => Self::ButtonAreas,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -158,6 +342,25 @@ impl From<AccelProfile> for input::AccelProfile {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollMethod {
+    NoScroll,
+    TwoFinger,
+    Edge,
+    OnButtonDown,
+}
+
+impl From<ScrollMethod> for input::ScrollMethod {
+    fn from(value: ScrollMethod) -> Self {
+        match value {
+            ScrollMethod::NoScroll => Self::NoScroll,
+            ScrollMethod::TwoFinger => Self::TwoFinger,
+            ScrollMethod::Edge => Self::Edge,
+            ScrollMethod::OnButtonDown => Self::OnButtonDown,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TapButtonMap {
     LeftRightMiddle,
@@ -173,6 +376,14 @@ impl From<TapButtonMap> for input::TapButtonMap {
     }
 }
 
+impl InputDevice {
+    pub fn matches(&self, name: &str, vendor: u32, product: u32) -> bool {
+        self.name == name
+            && self.vendor.map_or(true, |v| v == vendor)
+            && self.product.map_or(true, |p| p == product)
+    }
+}
+
 #[derive(knuffel::Decode, Debug, Default, PartialEq)]
 pub struct Tablet {
     #[knuffel(child, unwrap(argument))]
@@ -257,6 +468,21 @@ impl From<Transform> for smithay::utils::Transform {
     }
 }
 
+impl From<niri_ipc::Transform> for Transform {
+    fn from(value: niri_ipc::Transform) -> Self {
+        match value {
+            niri_ipc::Transform::Normal => Self::Normal,
+            niri_ipc::Transform::_90 => Self::_90,
+            niri_ipc::Transform::_180 => Self::_180,
+            niri_ipc::Transform::_270 => Self::_270,
+            niri_ipc::Transform::Flipped => Self::Flipped,
+            niri_ipc::Transform::Flipped90 => Self::Flipped90,
+            niri_ipc::Transform::Flipped180 => Self::Flipped180,
+            niri_ipc::Transform::Flipped270 => Self::Flipped270,
+        }
+    }
+}
+
 #[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Position {
     #[knuffel(property)]
@@ -265,6 +491,13 @@ pub struct Position {
     pub y: i32,
 }
 
+impl From<niri_ipc::ConfiguredPosition> for Position {
+    fn from(value: niri_ipc::ConfiguredPosition) -> Self {
+        let niri_ipc::ConfiguredPosition { x, y } = value;
+        Self { x, y }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Mode {
     pub width: u16,
@@ -272,6 +505,21 @@ pub struct Mode {
     pub refresh: Option<f64>,
 }
 
+impl From<niri_ipc::ConfiguredMode> for Mode {
+    fn from(value: niri_ipc::ConfiguredMode) -> Self {
+        let niri_ipc::ConfiguredMode {
+            width,
+            height,
+            refresh,
+        } = value;
+        Self {
+            width,
+            height,
+            refresh,
+        }
+    }
+}
+
 #[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
 pub struct Layout {
     #[knuffel(child, default)]
@@ -397,6 +645,134 @@ pub struct HotkeyOverlay {
     pub skip_at_startup: bool,
 }
 
+#[derive(knuffel::Decode, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Animations {
+    /// Disables all animations.
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child, default)]
+    pub view_movement: Animation,
+    #[knuffel(child, default)]
+    pub workspace_switch: Animation,
+    #[knuffel(child, default)]
+    pub window_movement: Animation,
+    #[knuffel(child, default)]
+    pub overview: Animation,
+    #[knuffel(child, default)]
+    pub overlay_fade: Animation,
+}
+
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+    #[knuffel(child)]
+    pub off: bool,
+    #[knuffel(child, unwrap(argument), default = 250)]
+    pub duration_ms: u32,
+    #[knuffel(child, unwrap(argument, str), default)]
+    pub curve: AnimationCurve,
+    #[knuffel(child)]
+    pub spring: Option<SpringParams>,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            off: false,
+            duration_ms: 250,
+            curve: AnimationCurve::default(),
+            spring: None,
+        }
+    }
+}
+
+/// Parameters of a spring animation, which replaces the duration and the curve.
+#[derive(knuffel::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct SpringParams {
+    /// 1 is critically damped, below 1 oscillates, above 1 approaches the target slower.
+    #[knuffel(property)]
+    pub damping_ratio: f64,
+    #[knuffel(property)]
+    pub stiffness: u32,
+    /// The animation ends once the spring stays this close to the target, as a fraction of the
+    /// distance it moves.
+    #[knuffel(property)]
+    pub epsilon: f64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum AnimationCurve {
+    Linear,
+    EaseOutQuad,
+    #[default]
+    EaseOutCubic,
+    EaseInOutCubic,
+    EaseOutQuart,
+    EaseOutQuint,
+    /// Custom curve with the same meaning as the CSS `cubic-bezier()` function.
+    CubicBezier {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
+pub struct WindowRule {
+    #[knuffel(children(name = "match"))]
+    pub matches: Vec<Match>,
+    #[knuffel(children(name = "exclude"))]
+    pub excludes: Vec<Match>,
+
+    // Rules applied at initial configure.
+    #[knuffel(child)]
+    pub default_column_width: Option<DefaultColumnWidth>,
+    #[knuffel(child, unwrap(argument))]
+    pub open_on_output: Option<String>,
+    #[knuffel(child, unwrap(argument))]
+    pub open_on_workspace: Option<u8>,
+    #[knuffel(child, unwrap(argument))]
+    pub open_fullscreen: Option<bool>,
+    #[knuffel(child, unwrap(argument))]
+    pub open_focused: Option<bool>,
+}
+
+#[derive(knuffel::Decode, Debug, Default, Clone, PartialEq)]
+pub struct Match {
+    #[knuffel(property, str)]
+    pub app_id: Option<RegexEq>,
+    #[knuffel(property, str)]
+    pub title: Option<RegexEq>,
+}
+
+/// `Regex` that implements `PartialEq` by its string form.
+#[derive(Debug, Clone)]
+pub struct RegexEq(pub Regex);
+
+impl PartialEq for RegexEq {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for RegexEq {}
+
+impl FromStr for RegexEq {
+    type Err = <Regex as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Regex::from_str(s).map(Self)
+    }
+}
+
+#[derive(knuffel::Decode, Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    #[knuffel(argument)]
+    pub name: String,
+    #[knuffel(child, unwrap(argument))]
+    pub open_on_output: Option<String>,
+}
+
 #[derive(knuffel::Decode, Debug, Default, PartialEq)]
 pub struct Binds(#[knuffel(children)] pub Vec<Bind>);
 
@@ -425,94 +801,6 @@ bitflags! {
     }
 }
 
-#[derive(knuffel::Decode, Debug, Clone, PartialEq)]
-pub enum Action {
-    Quit,
-    #[knuffel(skip)]
-    ChangeVt(i32),
-    Suspend,
-    PowerOffMonitors,
-    ToggleDebugTint,
-    Spawn(#[knuffel(arguments)] Vec<String>),
-    #[knuffel(skip)]
-    ConfirmScreenshot,
-    #[knuffel(skip)]
-    CancelScreenshot,
-    Screenshot,
-    ScreenshotScreen,
-    ScreenshotWindow,
-    CloseWindow,
-    FullscreenWindow,
-    FocusColumnLeft,
-    FocusColumnRight,
-    FocusColumnFirst,
-    FocusColumnLast,
-    FocusWindowDown,
-    FocusWindowUp,
-    FocusWindowOrWorkspaceDown,
-    FocusWindowOrWorkspaceUp,
-    MoveColumnLeft,
-    MoveColumnRight,
-    MoveColumnToFirst,
-    MoveColumnToLast,
-    MoveWindowDown,
-    MoveWindowUp,
-    MoveWindowDownOrToWorkspaceDown,
-    MoveWindowUpOrToWorkspaceUp,
-    ConsumeOrExpelWindowLeft,
-    ConsumeOrExpelWindowRight,
-    ConsumeWindowIntoColumn,
-    ExpelWindowFromColumn,
-    CenterColumn,
-    FocusWorkspaceDown,
-    FocusWorkspaceUp,
-    FocusWorkspace(#[knuffel(argument)] u8),
-    MoveWindowToWorkspaceDown,
-    MoveWindowToWorkspaceUp,
-    MoveWindowToWorkspace(#[knuffel(argument)] u8),
-    MoveColumnToWorkspaceDown,
-    MoveColumnToWorkspaceUp,
-    MoveColumnToWorkspace(#[knuffel(argument)] u8),
-    MoveWorkspaceDown,
-    MoveWorkspaceUp,
-    FocusMonitorLeft,
-    FocusMonitorRight,
-    FocusMonitorDown,
-    FocusMonitorUp,
-    MoveWindowToMonitorLeft,
-    MoveWindowToMonitorRight,
-    MoveWindowToMonitorDown,
-    MoveWindowToMonitorUp,
-    MoveColumnToMonitorLeft,
-    MoveColumnToMonitorRight,
-    MoveColumnToMonitorDown,
-    MoveColumnToMonitorUp,
-    SetWindowHeight(#[knuffel(argument, str)] SizeChange),
-    SwitchPresetColumnWidth,
-    MaximizeColumn,
-    SetColumnWidth(#[knuffel(argument, str)] SizeChange),
-    SwitchLayout(#[knuffel(argument)] LayoutAction),
-    ShowHotkeyOverlay,
-    MoveWorkspaceToMonitorLeft,
-    MoveWorkspaceToMonitorRight,
-    MoveWorkspaceToMonitorDown,
-    MoveWorkspaceToMonitorUp,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum SizeChange {
-    SetFixed(i32),
-    SetProportion(f64),
-    AdjustFixed(i32),
-    AdjustProportion(f64),
-}
-
-#[derive(knuffel::DecodeScalar, Debug, Clone, Copy, PartialEq)]
-pub enum LayoutAction {
-    Next,
-    Prev,
-}
-
 #[derive(knuffel::Decode, Debug, PartialEq)]
 pub struct DebugConfig {
     #[knuffel(child, unwrap(argument), default = 1.)]
@@ -547,18 +835,89 @@ impl Default for DebugConfig {
 
 impl Config {
     pub fn load(path: &Path) -> miette::Result<Self> {
+        Self::load_with_files(path, &mut Vec::new())
+    }
+
+    /// Loads the config, collecting the paths it depends on into `files`.
+    ///
+    /// These are the config itself, everything it includes recursively, and the directories that
+    /// glob includes look in, so that newly matching files can be noticed. The paths are collected
+    /// even when loading fails, so that fixing a broken included file can be noticed too.
+    pub fn load_with_files(path: &Path, files: &mut Vec<PathBuf>) -> miette::Result<Self> {
         let _span = tracy_client::span!("Config::load");
-        Self::load_internal(path).context("error loading config")
+
+        let partial =
+            Self::load_internal(path, &mut Vec::new(), files).context("error loading config")?;
+        Ok(partial.config)
     }
 
-    fn load_internal(path: &Path) -> miette::Result<Self> {
+    /// Loads a config file along with its includes.
+    ///
+    /// `stack` holds the canonical paths of the files that are currently being included, to
+    /// detect include cycles.
+    fn load_internal(
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        files: &mut Vec<PathBuf>,
+    ) -> miette::Result<PartialConfig> {
+        if !files.iter().any(|file| file == path) {
+            files.push(path.to_owned());
+        }
+
+        let canonical = canonicalize(path);
+        if stack.contains(&canonical) {
+            return Err(miette!("{path:?} includes itself"));
+        }
+
+        stack.push(canonical);
+        let rv = Self::load_file(path, stack, files);
+        stack.pop();
+        rv
+    }
+
+    fn load_file(
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        files: &mut Vec<PathBuf>,
+    ) -> miette::Result<PartialConfig> {
         let contents = std::fs::read_to_string(path)
             .into_diagnostic()
             .with_context(|| format!("error reading {path:?}"))?;
 
-        let config = Self::parse("config.kdl", &contents).context("error parsing")?;
+        let filename = path
+            .file_name()
+            .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+        let _span = tracy_client::span!("Config::parse");
+        let PartialConfig {
+            mut config,
+            sections,
+        } = knuffel::parse(&filename, &contents).context("error parsing")?;
         debug!("loaded config from {path:?}");
-        Ok(config)
+
+        // Included files form the base that the sections of this file go on top of.
+        let mut base: Option<PartialConfig> = None;
+        for include in mem::take(&mut config.includes) {
+            if let Some(dir) = glob_dir(path, &include.path) {
+                if !files.contains(&dir) {
+                    files.push(dir);
+                }
+            }
+
+            for included_path in resolve_include(path, &include.path)? {
+                let included = Self::load_internal(&included_path, stack, files)
+                    .with_context(|| format!("error loading {included_path:?}"))?;
+                base = Some(match base {
+                    Some(base) => base.merge(included),
+                    None => included,
+                });
+            }
+        }
+
+        let own = PartialConfig { config, sections };
+        Ok(match base {
+            Some(base) => base.merge(own),
+            None => own,
+        })
     }
 
     pub fn parse(filename: &str, text: &str) -> Result<Self, knuffel::Error> {
@@ -567,6 +926,150 @@ impl Config {
     }
 }
 
+/// Config loaded from a file and its includes.
+#[derive(Debug)]
+struct PartialConfig {
+    config: Config,
+    /// Names of the top-level sections set in the file or its includes.
+    sections: HashSet<String>,
+}
+
+impl<S: knuffel::traits::ErrorSpan> knuffel::DecodeChildren<S> for PartialConfig {
+    fn decode_children(
+        nodes: &[knuffel::ast::SpannedNode<S>],
+        ctx: &mut knuffel::decode::Context<S>,
+    ) -> Result<Self, knuffel::errors::DecodeError<S>> {
+        let sections = nodes
+            .iter()
+            .map(|node| String::from(&**node.node_name))
+            .collect();
+        let config = <Config as knuffel::DecodeChildren<S>>::decode_children(nodes, ctx)?;
+        Ok(Self { config, sections })
+    }
+}
+
+impl PartialConfig {
+    /// Merges the sections set in `top` over this config.
+    ///
+    /// Outputs, workspaces and binds replace the ones with the same name or key. Window rules and
+    /// spawn-at-startup commands are appended. Every other section replaces the previous one as a
+    /// whole.
+    fn merge(mut self, top: PartialConfig) -> Self {
+        let PartialConfig { config, sections } = top;
+        let Config {
+            includes: _,
+            input,
+            outputs,
+            spawn_at_startup,
+            layout,
+            prefer_no_csd,
+            cursor,
+            screenshot_path,
+            hotkey_overlay,
+            animations,
+            window_rules,
+            workspaces,
+            binds,
+            debug,
+        } = config;
+
+        let has = |name: &str| sections.contains(name);
+        let base = &mut self.config;
+
+        if has("input") {
+            base.input = input;
+        }
+        for output in outputs {
+            base.outputs.retain(|o| o.name != output.name);
+            base.outputs.push(output);
+        }
+        base.spawn_at_startup.extend(spawn_at_startup);
+        if has("layout") {
+            base.layout = layout;
+        }
+        if has("prefer-no-csd") {
+            base.prefer_no_csd = prefer_no_csd;
+        }
+        if has("cursor") {
+            base.cursor = cursor;
+        }
+        if has("screenshot-path") {
+            base.screenshot_path = screenshot_path;
+        }
+        if has("hotkey-overlay") {
+            base.hotkey_overlay = hotkey_overlay;
+        }
+        if has("animations") {
+            base.animations = animations;
+        }
+        base.window_rules.extend(window_rules);
+        for workspace in workspaces {
+            base.workspaces.retain(|ws| ws.name != workspace.name);
+            base.workspaces.push(workspace);
+        }
+        for bind in binds.0 {
+            base.binds.0.retain(|b| b.key != bind.key);
+            base.binds.0.push(bind);
+        }
+        if has("debug") {
+            base.debug = debug;
+        }
+
+        self.sections.extend(sections);
+        self
+    }
+}
+
+/// Returns the path with symlinks and relative components resolved, to compare paths reliably.
+///
+/// Paths that can't be resolved, for example because the file doesn't exist, are returned as is.
+fn canonicalize(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_owned())
+}
+
+fn is_glob(include: &str) -> bool {
+    include.contains(['*', '?', '['])
+}
+
+/// Returns the deepest directory that a glob include looks in, or `None` for a plain include.
+fn glob_dir(including: &Path, include: &str) -> Option<PathBuf> {
+    if !is_glob(include) {
+        return None;
+    }
+
+    let dir = including.parent().unwrap_or(Path::new(""));
+    dir.join(include)
+        .ancestors()
+        .skip(1)
+        .find(|path| !is_glob(&path.to_string_lossy()))
+        .map(Path::to_owned)
+}
+
+/// Returns the files matched by an include, in alphabetical order for glob patterns.
+fn resolve_include(including: &Path, include: &str) -> miette::Result<Vec<PathBuf>> {
+    let dir = including.parent().unwrap_or(Path::new(""));
+
+    if !is_glob(include) {
+        return Ok(vec![dir.join(include)]);
+    }
+
+    let pattern = if Path::new(include).is_absolute() {
+        include.to_owned()
+    } else {
+        let dir = dir
+            .to_str()
+            .ok_or_else(|| miette!("{dir:?} is not valid UTF-8"))?;
+        format!("{}/{include}", glob::Pattern::escape(dir))
+    };
+
+    glob::glob(&pattern)
+        .into_diagnostic()
+        .with_context(|| format!("invalid include pattern {include:?}"))?
+        .collect::<Result<_, _>>()
+        .into_diagnostic()
+        .with_context(|| format!("error resolving include {include:?}"))
+}
+
 impl Default for Config {
     fn default() -> Self {
         Config::parse(
@@ -647,72 +1150,107 @@ impl FromStr for Key {
     }
 }
 
-impl FromStr for SizeChange {
+impl FromStr for AccelProfile {
     type Err = miette::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.split_once('%') {
-            Some((value, empty)) => {
-                if !empty.is_empty() {
-                    return Err(miette!("trailing characters after '%' are not allowed"));
-                }
+        match s {
+            "adaptive" => Ok(Self::Adaptive),
+            "flat" => Ok(Self::Flat),
+            _ => Err(miette!(
+                r#"invalid accel profile, can be "adaptive" or "flat""#
+            )),
+        }
+    }
+}
 
-                match value.bytes().next() {
-                    Some(b'-' | b'+') => {
-                        let value = value
-                            .parse()
-                            .into_diagnostic()
-                            .context("error parsing value")?;
-                        Ok(Self::AdjustProportion(value))
-                    }
-                    Some(_) => {
-                        let value = value
-                            .parse()
-                            .into_diagnostic()
-                            .context("error parsing value")?;
-                        Ok(Self::SetProportion(value))
-                    }
-                    None => Err(miette!("value is missing")),
-                }
-            }
-            None => {
-                let value = s;
-                match value.bytes().next() {
-                    Some(b'-' | b'+') => {
-                        let value = value
-                            .parse()
-                            .into_diagnostic()
-                            .context("error parsing value")?;
-                        Ok(Self::AdjustFixed(value))
-                    }
-                    Some(_) => {
-                        let value = value
-                            .parse()
-                            .into_diagnostic()
-                            .context("error parsing value")?;
-                        Ok(Self::SetFixed(value))
-                    }
-                    None => Err(miette!("value is missing")),
-                }
-            }
+impl FromStr for ClickMethod {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clickfinger" => Ok(Self::Clickfinger),
+            "button-areas" => Ok(Self::ButtonAreas),
+            _ => Err(miette!(
+                r#"invalid click method, can be "button-areas" or "clickfinger""#
+            )),
         }
     }
 }
 
-impl FromStr for AccelProfile {
+impl FromStr for ScrollMethod {
     type Err = miette::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "adaptive" => Ok(Self::Adaptive),
-            "flat" => Ok(Self::Flat),
+            "no-scroll" => Ok(Self::NoScroll),
+            "two-finger" => Ok(Self::TwoFinger),
+            "edge" => Ok(Self::Edge),
+            "on-button-down" => Ok(Self::OnButtonDown),
             _ => Err(miette!(
-                r#"invalid accel profile, can be "adaptive" or "flat""#
+                r#"invalid scroll method, can be "no-scroll", "two-finger", "edge", or "on-button-down""#
             )),
         }
     }
 }
 
+impl FromStr for AnimationCurve {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => return Ok(Self::Linear),
+            "ease-out-quad" => return Ok(Self::EaseOutQuad),
+            "ease-out-cubic" => return Ok(Self::EaseOutCubic),
+            "ease-in-out-cubic" => return Ok(Self::EaseInOutCubic),
+            "ease-out-quart" => return Ok(Self::EaseOutQuart),
+            "ease-out-quint" => return Ok(Self::EaseOutQuint),
+            _ => (),
+        }
+
+        let Some(args) = s
+            .strip_prefix("cubic-bezier(")
+            .and_then(|s| s.strip_suffix(')'))
+        else {
+            return Err(miette!(
+                r#"invalid curve, can be "linear", "ease-out-quad", "ease-out-cubic", "ease-in-out-cubic", "ease-out-quart", "ease-out-quint", or "cubic-bezier(x1, y1, x2, y2)""#
+            ));
+        };
+
+        let values = args
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| miette!("error parsing cubic-bezier() values"))?;
+        let [x1, y1, x2, y2] = values[..] else {
+            return Err(miette!("cubic-bezier() must have exactly four values"));
+        };
+
+        if !(0. ..=1.).contains(&x1) || !(0. ..=1.).contains(&x2) {
+            return Err(miette!("cubic-bezier() X values must be between 0 and 1"));
+        }
+
+        Ok(Self::CubicBezier { x1, y1, x2, y2 })
+    }
+}
+
+impl FromStr for Percent {
+    type Err = miette::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((value, empty)) = s.split_once('%') else {
+            return Err(miette!("value must end with '%'"));
+        };
+
+        if !empty.is_empty() {
+            return Err(miette!("trailing characters after '%' are not allowed"));
+        }
+
+        let value: f64 = value.parse().map_err(|_| miette!("error parsing value"))?;
+        Ok(Percent(value / 100.))
+    }
+}
+
 impl FromStr for TapButtonMap {
     type Err = miette::Error;
 
@@ -751,6 +1289,8 @@ mod tests {
     fn parse() {
         check(
             r#"
+            include "extra/*.kdl"
+
             input {
                 keyboard {
                     repeat-delay 600
@@ -765,22 +1305,49 @@ mod tests {
                 touchpad {
                     tap
                     dwt
+                    dwtp
+                    drag false
+                    click-method "clickfinger"
                     accel-speed 0.2
                     accel-profile "flat"
+                    scroll-method "edge"
                     tap-button-map "left-middle-right"
+                    disabled-on-external-mouse
+                    scroll-factor 0.5
                 }
 
                 mouse {
                     natural-scroll
                     accel-speed 0.4
                     accel-profile "flat"
+                    left-handed
+                    middle-emulation
+                }
+
+                trackpoint {
+                    accel-speed 0.3
+                    scroll-method "on-button-down"
+                    scroll-button 274
+                }
+
+                trackball {
+                    natural-scroll
+                    accel-profile "adaptive"
                 }
 
                 tablet {
                     map-to-output "eDP-1"
                 }
 
+                device "Logitech MX Master" vendor=0x046d {
+                    natural-scroll
+                    accel-speed 0.3
+                }
+
                 disable-power-key-handling
+                focus-follows-mouse max-scroll-amount="25%"
+                warp-mouse-to-focus
+                workspace-auto-back-and-forth
             }
 
             output "eDP-1" {
@@ -838,6 +1405,42 @@ mod tests {
                 skip-at-startup
             }
 
+            animations {
+                view-movement {
+                    duration-ms 150
+                    curve "ease-out-quad"
+                }
+
+                workspace-switch {
+                    curve "cubic-bezier(0.25, 0.1, 0.25, 1)"
+                }
+
+                window-movement {
+                    spring damping-ratio=0.8 stiffness=1000 epsilon=0.001
+                }
+
+                overlay-fade {
+                    off
+                }
+            }
+
+            window-rule {
+                match app-id=".*alacritty"
+                exclude title="~"
+                exclude title="^vim"
+
+                open-on-output "eDP-1"
+                open-on-workspace 2
+                open-fullscreen true
+                open-focused false
+            }
+
+            workspace "chat" {
+                open-on-output "DP-1"
+            }
+
+            workspace "music"
+
             binds {
                 Mod+T { spawn "alacritty"; }
                 Mod+Q { close-window; }
@@ -845,6 +1448,9 @@ mod tests {
                 Mod+Ctrl+Shift+L { move-window-to-monitor-right; }
                 Mod+Comma { consume-window-into-column; }
                 Mod+1 { focus-workspace 1;}
+                Mod+2 { focus-workspace "chat"; }
+                Mod+Shift+2 { move-column-to-workspace "chat"; }
+                Mod+Alt+Left { move-floating-window x=-50; }
             }
 
             debug {
@@ -853,6 +1459,9 @@ mod tests {
             }
             "#,
             Config {
+                includes: vec![Include {
+                    path: "extra/*.kdl".to_owned(),
+                }],
                 input: Input {
                     keyboard: Keyboard {
                         xkb: Xkb {
@@ -865,22 +1474,62 @@ mod tests {
                         track_layout: TrackLayout::Window,
                     },
                     touchpad: Touchpad {
+                        off: false,
                         tap: true,
                         dwt: true,
+                        dwtp: true,
+                        drag: Some(false),
+                        drag_lock: false,
                         natural_scroll: false,
+                        click_method: Some(ClickMethod::Clickfinger),
                         accel_speed: 0.2,
                         accel_profile: Some(AccelProfile::Flat),
+                        scroll_method: Some(ScrollMethod::Edge),
+                        scroll_button: None,
                         tap_button_map: Some(TapButtonMap::LeftMiddleRight),
+                        left_handed: false,
+                        disabled_on_external_mouse: true,
+                        middle_emulation: false,
+                        scroll_factor: Some(0.5),
                     },
                     mouse: Mouse {
+                        off: false,
                         natural_scroll: true,
                         accel_speed: 0.4,
                         accel_profile: Some(AccelProfile::Flat),
+                        scroll_method: None,
+                        scroll_button: None,
+                        left_handed: true,
+                        middle_emulation: true,
+                        scroll_factor: None,
+                    },
+                    trackpoint: Trackpoint {
+                        accel_speed: 0.3,
+                        scroll_method: Some(ScrollMethod::OnButtonDown),
+                        scroll_button: Some(274),
+                        ..Default::default()
+                    },
+                    trackball: Trackball {
+                        natural_scroll: true,
+                        accel_profile: Some(AccelProfile::Adaptive),
+                        ..Default::default()
                     },
                     tablet: Tablet {
                         map_to_output: Some("eDP-1".to_owned()),
                     },
+                    devices: vec![InputDevice {
+                        name: "Logitech MX Master".to_owned(),
+                        vendor: Some(0x046d),
+                        natural_scroll: true,
+                        accel_speed: 0.3,
+                        ..Default::default()
+                    }],
                     disable_power_key_handling: true,
+                    focus_follows_mouse: Some(FocusFollowsMouse {
+                        max_scroll_amount: Some(Percent(0.25)),
+                    }),
+                    warp_mouse_to_focus: true,
+                    workspace_auto_back_and_forth: true,
                 },
                 outputs: vec![Output {
                     off: false,
@@ -957,13 +1606,75 @@ mod tests {
                 hotkey_overlay: HotkeyOverlay {
                     skip_at_startup: true,
                 },
+                animations: Animations {
+                    view_movement: Animation {
+                        duration_ms: 150,
+                        curve: AnimationCurve::EaseOutQuad,
+                        ..Default::default()
+                    },
+                    workspace_switch: Animation {
+                        curve: AnimationCurve::CubicBezier {
+                            x1: 0.25,
+                            y1: 0.1,
+                            x2: 0.25,
+                            y2: 1.,
+                        },
+                        ..Default::default()
+                    },
+                    window_movement: Animation {
+                        spring: Some(SpringParams {
+                            damping_ratio: 0.8,
+                            stiffness: 1000,
+                            epsilon: 0.001,
+                        }),
+                        ..Default::default()
+                    },
+                    overlay_fade: Animation {
+                        off: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                window_rules: vec![WindowRule {
+                    matches: vec![Match {
+                        app_id: Some(RegexEq::from_str(".*alacritty").unwrap()),
+                        title: None,
+                    }],
+                    excludes: vec![
+                        Match {
+                            app_id: None,
+                            title: Some(RegexEq::from_str("~").unwrap()),
+                        },
+                        Match {
+                            app_id: None,
+                            title: Some(RegexEq::from_str("^vim").unwrap()),
+                        },
+                    ],
+                    open_on_output: Some("eDP-1".to_owned()),
+                    open_on_workspace: Some(2),
+                    open_fullscreen: Some(true),
+                    open_focused: Some(false),
+                    ..Default::default()
+                }],
+                workspaces: vec![
+                    Workspace {
+                        name: "chat".to_owned(),
+                        open_on_output: Some("DP-1".to_owned()),
+                    },
+                    Workspace {
+                        name: "music".to_owned(),
+                        open_on_output: None,
+                    },
+                ],
                 binds: Binds(vec![
                     Bind {
                         key: Key {
                             keysym: Keysym::t,
                             modifiers: Modifiers::COMPOSITOR,
                         },
-                        actions: vec![Action::Spawn(vec!["alacritty".to_owned()])],
+                        actions: vec![Action::Spawn {
+                            command: vec!["alacritty".to_owned()],
+                        }],
                     },
                     Bind {
                         key: Key {
@@ -998,7 +1709,34 @@ mod tests {
                             keysym: Keysym::_1,
                             modifiers: Modifiers::COMPOSITOR,
                         },
-                        actions: vec![Action::FocusWorkspace(1)],
+                        actions: vec![Action::FocusWorkspace {
+                            reference: WorkspaceReferenceArg::Index(1),
+                        }],
+                    },
+                    Bind {
+                        key: Key {
+                            keysym: Keysym::_2,
+                            modifiers: Modifiers::COMPOSITOR,
+                        },
+                        actions: vec![Action::FocusWorkspace {
+                            reference: WorkspaceReferenceArg::Name("chat".to_owned()),
+                        }],
+                    },
+                    Bind {
+                        key: Key {
+                            keysym: Keysym::_2,
+                            modifiers: Modifiers::COMPOSITOR | Modifiers::SHIFT,
+                        },
+                        actions: vec![Action::MoveColumnToWorkspace {
+                            reference: WorkspaceReferenceArg::Name("chat".to_owned()),
+                        }],
+                    },
+                    Bind {
+                        key: Key {
+                            keysym: Keysym::Left,
+                            modifiers: Modifiers::COMPOSITOR | Modifiers::ALT,
+                        },
+                        actions: vec![Action::MoveFloatingWindow { x: -50, y: 0 }],
                     },
                 ]),
                 debug: DebugConfig {
@@ -1010,6 +1748,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn input_device_matches() {
+        let device = InputDevice {
+            name: "Logitech MX Master".to_owned(),
+            vendor: Some(0x046d),
+            ..Default::default()
+        };
+
+        assert!(device.matches("Logitech MX Master", 0x046d, 1));
+        assert!(device.matches("Logitech MX Master", 0x046d, 2));
+        assert!(!device.matches("Logitech MX Master", 0x1234, 1));
+        assert!(!device.matches("Logitech MX Anywhere", 0x046d, 1));
+    }
+
     #[test]
     fn can_create_default_config() {
         let _ = Config::default();
@@ -1041,6 +1793,73 @@ mod tests {
         assert!("1920x1080@60Hz".parse::<Mode>().is_err());
     }
 
+    #[test]
+    fn parse_animation_curve() {
+        assert_eq!(
+            "ease-in-out-cubic".parse::<AnimationCurve>().unwrap(),
+            AnimationCurve::EaseInOutCubic,
+        );
+        assert_eq!(
+            "cubic-bezier(0,0.5 , 1,-2)"
+                .parse::<AnimationCurve>()
+                .unwrap(),
+            AnimationCurve::CubicBezier {
+                x1: 0.,
+                y1: 0.5,
+                x2: 1.,
+                y2: -2.,
+            },
+        );
+
+        assert!("ease".parse::<AnimationCurve>().is_err());
+        assert!("cubic-bezier(0, 0, 1)".parse::<AnimationCurve>().is_err());
+        assert!("cubic-bezier(0, 0, 1, 1, 1)"
+            .parse::<AnimationCurve>()
+            .is_err());
+        assert!("cubic-bezier(1.5, 0, 1, 1)"
+            .parse::<AnimationCurve>()
+            .is_err());
+        assert!("cubic-bezier(0, 0, 1, 1".parse::<AnimationCurve>().is_err());
+    }
+
+    #[test]
+    fn parse_bind_actions() {
+        let config = Config::parse(
+            "test.kdl",
+            r#"
+            binds {
+                Mod+S { toggle-scratchpad; }
+                Mod+Shift+S { move-window-to-scratchpad "notes"; }
+                Mod+Space { switch-layout "prev"; }
+                Mod+Minus { set-column-width "-10%"; }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let actions: Vec<_> = config
+            .binds
+            .0
+            .into_iter()
+            .flat_map(|bind| bind.actions)
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                Action::ToggleScratchpad { name: None },
+                Action::MoveWindowToScratchpad {
+                    name: Some("notes".to_owned()),
+                },
+                Action::SwitchLayout {
+                    layout: LayoutSwitchTarget::Prev,
+                },
+                Action::SetColumnWidth {
+                    change: SizeChange::AdjustProportion(-10.),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn parse_size_change() {
         assert_eq!(
@@ -1071,4 +1890,155 @@ mod tests {
         assert!("-".parse::<SizeChange>().is_err());
         assert!("10% ".parse::<SizeChange>().is_err());
     }
+
+    /// Creates an empty directory for the config files of a test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("niri-config-test-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, name: &str, text: &str) {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    fn rule_outputs(config: &Config) -> Vec<&str> {
+        config
+            .window_rules
+            .iter()
+            .map(|rule| rule.open_on_output.as_deref().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn include_merge_precedence() {
+        let dir = test_dir("merge");
+        write(
+            &dir,
+            "base.kdl",
+            r#"
+            prefer-no-csd
+            layout {
+                gaps 8
+            }
+            window-rule {
+                open-on-output "base"
+            }
+            binds {
+                Mod+T { spawn "base"; }
+                Mod+Q { close-window; }
+            }
+            "#,
+        );
+        write(
+            &dir,
+            "config.kdl",
+            r#"
+            layout {
+                gaps 4
+            }
+            include "base.kdl"
+            window-rule {
+                open-on-output "main"
+            }
+            binds {
+                Mod+T { spawn "main"; }
+            }
+            "#,
+        );
+
+        let config = Config::load(&dir.join("config.kdl")).unwrap();
+
+        // Sections of the including file win regardless of where the include is.
+        assert_eq!(config.layout.gaps, 4);
+        // Sections that the including file doesn't set come from the include.
+        assert!(config.prefer_no_csd);
+        // Window rules extend, and binds replace the ones with the same key.
+        assert_eq!(rule_outputs(&config), ["base", "main"]);
+        let actions: Vec<_> = config.binds.0.iter().map(|bind| &bind.actions).collect();
+        assert_eq!(
+            actions,
+            [
+                &vec![Action::CloseWindow],
+                &vec![Action::Spawn {
+                    command: vec![String::from("main")],
+                }],
+            ]
+        );
+    }
+
+    #[test]
+    fn include_relative_to_including_file() {
+        let dir = test_dir("relative");
+        write(&dir, "config.kdl", r#"include "sub/first.kdl""#);
+        write(&dir, "sub/first.kdl", r#"include "second.kdl""#);
+        write(&dir, "sub/second.kdl", "layout { gaps 2; }");
+        // Must not be picked up, it's relative to the main config.
+        write(&dir, "second.kdl", "layout { gaps 3; }");
+
+        let mut files = Vec::new();
+        let config = Config::load_with_files(&dir.join("config.kdl"), &mut files).unwrap();
+        assert_eq!(config.layout.gaps, 2);
+        assert_eq!(
+            files,
+            [
+                dir.join("config.kdl"),
+                dir.join("sub/first.kdl"),
+                dir.join("sub/second.kdl"),
+            ]
+        );
+    }
+
+    #[test]
+    fn include_glob_order() {
+        let dir = test_dir("glob");
+        write(&dir, "config.kdl", r#"include "extra/*.kdl""#);
+        for name in ["b", "c", "a"] {
+            let text = format!(r#"window-rule {{ open-on-output "{name}"; }}"#);
+            write(&dir, &format!("extra/{name}.kdl"), &text);
+        }
+        write(&dir, "extra/ignored.txt", "not a config");
+
+        let mut files = Vec::new();
+        let config = Config::load_with_files(&dir.join("config.kdl"), &mut files).unwrap();
+        assert_eq!(rule_outputs(&config), ["a", "b", "c"]);
+
+        // The directory is watched to notice new files.
+        assert!(files.contains(&dir.join("extra")));
+    }
+
+    #[test]
+    fn include_diamond_is_allowed() {
+        let dir = test_dir("diamond");
+        write(&dir, "config.kdl", "include \"b.kdl\"\ninclude \"c.kdl\"");
+        write(&dir, "b.kdl", r#"include "d.kdl""#);
+        write(&dir, "c.kdl", r#"include "d.kdl""#);
+        write(&dir, "d.kdl", r#"window-rule { open-on-output "d"; }"#);
+
+        let mut files = Vec::new();
+        let config = Config::load_with_files(&dir.join("config.kdl"), &mut files).unwrap();
+        assert_eq!(rule_outputs(&config), ["d", "d"]);
+        assert_eq!(files.len(), 4);
+    }
+
+    #[test]
+    fn include_cycle_is_rejected() {
+        let dir = test_dir("cycle");
+        write(&dir, "config.kdl", r#"include "sub/other.kdl""#);
+        // A different spelling of the same path.
+        write(&dir, "sub/other.kdl", r#"include "../sub/./../config.kdl""#);
+
+        let mut files = Vec::new();
+        let err = Config::load_with_files(&dir.join("config.kdl"), &mut files).unwrap_err();
+        assert!(err
+            .chain()
+            .any(|err| err.to_string().contains("includes itself")));
+
+        // Files read before the error are still reported for watching.
+        assert!(files.contains(&dir.join("sub/other.kdl")));
+    }
 }
@@ -2,6 +2,7 @@
 #![warn(missing_docs)]
 
 use std::collections::HashMap;
+use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
@@ -13,15 +14,587 @@ pub const SOCKET_PATH_ENV: &str = "NIRI_SOCKET";
 pub enum Request {
     /// Request information about connected outputs.
     Outputs,
+    /// Request information about open windows.
+    Windows,
+    /// Request information about workspaces.
+    Workspaces,
+    /// Perform an action.
+    Action(Action),
+    /// Change output configuration temporarily.
+    ///
+    /// The configuration is changed temporarily and not saved into the config file. These
+    /// temporary changes are reverted the next time the config file is reloaded.
+    Output {
+        /// Output name.
+        output: String,
+        /// Configuration to apply.
+        action: OutputAction,
+    },
+    /// Start continuously receiving events from the compositor.
+    ///
+    /// The compositor replies with [`Response::Handled`], then keeps the connection open and
+    /// writes one [`Event`] per line as things change. The stream starts with events describing
+    /// the current state.
+    EventStream,
 }
 
 /// Response from niri to client.
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Response {
+    /// A request that does not need a response was handled successfully.
+    Handled,
     /// Information about connected outputs.
     ///
     /// Map from connector name to output info.
     Outputs(HashMap<String, Output>),
+    /// Information about open windows.
+    Windows(Vec<Window>),
+    /// Information about workspaces.
+    Workspaces(Vec<Workspace>),
+    /// Output configuration change result.
+    OutputConfigChanged(OutputConfigChanged),
+}
+
+/// Actions that niri can perform.
+///
+/// This is the single list of actions: config binds are parsed into it (with the `knuffel`
+/// feature), and `niri msg action` is generated from it (with the `clap` feature). Actions that
+/// only make sense inside niri are skipped by both IPC and the CLI.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::Subcommand))]
+#[cfg_attr(feature = "knuffel", derive(knuffel::Decode))]
+pub enum Action {
+    /// Exit niri.
+    Quit,
+    /// Switch to a virtual terminal.
+    #[serde(skip)]
+    #[cfg_attr(feature = "clap", command(skip))]
+    #[cfg_attr(feature = "knuffel", knuffel(skip))]
+    ChangeVt(i32),
+    /// Suspend the system.
+    Suspend,
+    /// Power off all monitors via DPMS.
+    PowerOffMonitors,
+    /// Spawn a command.
+    Spawn {
+        /// Command to spawn.
+        #[cfg_attr(feature = "clap", arg(last = true, required = true))]
+        #[cfg_attr(feature = "knuffel", knuffel(arguments))]
+        command: Vec<String>,
+    },
+    /// Confirm the selection in the screenshot UI.
+    #[serde(skip)]
+    #[cfg_attr(feature = "clap", command(skip))]
+    #[cfg_attr(feature = "knuffel", knuffel(skip))]
+    ConfirmScreenshot,
+    /// Close the screenshot UI.
+    #[serde(skip)]
+    #[cfg_attr(feature = "clap", command(skip))]
+    #[cfg_attr(feature = "knuffel", knuffel(skip))]
+    CancelScreenshot,
+    /// Open the screenshot UI.
+    Screenshot,
+    /// Screenshot the focused screen.
+    ScreenshotScreen,
+    /// Screenshot the focused window.
+    ScreenshotWindow,
+    /// Close the focused window.
+    CloseWindow,
+    /// Toggle fullscreen on the focused window.
+    FullscreenWindow,
+    /// Focus the column to the left.
+    FocusColumnLeft,
+    /// Focus the column to the right.
+    FocusColumnRight,
+    /// Focus the first column.
+    FocusColumnFirst,
+    /// Focus the last column.
+    FocusColumnLast,
+    /// Focus the window below.
+    FocusWindowDown,
+    /// Focus the window above.
+    FocusWindowUp,
+    /// Focus the window or the workspace below.
+    FocusWindowOrWorkspaceDown,
+    /// Focus the window or the workspace above.
+    FocusWindowOrWorkspaceUp,
+    /// Focus the previously focused window.
+    FocusWindowPrevious,
+    /// Move the focused column to the left.
+    MoveColumnLeft,
+    /// Move the focused column to the right.
+    MoveColumnRight,
+    /// Move the focused column to the start of the workspace.
+    MoveColumnToFirst,
+    /// Move the focused column to the end of the workspace.
+    MoveColumnToLast,
+    /// Move the focused window down in a column.
+    MoveWindowDown,
+    /// Move the focused window up in a column.
+    MoveWindowUp,
+    /// Move the focused window down in a column or to the workspace below.
+    MoveWindowDownOrToWorkspaceDown,
+    /// Move the focused window up in a column or to the workspace above.
+    MoveWindowUpOrToWorkspaceUp,
+    /// Consume or expel the focused window left.
+    ConsumeOrExpelWindowLeft,
+    /// Consume or expel the focused window right.
+    ConsumeOrExpelWindowRight,
+    /// Consume the window to the right into the focused column.
+    ConsumeWindowIntoColumn,
+    /// Expel the focused window from the column.
+    ExpelWindowFromColumn,
+    /// Center the focused column on the screen.
+    CenterColumn,
+    /// Focus the workspace below.
+    FocusWorkspaceDown,
+    /// Focus the workspace above.
+    FocusWorkspaceUp,
+    /// Focus a workspace by index or name.
+    FocusWorkspace {
+        /// Index or name of the workspace to focus.
+        ///
+        /// Indexes start from 1. Prefix with "name:" to refer to a workspace named like a number,
+        /// e.g. "name:1".
+        #[cfg_attr(feature = "clap", arg())]
+        #[cfg_attr(feature = "knuffel", knuffel(argument))]
+        reference: WorkspaceReferenceArg,
+    },
+    /// Focus the previously active workspace.
+    FocusWorkspacePrevious,
+    /// Move the focused window to the workspace below.
+    MoveWindowToWorkspaceDown,
+    /// Move the focused window to the workspace above.
+    MoveWindowToWorkspaceUp,
+    /// Move the focused window to a workspace by index or name.
+    MoveWindowToWorkspace {
+        /// Index or name of the target workspace.
+        ///
+        /// Indexes start from 1. Prefix with "name:" to refer to a workspace named like a number,
+        /// e.g. "name:1".
+        #[cfg_attr(feature = "clap", arg())]
+        #[cfg_attr(feature = "knuffel", knuffel(argument))]
+        reference: WorkspaceReferenceArg,
+    },
+    /// Move the focused column to the workspace below.
+    MoveColumnToWorkspaceDown,
+    /// Move the focused column to the workspace above.
+    MoveColumnToWorkspaceUp,
+    /// Move the focused column to a workspace by index or name.
+    MoveColumnToWorkspace {
+        /// Index or name of the target workspace.
+        ///
+        /// Indexes start from 1. Prefix with "name:" to refer to a workspace named like a number,
+        /// e.g. "name:1".
+        #[cfg_attr(feature = "clap", arg())]
+        #[cfg_attr(feature = "knuffel", knuffel(argument))]
+        reference: WorkspaceReferenceArg,
+    },
+    /// Move the focused workspace down.
+    MoveWorkspaceDown,
+    /// Move the focused workspace up.
+    MoveWorkspaceUp,
+    /// Hide the focused window in a scratchpad.
+    ///
+    /// A window already hidden in that scratchpad is shown in its place.
+    MoveWindowToScratchpad {
+        /// Name of the scratchpad.
+        ///
+        /// Without a name, the default scratchpad is used.
+        #[cfg_attr(feature = "clap", arg())]
+        #[cfg_attr(feature = "knuffel", knuffel(argument, default))]
+        name: Option<String>,
+    },
+    /// Show a scratchpad window floating on the focused workspace, or hide it again.
+    ToggleScratchpad {
+        /// Name of the scratchpad.
+        ///
+        /// Without a name, the default scratchpad is used.
+        #[cfg_attr(feature = "clap", arg())]
+        #[cfg_attr(feature = "knuffel", knuffel(argument, default))]
+        name: Option<String>,
+    },
+    /// Focus the monitor to the left.
+    FocusMonitorLeft,
+    /// Focus the monitor to the right.
+    FocusMonitorRight,
+    /// Focus the monitor below.
+    FocusMonitorDown,
+    /// Focus the monitor above.
+    FocusMonitorUp,
+    /// Move the focused window to the monitor to the left.
+    MoveWindowToMonitorLeft,
+    /// Move the focused window to the monitor to the right.
+    MoveWindowToMonitorRight,
+    /// Move the focused window to the monitor below.
+    MoveWindowToMonitorDown,
+    /// Move the focused window to the monitor above.
+    MoveWindowToMonitorUp,
+    /// Move the focused column to the monitor to the left.
+    MoveColumnToMonitorLeft,
+    /// Move the focused column to the monitor to the right.
+    MoveColumnToMonitorRight,
+    /// Move the focused column to the monitor below.
+    MoveColumnToMonitorDown,
+    /// Move the focused column to the monitor above.
+    MoveColumnToMonitorUp,
+    /// Change the height of the focused window.
+    SetWindowHeight {
+        /// How to change the height.
+        #[cfg_attr(feature = "clap", arg(allow_hyphen_values = true))]
+        #[cfg_attr(feature = "knuffel", knuffel(argument, str))]
+        change: SizeChange,
+    },
+    /// Switch between preset column widths.
+    SwitchPresetColumnWidth,
+    /// Toggle the maximized state of the focused column.
+    MaximizeColumn,
+    /// Toggle the focused column between stacked and tabbed display.
+    ToggleColumnTabbedDisplay,
+    /// Change the width of the focused column.
+    SetColumnWidth {
+        /// How to change the width.
+        #[cfg_attr(feature = "clap", arg(allow_hyphen_values = true))]
+        #[cfg_attr(feature = "knuffel", knuffel(argument, str))]
+        change: SizeChange,
+    },
+    /// Move the focused window between the floating and the tiling layout.
+    ToggleWindowFloating,
+    /// Switch the focus between the floating and the tiling layout.
+    SwitchFocusBetweenFloatingAndTiling,
+    /// Move the focused floating window by the given amount of logical pixels.
+    MoveFloatingWindow {
+        /// Horizontal movement, positive to the right.
+        #[cfg_attr(
+            feature = "clap",
+            arg(short, long, default_value_t = 0, allow_hyphen_values = true)
+        )]
+        #[cfg_attr(feature = "knuffel", knuffel(property, default))]
+        x: i32,
+        /// Vertical movement, positive down.
+        #[cfg_attr(
+            feature = "clap",
+            arg(short, long, default_value_t = 0, allow_hyphen_values = true)
+        )]
+        #[cfg_attr(feature = "knuffel", knuffel(property, default))]
+        y: i32,
+    },
+    /// Switch between keyboard layouts.
+    SwitchLayout {
+        /// Layout to switch to.
+        #[cfg_attr(feature = "clap", arg())]
+        #[cfg_attr(feature = "knuffel", knuffel(argument))]
+        layout: LayoutSwitchTarget,
+    },
+    /// Show the hotkey overlay.
+    ShowHotkeyOverlay,
+    /// Toggle the zoomed-out overview of all workspaces.
+    ToggleOverview,
+    /// Open the window switcher or select the next window in it.
+    WindowSwitcherNext,
+    /// Open the window switcher or select the previous window in it.
+    WindowSwitcherPrevious,
+    /// Focus the window selected in the window switcher and close it.
+    #[serde(skip)]
+    #[cfg_attr(feature = "clap", command(skip))]
+    #[cfg_attr(feature = "knuffel", knuffel(skip))]
+    ConfirmWindowSwitcher,
+    /// Close the window switcher without changing the focus.
+    #[serde(skip)]
+    #[cfg_attr(feature = "clap", command(skip))]
+    #[cfg_attr(feature = "knuffel", knuffel(skip))]
+    CancelWindowSwitcher,
+    /// Move the focused workspace to the monitor to the left.
+    MoveWorkspaceToMonitorLeft,
+    /// Move the focused workspace to the monitor to the right.
+    MoveWorkspaceToMonitorRight,
+    /// Move the focused workspace to the monitor below.
+    MoveWorkspaceToMonitorDown,
+    /// Move the focused workspace to the monitor above.
+    MoveWorkspaceToMonitorUp,
+    /// Toggle a debug tint on windows.
+    ToggleDebugTint,
+}
+
+/// Open toplevel window.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Window {
+    /// Unique ID of the window.
+    ///
+    /// This ID stays the same for the lifetime of the window and matches the IDs in [`Event`]s.
+    pub id: u64,
+    /// Title of the window, if set.
+    pub title: Option<String>,
+    /// Application ID of the window, if set.
+    pub app_id: Option<String>,
+    /// Process ID of the client that created the window, if known.
+    pub pid: Option<i32>,
+    /// Name of the output the window is on.
+    ///
+    /// `None` if no outputs are connected.
+    pub output: Option<String>,
+    /// Index of the window's workspace on its output.
+    ///
+    /// Indexes start from 1.
+    pub workspace_index: usize,
+    /// Index of the window's column on its workspace.
+    ///
+    /// Indexes start from 1. `None` for floating windows.
+    pub column_index: Option<usize>,
+    /// Index of the window within its column.
+    ///
+    /// Indexes start from 1. `None` for floating windows.
+    pub tile_index: Option<usize>,
+    /// Whether the window is in the floating layout.
+    pub is_floating: bool,
+    /// Width and height of the window in logical pixels.
+    pub size: (i32, i32),
+    /// Whether the window is fullscreen.
+    pub is_fullscreen: bool,
+    /// Whether the window has keyboard focus.
+    pub is_focused: bool,
+}
+
+/// Workspace.
+///
+/// Workspaces in niri are dynamic: every output has an empty workspace at the end, and other
+/// workspaces are removed once they become empty and inactive. Named workspaces declared in the
+/// config are the exception: they persist even when empty.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Workspace {
+    /// Index of the workspace on its output.
+    ///
+    /// Indexes start from 1.
+    pub index: usize,
+    /// Name of the workspace, if it was declared in the config.
+    pub name: Option<String>,
+    /// Name of the output the workspace is currently on.
+    ///
+    /// `None` if no outputs are connected.
+    pub output: Option<String>,
+    /// Name of the output the workspace was created on.
+    ///
+    /// This differs from [`Self::output`] when the original output was disconnected and the
+    /// workspace moved to a different output. Once the original output connects again, the
+    /// workspace moves back to it.
+    pub original_output: Option<String>,
+    /// Whether the workspace is the active one on its output.
+    pub is_active: bool,
+    /// Whether the workspace has no windows.
+    pub is_empty: bool,
+    /// Number of windows on the workspace.
+    pub window_count: usize,
+}
+
+/// Compositor event sent to event stream clients.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Event {
+    /// A new output was connected.
+    OutputAdded {
+        /// Name of the output.
+        name: String,
+    },
+    /// An output was disconnected.
+    OutputRemoved {
+        /// Name of the output.
+        name: String,
+    },
+    /// A workspace was activated on an output.
+    WorkspaceActivated {
+        /// Name of the output.
+        output: String,
+        /// Index of the workspace on the output.
+        ///
+        /// Indexes start from 1.
+        index: usize,
+    },
+    /// A new window was opened.
+    WindowOpened {
+        /// Unique ID of the window.
+        id: u64,
+        /// Title of the window, if set.
+        title: Option<String>,
+        /// Application ID of the window, if set.
+        app_id: Option<String>,
+    },
+    /// A window was closed.
+    WindowClosed {
+        /// Unique ID of the window.
+        id: u64,
+    },
+    /// The title of a window changed.
+    WindowTitleChanged {
+        /// Unique ID of the window.
+        id: u64,
+        /// New title of the window.
+        title: Option<String>,
+    },
+    /// Keyboard focus moved to a different window.
+    WindowFocusChanged {
+        /// Unique ID of the newly focused window.
+        ///
+        /// `None` if no window has keyboard focus, for example when a layer-shell surface is
+        /// focused.
+        id: Option<u64>,
+    },
+    /// The active keyboard layout changed.
+    KeyboardLayoutSwitched {
+        /// Index of the layout in the keymap.
+        index: u32,
+    },
+    /// The config was successfully reloaded.
+    ConfigReloaded,
+}
+
+/// Change in window or column size.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SizeChange {
+    /// Set the size in logical pixels.
+    SetFixed(i32),
+    /// Set the size as a proportion of the working area.
+    SetProportion(f64),
+    /// Add or subtract to the current size in logical pixels.
+    AdjustFixed(i32),
+    /// Add or subtract to the current size as a proportion of the working area.
+    AdjustProportion(f64),
+}
+
+/// Workspace reference (index or name) to operate on.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceReferenceArg {
+    /// Index of the workspace on the active output.
+    ///
+    /// Indexes start from 1.
+    Index(u8),
+    /// Name of the workspace, as declared in the config.
+    Name(String),
+}
+
+/// Layout to switch to.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "knuffel", derive(knuffel::DecodeScalar))]
+pub enum LayoutSwitchTarget {
+    /// The next configured layout.
+    Next,
+    /// The previous configured layout.
+    Prev,
+}
+
+/// Output actions that niri can perform.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::Subcommand))]
+pub enum OutputAction {
+    /// Turn off the output.
+    Off,
+    /// Turn on the output.
+    On,
+    /// Set the output mode.
+    Mode {
+        /// Mode to set, or "auto" for automatic selection.
+        ///
+        /// Run `niri msg outputs` to see the available modes.
+        #[cfg_attr(feature = "clap", arg())]
+        mode: ModeToSet,
+    },
+    /// Set the output scale.
+    Scale {
+        /// Scale factor to set.
+        #[cfg_attr(feature = "clap", arg())]
+        scale: f64,
+    },
+    /// Set the output transform.
+    Transform {
+        /// Transform to set, counter-clockwise.
+        #[cfg_attr(feature = "clap", arg())]
+        transform: Transform,
+    },
+    /// Set the output position.
+    Position {
+        /// Position to set, or "auto" for automatic selection.
+        #[cfg_attr(feature = "clap", command(subcommand))]
+        position: PositionToSet,
+    },
+}
+
+/// Output mode to set.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ModeToSet {
+    /// Niri will pick the mode automatically.
+    Automatic,
+    /// Specific mode.
+    Specific(ConfiguredMode),
+}
+
+/// Output mode as set in the config file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ConfiguredMode {
+    /// Width in physical pixels.
+    pub width: u16,
+    /// Height in physical pixels.
+    pub height: u16,
+    /// Refresh rate.
+    pub refresh: Option<f64>,
+}
+
+/// Output position to set.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::Subcommand))]
+#[cfg_attr(feature = "clap", command(subcommand_value_name = "POSITION"))]
+#[cfg_attr(feature = "clap", command(subcommand_help_heading = "Position Values"))]
+pub enum PositionToSet {
+    /// Position the output automatically.
+    #[cfg_attr(feature = "clap", command(name = "auto"))]
+    Automatic,
+    /// Set a specific position.
+    #[cfg_attr(feature = "clap", command(name = "set"))]
+    Specific(ConfiguredPosition),
+}
+
+/// Output position as set in the config file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+pub struct ConfiguredPosition {
+    /// Logical X position.
+    #[cfg_attr(feature = "clap", arg(allow_hyphen_values = true))]
+    pub x: i32,
+    /// Logical Y position.
+    #[cfg_attr(feature = "clap", arg(allow_hyphen_values = true))]
+    pub y: i32,
+}
+
+/// Output transform, which goes counter-clockwise.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    /// Untransformed.
+    Normal,
+    /// Rotated by 90°.
+    #[serde(rename = "90")]
+    _90,
+    /// Rotated by 180°.
+    #[serde(rename = "180")]
+    _180,
+    /// Rotated by 270°.
+    #[serde(rename = "270")]
+    _270,
+    /// Flipped horizontally.
+    Flipped,
+    /// Rotated by 90° and flipped horizontally.
+    Flipped90,
+    /// Flipped vertically.
+    Flipped180,
+    /// Rotated by 270° and flipped horizontally.
+    Flipped270,
+}
+
+/// Output configuration change result.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputConfigChanged {
+    /// The target output was connected and the change was applied.
+    Applied,
+    /// The target output was not found, the change will be applied when it is connected.
+    OutputWasMissing,
+    /// The requested scale is fractional, which is not supported, so nothing was changed.
+    FractionalScale,
 }
 
 /// Connected output.
@@ -53,3 +626,180 @@ pub struct Mode {
     /// Refresh rate in millihertz.
     pub refresh_rate: u32,
 }
+
+impl FromStr for SizeChange {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('%') {
+            Some((value, empty)) => {
+                if !empty.is_empty() {
+                    return Err("trailing characters after '%' are not allowed");
+                }
+
+                match value.bytes().next() {
+                    Some(b'-' | b'+') => {
+                        let value = value.parse().map_err(|_| "error parsing value")?;
+                        Ok(Self::AdjustProportion(value))
+                    }
+                    Some(_) => {
+                        let value = value.parse().map_err(|_| "error parsing value")?;
+                        Ok(Self::SetProportion(value))
+                    }
+                    None => Err("value is missing"),
+                }
+            }
+            None => {
+                let value = s;
+                match value.bytes().next() {
+                    Some(b'-' | b'+') => {
+                        let value = value.parse().map_err(|_| "error parsing value")?;
+                        Ok(Self::AdjustFixed(value))
+                    }
+                    Some(_) => {
+                        let value = value.parse().map_err(|_| "error parsing value")?;
+                        Ok(Self::SetFixed(value))
+                    }
+                    None => Err("value is missing"),
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for WorkspaceReferenceArg {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("workspace reference cannot be empty");
+        }
+
+        // The prefix allows referring to workspaces with numeric names.
+        if let Some(name) = s.strip_prefix("name:") {
+            if name.is_empty() {
+                return Err("workspace name cannot be empty");
+            }
+            return Ok(Self::Name(name.to_owned()));
+        }
+
+        match s.parse() {
+            Ok(index) => Ok(Self::Index(index)),
+            Err(_) => Ok(Self::Name(s.to_owned())),
+        }
+    }
+}
+
+// In the config, numbers are indexes and strings are names.
+#[cfg(feature = "knuffel")]
+impl<S: knuffel::traits::ErrorSpan> knuffel::DecodeScalar<S> for WorkspaceReferenceArg {
+    fn type_check(
+        type_name: &Option<knuffel::span::Spanned<knuffel::ast::TypeName, S>>,
+        ctx: &mut knuffel::decode::Context<S>,
+    ) {
+        if let Some(type_name) = &type_name {
+            ctx.emit_error(knuffel::errors::DecodeError::unexpected(
+                type_name,
+                "type name",
+                "no type name expected for this node",
+            ));
+        }
+    }
+
+    fn raw_decode(
+        val: &knuffel::span::Spanned<knuffel::ast::Literal, S>,
+        ctx: &mut knuffel::decode::Context<S>,
+    ) -> Result<Self, knuffel::errors::DecodeError<S>> {
+        match &**val {
+            knuffel::ast::Literal::String(s) => Ok(Self::Name(s.to_string())),
+            knuffel::ast::Literal::Int(value) => match value.try_into() {
+                Ok(index) => Ok(Self::Index(index)),
+                Err(err) => {
+                    ctx.emit_error(knuffel::errors::DecodeError::conversion(val, err));
+                    Ok(Self::Index(0))
+                }
+            },
+            _ => {
+                ctx.emit_error(knuffel::errors::DecodeError::unsupported(
+                    val,
+                    "expected a workspace index or name",
+                ));
+                Ok(Self::Index(0))
+            }
+        }
+    }
+}
+
+impl FromStr for LayoutSwitchTarget {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "next" => Ok(Self::Next),
+            "prev" => Ok(Self::Prev),
+            _ => Err(r#"invalid layout action, can be "next" or "prev""#),
+        }
+    }
+}
+
+impl FromStr for ModeToSet {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Self::Automatic);
+        }
+
+        let mode = s.parse()?;
+        Ok(Self::Specific(mode))
+    }
+}
+
+impl FromStr for ConfiguredMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((width, rest)) = s.split_once('x') else {
+            return Err("no 'x' separator found");
+        };
+
+        let (height, refresh) = match rest.split_once('@') {
+            Some((height, refresh)) => (height, Some(refresh)),
+            None => (rest, None),
+        };
+
+        let width = width.parse().map_err(|_| "error parsing width")?;
+        let height = height.parse().map_err(|_| "error parsing height")?;
+        let refresh = refresh
+            .map(str::parse)
+            .transpose()
+            .map_err(|_| "error parsing refresh rate")?;
+
+        Ok(Self {
+            width,
+            height,
+            refresh,
+        })
+    }
+}
+
+impl FromStr for Transform {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "normal" => Ok(Self::Normal),
+            "90" => Ok(Self::_90),
+            "180" => Ok(Self::_180),
+            "270" => Ok(Self::_270),
+            "flipped" => Ok(Self::Flipped),
+            "flipped-90" => Ok(Self::Flipped90),
+            "flipped-180" => Ok(Self::Flipped180),
+            "flipped-270" => Ok(Self::Flipped270),
+            _ => Err(concat!(
+                r#"invalid transform, can be "90", "180", "270", "#,
+                r#""flipped", "flipped-90", "flipped-180" or "flipped-270""#
+            )),
+        }
+    }
+}
@@ -1,32 +1,119 @@
 use std::time::Duration;
 
-use keyframe::functions::EaseOutCubic;
+use keyframe::functions::{
+    EaseInOutCubic, EaseOutCubic, EaseOutQuad, EaseOutQuart, EaseOutQuint, Linear,
+};
 use keyframe::EasingFunction;
+use niri_config::{AnimationCurve, Config};
 use portable_atomic::{AtomicF64, Ordering};
 
 use crate::utils::get_monotonic_time;
 
+/// Multiplier for all animation durations, zero when animations are disabled.
 pub static ANIMATION_SLOWDOWN: AtomicF64 = AtomicF64::new(1.);
 
+/// Updates the animation slowdown from the config.
+pub fn set_slowdown(config: &Config) {
+    let slowdown = if config.animations.off {
+        0.
+    } else {
+        config.debug.animation_slowdown
+    };
+    ANIMATION_SLOWDOWN.store(slowdown, Ordering::Relaxed);
+}
+
+/// Longest that a spring animation is allowed to run.
+const MAX_SPRING_DURATION: Duration = Duration::from_secs(10);
+
 #[derive(Debug)]
 pub struct Animation {
     from: f64,
     to: f64,
     duration: Duration,
+    kind: Kind,
     start_time: Duration,
     current_time: Duration,
 }
 
+#[derive(Debug)]
+enum Kind {
+    Easing(AnimationCurve),
+    Spring {
+        spring: Spring,
+        /// Duration of the spring motion before the slowdown is applied.
+        duration: Duration,
+    },
+}
+
+/// Damped harmonic oscillator moving from one value to another.
+#[derive(Debug, Clone, Copy)]
+pub struct Spring {
+    pub from: f64,
+    pub to: f64,
+    /// Velocity at the start, in units per second.
+    pub initial_velocity: f64,
+    pub params: SpringParams,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpringParams {
+    pub damping: f64,
+    pub mass: f64,
+    pub stiffness: f64,
+    pub epsilon: f64,
+}
+
 impl Animation {
-    pub fn new(from: f64, to: f64, over: Duration) -> Self {
+    /// Creates a new animation.
+    ///
+    /// The initial velocity, in units per second, is only used by spring animations.
+    pub fn new(from: f64, to: f64, initial_velocity: f64, config: niri_config::Animation) -> Self {
         // FIXME: ideally we shouldn't use current time here because animations started within the
         // same frame cycle should have the same start time to be synchronized.
         let now = get_monotonic_time();
 
+        let (duration, kind) = match config.spring {
+            Some(params) => {
+                // The spring runs from 0 to 1 and gets scaled to the actual distance, so that
+                // epsilon is a fraction of the distance rather than an absolute value.
+                let distance = to - from;
+                let (target, initial_velocity) = if distance.abs() > f64::EPSILON {
+                    (1., initial_velocity / distance)
+                } else {
+                    // Nothing to move, so the spring is at rest.
+                    (0., 0.)
+                };
+
+                let spring = Spring {
+                    from: 0.,
+                    to: target,
+                    initial_velocity,
+                    params: SpringParams::new(
+                        params.damping_ratio,
+                        f64::from(params.stiffness),
+                        params.epsilon,
+                    ),
+                };
+                let duration = spring.duration();
+                (duration, Kind::Spring { spring, duration })
+            }
+            None => {
+                let duration = Duration::from_millis(u64::from(config.duration_ms));
+                (duration, Kind::Easing(config.curve))
+            }
+        };
+
+        let duration = if config.off {
+            Duration::ZERO
+        } else {
+            duration.mul_f64(ANIMATION_SLOWDOWN.load(Ordering::Relaxed))
+        };
+
         Self {
             from,
             to,
-            duration: over.mul_f64(ANIMATION_SLOWDOWN.load(Ordering::Relaxed)),
+            duration,
+            kind,
             start_time: now,
             current_time: now,
         }
@@ -41,10 +128,34 @@ impl Animation {
     }
 
     pub fn value(&self) -> f64 {
-        let passed = (self.current_time - self.start_time).as_secs_f64();
+        self.value_at(self.current_time)
+    }
+
+    /// Returns the current rate of change of the value, in units per second.
+    pub fn velocity(&self) -> f64 {
+        if self.is_done() {
+            return 0.;
+        }
+
+        let dt = Duration::from_millis(1);
+        (self.value_at(self.current_time + dt) - self.value()) / dt.as_secs_f64()
+    }
+
+    fn value_at(&self, time: Duration) -> f64 {
+        if time >= self.start_time + self.duration {
+            return self.to;
+        }
+
+        let passed = time.saturating_sub(self.start_time).as_secs_f64();
         let total = self.duration.as_secs_f64();
         let x = (passed / total).clamp(0., 1.);
-        EaseOutCubic.y(x) * (self.to - self.from) + self.from
+
+        match &self.kind {
+            Kind::Easing(curve) => curve_y(*curve, x) * (self.to - self.from) + self.from,
+            Kind::Spring { spring, duration } => {
+                spring.value_at(duration.mul_f64(x)) * (self.to - self.from) + self.from
+            }
+        }
     }
 
     pub fn to(&self) -> f64 {
@@ -56,3 +167,264 @@ impl Animation {
         self.from
     }
 }
+
+impl SpringParams {
+    pub fn new(damping_ratio: f64, stiffness: f64, epsilon: f64) -> Self {
+        // Keep the spring from oscillating forever or taking no time at all.
+        let damping_ratio = damping_ratio.max(0.01);
+        let stiffness = stiffness.max(1.);
+        let epsilon = epsilon.clamp(f64::EPSILON, 1.);
+
+        let mass = 1.;
+        let critical_damping = 2. * (mass * stiffness).sqrt();
+        let damping = damping_ratio * critical_damping;
+
+        Self {
+            damping,
+            mass,
+            stiffness,
+            epsilon,
+        }
+    }
+}
+
+impl Spring {
+    pub fn value_at(&self, t: Duration) -> f64 {
+        self.oscillate(t.as_secs_f64())
+    }
+
+    /// Returns how long it takes for the spring to settle within epsilon of the target.
+    ///
+    /// Epsilon is in the same units as the values.
+    pub fn duration(&self) -> Duration {
+        const DELTA: f64 = 0.001;
+
+        if (self.to - self.from).abs() <= f64::EPSILON && self.initial_velocity == 0. {
+            return Duration::ZERO;
+        }
+
+        let beta = self.params.damping / (2. * self.params.mass);
+        let omega0 = (self.params.stiffness / self.params.mass).sqrt();
+
+        // Underdamped: the oscillation stays within an envelope whose settling time has a closed
+        // form.
+        if !is_critically_damped(beta, omega0) && beta < omega0 {
+            let omega1 = (omega0 * omega0 - beta * beta).sqrt();
+            let x0 = self.from - self.to;
+            let amplitude = x0.hypot((beta * x0 + self.initial_velocity) / omega1);
+            let t = (amplitude / self.params.epsilon).ln() / beta;
+            return Duration::from_secs_f64(t.clamp(0., MAX_SPRING_DURATION.as_secs_f64()));
+        }
+
+        // Critically damped and overdamped: find the time with Newton's method, starting from
+        // where a unit envelope drops below epsilon.
+        let mut x0 = -self.params.epsilon.ln() / beta;
+        let mut y0 = self.oscillate(x0);
+        let mut x1 = x0;
+        for _ in 0..1000 {
+            if (self.to - y0).abs() <= self.params.epsilon {
+                break;
+            }
+
+            let m = (self.oscillate(x0 + DELTA) - y0) / DELTA;
+            x1 = (self.to - y0 + m * x0) / m;
+            if !x1.is_finite() {
+                x1 = x0;
+                break;
+            }
+
+            x0 = x1;
+            y0 = self.oscillate(x0);
+        }
+
+        Duration::from_secs_f64(x1.clamp(0., MAX_SPRING_DURATION.as_secs_f64()))
+    }
+
+    fn oscillate(&self, t: f64) -> f64 {
+        let b = self.params.damping;
+        let m = self.params.mass;
+        let k = self.params.stiffness;
+        let v0 = self.initial_velocity;
+
+        let beta = b / (2. * m);
+        let omega0 = (k / m).sqrt();
+
+        let x0 = self.from - self.to;
+        let envelope = (-beta * t).exp();
+
+        // Solutions of m*x'' + b*x' + k*x = 0 with x(0) = x0 and x'(0) = v0.
+        //
+        // Critically damped.
+        if is_critically_damped(beta, omega0) {
+            return self.to + envelope * (x0 + (beta * x0 + v0) * t);
+        }
+
+        // Underdamped.
+        if beta < omega0 {
+            let omega1 = (omega0 * omega0 - beta * beta).sqrt();
+            return self.to
+                + envelope
+                    * (x0 * (omega1 * t).cos() + ((beta * x0 + v0) / omega1) * (omega1 * t).sin());
+        }
+
+        // Overdamped.
+        let omega2 = (beta * beta - omega0 * omega0).sqrt();
+        self.to
+            + envelope
+                * (x0 * (omega2 * t).cosh() + ((beta * x0 + v0) / omega2) * (omega2 * t).sinh())
+    }
+}
+
+/// Returns whether the damping is critical, with a tolerance since the floats rarely match exactly.
+fn is_critically_damped(beta: f64, omega0: f64) -> bool {
+    (beta - omega0).abs() <= omega0 * 1e-6
+}
+
+fn curve_y(curve: AnimationCurve, x: f64) -> f64 {
+    match curve {
+        AnimationCurve::Linear => Linear.y(x),
+        AnimationCurve::EaseOutQuad => EaseOutQuad.y(x),
+        AnimationCurve::EaseOutCubic => EaseOutCubic.y(x),
+        AnimationCurve::EaseInOutCubic => EaseInOutCubic.y(x),
+        AnimationCurve::EaseOutQuart => EaseOutQuart.y(x),
+        AnimationCurve::EaseOutQuint => EaseOutQuint.y(x),
+        AnimationCurve::CubicBezier { x1, y1, x2, y2 } => cubic_bezier_y(x1, y1, x2, y2, x),
+    }
+}
+
+/// Evaluates a cubic Bézier curve from (0, 0) to (1, 1) at the given X.
+fn cubic_bezier_y(x1: f64, y1: f64, x2: f64, y2: f64, x: f64) -> f64 {
+    let bezier = |p1: f64, p2: f64, t: f64| {
+        let u = 1. - t;
+        3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t
+    };
+
+    // X is monotonic in t since x1 and x2 are within [0, 1], so bisection always converges.
+    let (mut lo, mut hi) = (0., 1.);
+    let mut t = x;
+    for _ in 0..64 {
+        let value = bezier(x1, x2, t);
+        if (value - x).abs() < 1e-7 {
+            break;
+        }
+
+        if value < x {
+            lo = t;
+        } else {
+            hi = t;
+        }
+        t = (lo + hi) / 2.;
+    }
+
+    bezier(y1, y2, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cubic_bezier() {
+        assert_eq!(cubic_bezier_y(0.25, 0.1, 0.25, 1., 0.), 0.);
+        assert_eq!(cubic_bezier_y(0.25, 0.1, 0.25, 1., 1.), 1.);
+
+        // A straight line is linear.
+        for x in [0.1, 0.3, 0.5, 0.9] {
+            assert!((cubic_bezier_y(0.3, 0.3, 0.7, 0.7, x) - x).abs() < 1e-6);
+        }
+
+        // CSS "ease" is past the midpoint at half time.
+        assert!(cubic_bezier_y(0.25, 0.1, 0.25, 1., 0.5) > 0.75);
+    }
+
+    fn spring(damping_ratio: f64, initial_velocity: f64) -> Spring {
+        Spring {
+            from: 0.,
+            to: 1.,
+            initial_velocity,
+            params: SpringParams::new(damping_ratio, 800., 0.0001),
+        }
+    }
+
+    #[test]
+    fn spring_settles_at_target() {
+        for damping_ratio in [0.5, 1., 2.] {
+            let spring = spring(damping_ratio, 0.);
+            let duration = spring.duration();
+            assert!(Duration::ZERO < duration && duration < MAX_SPRING_DURATION);
+
+            let end = spring.value_at(duration);
+            assert!((end - 1.).abs() < 0.01, "{damping_ratio}: {end}");
+        }
+    }
+
+    #[test]
+    fn spring_settles_within_epsilon() {
+        for damping_ratio in [0.5, 1., 2.] {
+            for initial_velocity in [0., 5., -5.] {
+                let spring = spring(damping_ratio, initial_velocity);
+                let duration = spring.duration();
+
+                let end = spring.value_at(duration);
+                assert!(
+                    (end - 1.).abs() <= 0.0001,
+                    "{damping_ratio}, {initial_velocity}: {end}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn spring_animation_is_scaled_to_distance() {
+        let config = niri_config::Animation {
+            spring: Some(niri_config::SpringParams {
+                damping_ratio: 1.,
+                stiffness: 800,
+                epsilon: 0.0001,
+            }),
+            ..Default::default()
+        };
+        let mut anim = Animation::new(0., 1920., 1000., config);
+
+        // Epsilon is relative, so a long movement still ends right at the target.
+        let duration = anim.duration;
+        anim.set_current_time(anim.start_time + duration.mul_f64(0.999));
+        assert!((anim.value() - 1920.).abs() < 1., "{}", anim.value());
+
+        // The initial velocity is in the units of the values.
+        let dt = Duration::from_micros(1);
+        let velocity = anim.value_at(anim.start_time + dt) / dt.as_secs_f64();
+        assert!((velocity - 1000.).abs() < 10., "{velocity}");
+    }
+
+    #[test]
+    fn critically_damped_spring_does_not_overshoot() {
+        let spring = spring(1., 0.);
+        let duration = spring.duration();
+
+        for i in 0..=100 {
+            let value = spring.value_at(duration.mul_f64(i as f64 / 100.));
+            assert!((0. ..=1.).contains(&value), "{value}");
+        }
+    }
+
+    #[test]
+    fn spring_starts_with_initial_velocity() {
+        let spring = spring(1., -5.);
+
+        let dt = 0.0001;
+        let velocity = spring.value_at(Duration::from_secs_f64(dt)) / dt;
+        assert!((velocity + 5.).abs() < 0.1, "{velocity}");
+    }
+
+    #[test]
+    fn spring_at_rest_takes_no_time() {
+        let spring = Spring {
+            from: 1.,
+            to: 1.,
+            initial_velocity: 0.,
+            params: SpringParams::new(1., 800., 0.0001),
+        };
+        assert_eq!(spring.duration(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,716 @@
+//! Headless backend for tests and CI.
+//!
+//! Renders offscreen into pixman images on the CPU and drives the frame clock from timers, so niri
+//! can run with real Wayland clients without a GPU or any display hardware.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::mem;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{ensure, Context};
+use smithay::backend::allocator::dmabuf::Dmabuf;
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::damage::OutputDamageTracker;
+use smithay::backend::renderer::gles::GlesRenderer;
+use smithay::backend::renderer::pixman::PixmanRenderer;
+use smithay::backend::renderer::{Bind, DebugFlags, ExportMem, ImportDma, Offscreen, Renderer};
+use smithay::output::{Mode, Output, PhysicalProperties, Subpixel};
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::reexports::pixman::Image;
+use smithay::reexports::wayland_protocols::wp::presentation_time::server::wp_presentation_feedback;
+use smithay::utils::{Buffer, Physical, Rectangle, Size, Transform};
+
+use super::RenderResult;
+use crate::frame_clock::FrameClock;
+use crate::niri::{Niri, RedrawState};
+use crate::utils::get_monotonic_time;
+
+pub struct Headless {
+    renderer: PixmanRenderer,
+    outputs: HashMap<String, HeadlessOutput>,
+    ipc_outputs: Rc<RefCell<HashMap<String, niri_ipc::Output>>>,
+    enabled_outputs: Arc<Mutex<HashMap<String, Output>>>,
+}
+
+struct HeadlessOutput {
+    output: Output,
+    damage_tracker: OutputDamageTracker,
+    /// Mode given on the command line, used when the config doesn't set one.
+    default_mode: Mode,
+    /// Copy of the last frame with damage, for inspecting what was drawn.
+    last_frame: Option<HeadlessFrame>,
+}
+
+/// Frame rendered to a headless output.
+pub struct HeadlessFrame {
+    /// Size of the frame in physical pixels.
+    pub size: Size<i32, Physical>,
+    /// Pixels in the [`Fourcc::Abgr8888`] format: R, G, B and A bytes, row by row, no padding.
+    pub pixels: Vec<u8>,
+}
+
+impl Headless {
+    /// Creates a headless backend with virtual outputs of the given modes.
+    ///
+    /// Outputs are named `headless-1`, `headless-2` and so on.
+    pub fn new(modes: &[niri_ipc::ConfiguredMode]) -> anyhow::Result<Self> {
+        let renderer = PixmanRenderer::new().context("error creating renderer")?;
+
+        let mut outputs = HashMap::new();
+        let mut ipc_outputs = HashMap::new();
+
+        for (idx, configured) in modes.iter().enumerate() {
+            let refresh = configured.refresh.unwrap_or(60.);
+            ensure!(
+                refresh > 1.,
+                "headless output refresh rate must be higher than 1 Hz"
+            );
+
+            let name = format!("headless-{}", idx + 1);
+            let output = Output::new(
+                name.clone(),
+                PhysicalProperties {
+                    size: (0, 0).into(),
+                    subpixel: Subpixel::Unknown,
+                    make: "niri".into(),
+                    model: "Headless".into(),
+                },
+            );
+
+            let mode = Mode {
+                size: (i32::from(configured.width), i32::from(configured.height)).into(),
+                refresh: (refresh * 1000.).round() as i32,
+            };
+            output.change_current_state(Some(mode), None, None, None);
+            output.set_preferred(mode);
+
+            // The output is enabled, and the IPC info filled in, once we apply the config.
+            let physical_properties = output.physical_properties();
+            ipc_outputs.insert(
+                name.clone(),
+                niri_ipc::Output {
+                    name: name.clone(),
+                    make: physical_properties.make,
+                    model: physical_properties.model,
+                    physical_size: None,
+                    modes: vec![ipc_mode(mode)],
+                    current_mode: None,
+                },
+            );
+
+            let damage_tracker = OutputDamageTracker::from_output(&output);
+            outputs.insert(
+                name,
+                HeadlessOutput {
+                    output,
+                    damage_tracker,
+                    default_mode: mode,
+                    last_frame: None,
+                },
+            );
+        }
+
+        Ok(Self {
+            renderer,
+            outputs,
+            ipc_outputs: Rc::new(RefCell::new(ipc_outputs)),
+            enabled_outputs: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn init(&mut self, niri: &mut Niri) {
+        self.on_output_config_changed(niri);
+    }
+
+    pub fn seat_name(&self) -> String {
+        "headless".to_owned()
+    }
+
+    pub fn with_primary_renderer<T>(
+        &mut self,
+        _f: impl FnOnce(&mut GlesRenderer) -> T,
+    ) -> Option<T> {
+        // There's no GLES renderer here, so features that need one (screenshots, screencasting)
+        // are unavailable. Use last_frame() to see what was drawn instead.
+        None
+    }
+
+    /// Returns the last frame that was rendered to the output with damage.
+    pub fn last_frame(&self, output: &Output) -> Option<&HeadlessFrame> {
+        self.outputs.get(&output.name())?.last_frame.as_ref()
+    }
+
+    pub fn render(
+        &mut self,
+        niri: &mut Niri,
+        output: &Output,
+        target_presentation_time: Duration,
+    ) -> RenderResult {
+        let _span = tracy_client::span!("Headless::render");
+
+        let Some(headless_output) = self.outputs.get_mut(&output.name()) else {
+            error!("missing headless output {}", output.name());
+            return RenderResult::Skipped;
+        };
+
+        // Render the elements.
+        let elements = niri.render::<PixmanRenderer>(&mut self.renderer, output, true);
+
+        // Render them into an offscreen image. Binding takes ownership of the image, so we can't
+        // keep it around between frames and have to redraw everything every time.
+        let mode = output.current_mode().unwrap();
+        let size = mode.size.to_logical(1).to_buffer(1, Transform::Normal);
+        let buffer: Image<'static, 'static> =
+            match self.renderer.create_buffer(Fourcc::Abgr8888, size) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    warn!("error creating offscreen buffer: {err:?}");
+                    return RenderResult::Skipped;
+                }
+            };
+
+        if let Err(err) = self.renderer.bind(buffer) {
+            warn!("error binding offscreen buffer: {err:?}");
+            return RenderResult::Skipped;
+        }
+
+        let res = match headless_output.damage_tracker.render_output(
+            &mut self.renderer,
+            0,
+            &elements,
+            [0.; 4],
+        ) {
+            Ok(res) => res,
+            Err(err) => {
+                warn!("error rendering frame: {err:?}");
+                return RenderResult::Skipped;
+            }
+        };
+
+        niri.update_primary_scanout_output(output, &res.states);
+
+        let rv = if res.damage.is_some() {
+            match download(&mut self.renderer, size) {
+                Ok(pixels) => {
+                    headless_output.last_frame = Some(HeadlessFrame {
+                        size: mode.size,
+                        pixels,
+                    });
+                }
+                Err(err) => warn!("error downloading frame: {err:?}"),
+            }
+
+            let mut presentation_feedbacks = niri.take_presentation_feedbacks(output, &res.states);
+            presentation_feedbacks.presented::<_, smithay::utils::Monotonic>(
+                target_presentation_time,
+                refresh_interval(mode),
+                0,
+                wp_presentation_feedback::Kind::empty(),
+            );
+
+            RenderResult::Submitted
+        } else {
+            RenderResult::NoDamage
+        };
+
+        // Pretend that the frame was scanned out and wait for the next virtual VBlank.
+        queue_vblank_timer(niri, output.clone(), target_presentation_time);
+
+        rv
+    }
+
+    pub fn toggle_debug_tint(&mut self) {
+        let renderer = &mut self.renderer;
+        renderer.set_debug_flags(renderer.debug_flags() ^ DebugFlags::TINT);
+    }
+
+    pub fn import_dmabuf(&mut self, dmabuf: &Dmabuf) -> bool {
+        match self.renderer.import_dmabuf(dmabuf, None) {
+            Ok(_texture) => true,
+            Err(err) => {
+                debug!("error importing dmabuf: {err:?}");
+                false
+            }
+        }
+    }
+
+    pub fn ipc_outputs(&self) -> Rc<RefCell<HashMap<String, niri_ipc::Output>>> {
+        self.ipc_outputs.clone()
+    }
+
+    pub fn enabled_outputs(&self) -> Arc<Mutex<HashMap<String, Output>>> {
+        self.enabled_outputs.clone()
+    }
+
+    pub fn on_output_config_changed(&mut self, niri: &mut Niri) {
+        let _span = tracy_client::span!("Headless::on_output_config_changed");
+
+        // Go in a fixed order so that outputs get added in a predictable way.
+        let mut names = self.outputs.keys().cloned().collect::<Vec<_>>();
+        names.sort_unstable();
+
+        for name in names {
+            let headless_output = &self.outputs[&name];
+            let output = headless_output.output.clone();
+            let is_enabled = niri.output_state.contains_key(&output);
+
+            let config = niri
+                .config
+                .borrow()
+                .outputs
+                .iter()
+                .find(|o| o.name == name)
+                .cloned()
+                .unwrap_or_default();
+
+            if config.off {
+                if is_enabled {
+                    niri.remove_output(&output);
+                    self.enabled_outputs.lock().unwrap().remove(&name);
+                    if let Some(ipc_output) = self.ipc_outputs.borrow_mut().get_mut(&name) {
+                        ipc_output.current_mode = None;
+                    }
+                }
+                continue;
+            }
+
+            // Virtual outputs support any mode, so just use the configured one.
+            let mode = match config.mode {
+                Some(target) if target.refresh.unwrap_or(60.) > 1. => Mode {
+                    size: (i32::from(target.width), i32::from(target.height)).into(),
+                    refresh: (target.refresh.unwrap_or(60.) * 1000.).round() as i32,
+                },
+                Some(_) => {
+                    warn!(
+                        "output {name:?}: configured refresh rate must be higher than 1 Hz, \
+                         falling back to the default mode"
+                    );
+                    headless_output.default_mode
+                }
+                None => headless_output.default_mode,
+            };
+
+            let mode_changed = output.current_mode() != Some(mode);
+            if mode_changed {
+                debug!("output {name:?}: picking mode: {mode:?}");
+                output.change_current_state(Some(mode), None, None, None);
+                output.set_preferred(mode);
+            }
+
+            if let Some(ipc_output) = self.ipc_outputs.borrow_mut().get_mut(&name) {
+                ipc_output.modes = vec![ipc_mode(mode)];
+                ipc_output.current_mode = Some(0);
+            }
+
+            if !is_enabled {
+                niri.add_output(output.clone(), Some(refresh_interval(mode)));
+                self.enabled_outputs.lock().unwrap().insert(name, output);
+            } else if mode_changed {
+                let output_state = niri.output_state.get_mut(&output).unwrap();
+                output_state.frame_clock = FrameClock::new(Some(refresh_interval(mode)));
+                niri.output_resized(output);
+            }
+        }
+    }
+}
+
+/// Copies the pixels of the currently bound buffer.
+fn download(renderer: &mut PixmanRenderer, size: Size<i32, Buffer>) -> anyhow::Result<Vec<u8>> {
+    let mapping = renderer
+        .copy_framebuffer(Rectangle::from_loc_and_size((0, 0), size), Fourcc::Abgr8888)
+        .context("error copying framebuffer")?;
+    let pixels = renderer
+        .map_texture(&mapping)
+        .context("error mapping texture")?;
+    Ok(pixels.to_vec())
+}
+
+fn refresh_interval(mode: Mode) -> Duration {
+    Duration::from_secs_f64(1_000f64 / mode.refresh as f64)
+}
+
+fn ipc_mode(mode: Mode) -> niri_ipc::Mode {
+    niri_ipc::Mode {
+        width: mode.size.w as u16,
+        height: mode.size.h as u16,
+        refresh_rate: mode.refresh as u32,
+    }
+}
+
+fn queue_vblank_timer(niri: &mut Niri, output: Output, target_presentation_time: Duration) {
+    let output_state = niri.output_state.get_mut(&output).unwrap();
+    let new_state = RedrawState::WaitingForVBlank {
+        redraw_needed: false,
+    };
+    match mem::replace(&mut output_state.redraw_state, new_state) {
+        RedrawState::Idle => unreachable!(),
+        RedrawState::Queued(_) => (),
+        RedrawState::WaitingForVBlank { .. } => unreachable!(),
+        RedrawState::WaitingForEstimatedVBlank(_) => unreachable!(),
+        RedrawState::WaitingForEstimatedVBlankAndQueued(_) => unreachable!(),
+    }
+
+    let now = get_monotonic_time();
+    let timer = Timer::from_duration(target_presentation_time.saturating_sub(now));
+    niri.event_loop
+        .insert_source(timer, move |_, _, state| {
+            on_vblank(&mut state.niri, output.clone(), target_presentation_time);
+            TimeoutAction::Drop
+        })
+        .unwrap();
+}
+
+fn on_vblank(niri: &mut Niri, output: Output, presentation_time: Duration) {
+    let _span = tracy_client::span!("Headless::on_vblank");
+
+    // The output could have been removed while we were waiting.
+    let Some(output_state) = niri.output_state.get_mut(&output) else {
+        return;
+    };
+
+    // It could also have been disabled and enabled again, in which case its new state isn't
+    // waiting for this VBlank.
+    let RedrawState::WaitingForVBlank { redraw_needed } = output_state.redraw_state else {
+        return;
+    };
+    output_state.redraw_state = RedrawState::Idle;
+
+    output_state.frame_clock.presented(presentation_time);
+
+    if redraw_needed || output_state.unfinished_animations_remain {
+        niri.queue_redraw(output);
+    } else {
+        niri.send_frame_callbacks(&output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::{ErrorKind, Read, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::PathBuf;
+    use std::sync::Once;
+
+    use niri_config::Config;
+    use smithay::reexports::calloop::EventLoop;
+    use smithay::reexports::wayland_server::Display;
+
+    use super::*;
+    use crate::niri::State;
+
+    fn create_state(event_loop: &EventLoop<'static, State>, modes: &[&str]) -> State {
+        // The Wayland socket goes into the runtime dir, which CI containers may not have. Tests run
+        // on parallel threads, and setting the environment while another thread reads it is a data
+        // race, so do it once, before any test gets past this point.
+        static RUNTIME_DIR: Once = Once::new();
+        RUNTIME_DIR.call_once(|| {
+            if env::var_os("XDG_RUNTIME_DIR").is_none() {
+                let dir = env::temp_dir().join(format!("niri-test-{}", std::process::id()));
+                std::fs::create_dir_all(&dir).unwrap();
+                env::set_var("XDG_RUNTIME_DIR", dir);
+            }
+        });
+
+        let modes = modes.iter().map(|mode| mode.parse().unwrap()).collect();
+        State::new(
+            Config::default(),
+            Some(modes),
+            event_loop.handle(),
+            event_loop.get_signal(),
+            Display::new().unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// Minimal Wayland client speaking the wire protocol directly.
+    struct Client {
+        stream: UnixStream,
+        buf: Vec<u8>,
+        next_id: u32,
+    }
+
+    struct Event {
+        object: u32,
+        opcode: u16,
+        args: Vec<u8>,
+    }
+
+    impl Client {
+        fn connect(state: &State) -> Self {
+            let path = PathBuf::from(env::var_os("XDG_RUNTIME_DIR").unwrap())
+                .join(&state.niri.socket_name);
+            let stream = UnixStream::connect(path).unwrap();
+            stream.set_nonblocking(true).unwrap();
+
+            Self {
+                stream,
+                buf: Vec::new(),
+                // 1 is the wl_display.
+                next_id: 2,
+            }
+        }
+
+        fn new_id(&mut self) -> u32 {
+            let id = self.next_id;
+            self.next_id += 1;
+            id
+        }
+
+        fn send(&mut self, object: u32, opcode: u16, args: &[u32]) {
+            let size = (8 + args.len() * 4) as u32;
+            let mut msg = vec![object, size << 16 | u32::from(opcode)];
+            msg.extend_from_slice(args);
+            let bytes = msg.iter().flat_map(|x| x.to_ne_bytes()).collect::<Vec<_>>();
+            self.stream.write_all(&bytes).unwrap();
+        }
+
+        /// Sends a wl_display.sync and processes the compositor until it's done, returning all
+        /// events received in the meantime.
+        fn roundtrip(
+            &mut self,
+            event_loop: &mut EventLoop<'static, State>,
+            state: &mut State,
+        ) -> Vec<Event> {
+            let callback = self.new_id();
+            self.send(1, 0, &[callback]);
+
+            let mut events = Vec::new();
+            for _ in 0..500 {
+                event_loop
+                    .dispatch(Some(Duration::from_millis(10)), state)
+                    .unwrap();
+                state.refresh_and_flush_clients();
+
+                self.read();
+                while let Some(event) = self.parse_event() {
+                    // wl_display.error
+                    assert!(
+                        !(event.object == 1 && event.opcode == 0),
+                        "protocol error: {:?}",
+                        event.args
+                    );
+
+                    // wl_callback.done
+                    if event.object == callback {
+                        return events;
+                    }
+                    events.push(event);
+                }
+            }
+
+            panic!("roundtrip timed out");
+        }
+
+        fn read(&mut self) {
+            let mut chunk = [0; 4096];
+            loop {
+                match self.stream.read(&mut chunk) {
+                    Ok(0) => panic!("compositor closed the connection"),
+                    Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                    Err(err) => panic!("error reading from the compositor: {err}"),
+                }
+            }
+        }
+
+        fn parse_event(&mut self) -> Option<Event> {
+            if self.buf.len() < 8 {
+                return None;
+            }
+
+            let object = read_u32(&self.buf, 0);
+            let header = read_u32(&self.buf, 4);
+            let size = (header >> 16) as usize;
+            if self.buf.len() < size {
+                return None;
+            }
+
+            let args = self.buf[8..size].to_vec();
+            self.buf.drain(..size);
+            Some(Event {
+                object,
+                opcode: header as u16,
+                args,
+            })
+        }
+    }
+
+    fn read_u32(buf: &[u8], offset: usize) -> u32 {
+        u32::from_ne_bytes(buf[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn read_string(buf: &[u8], offset: usize) -> (String, usize) {
+        let len = read_u32(buf, offset) as usize;
+        let start = offset + 4;
+        // The length includes the terminating nul, and the contents are padded to 32 bits.
+        let string = String::from_utf8(buf[start..start + len - 1].to_vec()).unwrap();
+        (string, start + ((len + 3) & !3))
+    }
+
+    fn string_arg(s: &str) -> Vec<u32> {
+        let mut bytes = s.as_bytes().to_vec();
+        bytes.push(0);
+        let len = bytes.len() as u32;
+        bytes.resize((bytes.len() + 3) & !3, 0);
+
+        let mut args = vec![len];
+        args.extend(
+            bytes
+                .chunks(4)
+                .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap())),
+        );
+        args
+    }
+
+    #[test]
+    fn client_connects_and_creates_surface() {
+        let mut event_loop = EventLoop::try_new().unwrap();
+        let mut state = create_state(&event_loop, &["1280x720", "800x600@30"]);
+        let mut client = Client::connect(&state);
+
+        // wl_display.get_registry
+        let registry = client.new_id();
+        client.send(1, 1, &[registry]);
+
+        let mut globals = Vec::new();
+        for event in client.roundtrip(&mut event_loop, &mut state) {
+            // wl_registry.global
+            if event.object == registry && event.opcode == 0 {
+                let name = read_u32(&event.args, 0);
+                let (interface, offset) = read_string(&event.args, 4);
+                let version = read_u32(&event.args, offset);
+                globals.push((name, interface, version));
+            }
+        }
+
+        let find = |interface: &str| globals.iter().find(|(_, i, _)| i == interface);
+        assert!(find("xdg_wm_base").is_some());
+        assert!(find("wl_shm").is_some());
+        let outputs = globals.iter().filter(|(_, i, _)| i == "wl_output").count();
+        assert_eq!(outputs, 2);
+
+        // wl_registry.bind the compositor, then create and commit a surface.
+        let (name, _, _) = find("wl_compositor").unwrap().clone();
+        let compositor = client.new_id();
+        let mut args = vec![name];
+        args.extend(string_arg("wl_compositor"));
+        args.extend([1, compositor]);
+        client.send(registry, 0, &args);
+
+        // wl_compositor.create_surface
+        let surface = client.new_id();
+        client.send(compositor, 0, &[surface]);
+        // wl_surface.commit
+        client.send(surface, 6, &[]);
+
+        client.roundtrip(&mut event_loop, &mut state);
+    }
+
+    #[test]
+    fn output_config_changes_apply() {
+        let event_loop = EventLoop::try_new().unwrap();
+        let mut state = create_state(&event_loop, &["1280x720"]);
+        let output = state.niri.output_by_name["headless-1"].clone();
+
+        let set_config = |state: &mut State, config: niri_config::Output| {
+            state.niri.config.borrow_mut().outputs = vec![config];
+            state.reload_output_config();
+        };
+
+        set_config(
+            &mut state,
+            niri_config::Output {
+                name: String::from("headless-1"),
+                mode: Some(niri_config::Mode {
+                    width: 1920,
+                    height: 1080,
+                    refresh: Some(30.),
+                }),
+                ..Default::default()
+            },
+        );
+        let mode = output.current_mode().unwrap();
+        assert_eq!(mode.size, (1920, 1080).into());
+        assert_eq!(mode.refresh, 30_000);
+        let ipc_mode = state.backend.ipc_outputs().borrow()["headless-1"].modes[0];
+        assert_eq!((ipc_mode.width, ipc_mode.height), (1920, 1080));
+
+        set_config(
+            &mut state,
+            niri_config::Output {
+                name: String::from("headless-1"),
+                off: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(state.niri.global_space.outputs().count(), 0);
+        assert!(state.backend.enabled_outputs().lock().unwrap().is_empty());
+        assert_eq!(
+            state.backend.ipc_outputs().borrow()["headless-1"].current_mode,
+            None
+        );
+
+        set_config(&mut state, niri_config::Output::default());
+        assert_eq!(state.niri.global_space.outputs().count(), 1);
+        let mode = output.current_mode().unwrap();
+        assert_eq!(mode.size, (1280, 720).into());
+        assert_eq!(mode.refresh, 60_000);
+    }
+
+    #[test]
+    fn transient_output_scale_must_be_integer() {
+        let event_loop = EventLoop::try_new().unwrap();
+        let mut state = create_state(&event_loop, &["1280x720"]);
+        let output = state.niri.output_by_name["headless-1"].clone();
+
+        let scale = |scale| niri_ipc::OutputAction::Scale { scale };
+
+        let result = state.apply_transient_output_config("headless-1", scale(1.5));
+        assert_eq!(result, niri_ipc::OutputConfigChanged::FractionalScale);
+        assert_eq!(output.current_scale().integer_scale(), 1);
+
+        let result = state.apply_transient_output_config("headless-1", scale(2.));
+        assert_eq!(result, niri_ipc::OutputConfigChanged::Applied);
+        assert_eq!(output.current_scale().integer_scale(), 2);
+
+        let result = state.apply_transient_output_config("headless-2", scale(2.));
+        assert_eq!(result, niri_ipc::OutputConfigChanged::OutputWasMissing);
+    }
+
+    #[test]
+    fn rendered_frame_can_be_inspected() {
+        let mut event_loop = EventLoop::try_new().unwrap();
+        let mut state = create_state(&event_loop, &["320x240"]);
+        let output = state.niri.output_by_name["headless-1"].clone();
+
+        for _ in 0..500 {
+            if state.backend.headless().last_frame(&output).is_some() {
+                break;
+            }
+
+            event_loop
+                .dispatch(Some(Duration::from_millis(10)), &mut state)
+                .unwrap();
+            state.refresh_and_flush_clients();
+        }
+
+        let frame = state.backend.headless().last_frame(&output).unwrap();
+        assert_eq!(frame.size, (320, 240).into());
+        assert_eq!(frame.pixels.len(), 320 * 240 * 4);
+
+        // With no windows, the frame is the opaque gray background, save for the cursor.
+        let corner = &frame.pixels[frame.pixels.len() - 4..];
+        assert!(corner[0] == corner[1] && corner[1] == corner[2]);
+        assert_eq!(corner[3], 255);
+        let background = frame
+            .pixels
+            .chunks(4)
+            .filter(|pixel| pixel == &corner)
+            .count();
+        assert!(background > 320 * 240 * 9 / 10);
+    }
+}
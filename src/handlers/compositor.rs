@@ -3,6 +3,7 @@ use std::collections::hash_map::Entry;
 use smithay::backend::renderer::utils::{on_commit_buffer_handler, with_renderer_surface_state};
 use smithay::input::pointer::CursorImageStatus;
 use smithay::reexports::calloop::Interest;
+use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel;
 use smithay::reexports::wayland_server::protocol::wl_buffer;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::reexports::wayland_server::{Client, Resource};
@@ -16,9 +17,10 @@ use smithay::wayland::dmabuf::get_dmabuf;
 use smithay::wayland::shm::{ShmHandler, ShmState};
 use smithay::{delegate_compositor, delegate_shm};
 
-use super::xdg_shell;
+use crate::layout::workspace::ColumnWidth;
 use crate::niri::{ClientState, State};
 use crate::utils::clone2;
+use crate::window::{should_float, InitialConfigureState, ResolvedWindowRules, Unmapped};
 
 impl CompositorHandler for State {
     fn compositor_state(&mut self) -> &mut CompositorState {
@@ -101,19 +103,93 @@ impl CompositorHandler for State {
 
                 if is_mapped {
                     // The toplevel got mapped.
-                    let window = entry.remove();
+                    let Unmapped { window, state } = entry.remove();
                     window.on_commit();
 
-                    if let Some(output) = self.niri.layout.add_window(window, None, false).cloned()
-                    {
+                    let rules = match state {
+                        InitialConfigureState::Configured { rules } => rules,
+                        // The client committed a buffer without waiting for the initial
+                        // configure, which is a protocol error, but let's not break.
+                        InitialConfigureState::NotConfigured => ResolvedWindowRules::compute(
+                            &self.niri.config.borrow().window_rules,
+                            window.toplevel(),
+                        ),
+                    };
+
+                    let output = rules
+                        .open_on_output
+                        .as_deref()
+                        .and_then(|name| self.niri.output_by_name.get(name))
+                        .cloned();
+                    let workspace_idx = rules
+                        .open_on_workspace
+                        .map(|idx| usize::from(idx.saturating_sub(1)));
+
+                    // An empty default width rule means that the window picks its own width.
+                    let width = rules
+                        .default_width
+                        .map(|width| width.unwrap_or(ColumnWidth::Fixed(window.geometry().size.w)));
+
+                    let is_fullscreen = window.toplevel().with_pending_state(|state| {
+                        state.states.contains(xdg_toplevel::State::Fullscreen)
+                    });
+                    let is_maximized = window.toplevel().with_pending_state(|state| {
+                        state.states.contains(xdg_toplevel::State::Maximized)
+                    });
+
+                    // Windows reappearing after a restart go back to where they were, tiled or
+                    // floating.
+                    let identity = self.niri.window_identity(&window);
+                    let is_restored =
+                        self.niri
+                            .layout
+                            .restore_window(&window, &identity, rules.open_focused);
+                    let is_floating = if is_restored {
+                        self.niri.layout.is_floating(&window)
+                    } else {
+                        should_float(&window)
+                    };
+
+                    let output = if is_restored {
+                        self.niri
+                            .layout
+                            .find_window_and_output(window.toplevel().wl_surface())
+                            .map(|(_, output)| output.clone())
+                    } else {
+                        self.niri
+                            .layout
+                            .add_window_to(
+                                window.clone(),
+                                output.as_ref(),
+                                workspace_idx,
+                                rules.open_focused,
+                                width,
+                                false,
+                                is_floating,
+                            )
+                            .cloned()
+                    };
+
+                    if let Some(server) = &self.niri.ipc_server {
+                        server.window_opened(&window);
+                    }
+
+                    if is_maximized && !is_floating {
+                        self.niri.layout.set_maximized(&window, true);
+                    }
+                    if is_fullscreen {
+                        self.niri.layout.set_fullscreen(&window, true);
+                    }
+
+                    if let Some(output) = output {
                         self.niri.queue_redraw(output);
                     }
                     return;
                 }
 
                 // The toplevel remains unmapped.
-                let window = entry.get();
-                xdg_shell::send_initial_configure_if_needed(window.toplevel());
+                let toplevel = entry.get().window.toplevel().clone();
+                self.send_initial_configure_if_needed(&toplevel);
                 return;
             }
 
@@ -130,18 +206,53 @@ impl CompositorHandler for State {
                 if !is_mapped {
                     // The toplevel got unmapped.
                     self.niri.layout.remove_window(&window);
-                    self.niri.unmapped_windows.insert(surface.clone(), window);
+                    if let Some(server) = &self.niri.ipc_server {
+                        server.window_closed(&window);
+                    }
+                    self.niri
+                        .unmapped_windows
+                        .insert(surface.clone(), Unmapped::new(window));
                     self.niri.queue_redraw(output);
                     return;
                 }
 
                 // The toplevel remains mapped.
                 self.niri.layout.update_window(&window);
+                if let Some(server) = &self.niri.ipc_server {
+                    server.check_window_title(&window);
+                }
 
                 // Popup placement depends on window size which might have changed.
                 self.update_reactive_popups(&window, &output);
 
                 self.niri.queue_redraw(output);
+                self.niri.queue_redraw_window_switcher();
+                return;
+            }
+
+            // This is a commit of a toplevel hidden in a scratchpad.
+            if let Some(window) = self.niri.layout.hidden_scratchpad_window(surface) {
+                let window = window.clone();
+                window.on_commit();
+
+                let is_mapped =
+                    with_renderer_surface_state(surface, |state| state.buffer().is_some());
+
+                if let Some(server) = &self.niri.ipc_server {
+                    if is_mapped {
+                        server.check_window_title(&window);
+                    } else {
+                        server.window_closed(&window);
+                    }
+                }
+
+                if !is_mapped {
+                    // The toplevel got unmapped while hidden.
+                    self.niri.layout.remove_hidden_scratchpad_window(surface);
+                    self.niri
+                        .unmapped_windows
+                        .insert(surface.clone(), Unmapped::new(window));
+                }
                 return;
             }
 
@@ -154,6 +265,7 @@ impl CompositorHandler for State {
             window.on_commit();
             self.niri.layout.update_window(&window);
             self.niri.queue_redraw(output);
+            self.niri.queue_redraw_window_switcher();
             return;
         }
 
@@ -3,15 +3,16 @@ use smithay::desktop::{
     PopupKeyboardGrab, PopupKind, PopupManager, PopupPointerGrab, PopupUngrabStrategy, Window,
     WindowSurfaceType,
 };
-use smithay::input::pointer::Focus;
+use smithay::input::pointer::{CursorIcon, CursorImageStatus, Focus};
 use smithay::output::Output;
 use smithay::reexports::wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1;
 use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_positioner::ConstraintAdjustment;
-use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::{self, ResizeEdge};
+use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel;
 use smithay::reexports::wayland_server::protocol::wl_output;
 use smithay::reexports::wayland_server::protocol::wl_seat::WlSeat;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::utils::{Logical, Rectangle, Serial};
+use smithay::reexports::wayland_server::Resource;
+use smithay::utils::{Logical, Rectangle, Serial, Size};
 use smithay::wayland::compositor::{send_surface_state, with_states};
 use smithay::wayland::input_method::InputMethodSeat;
 use smithay::wayland::shell::kde::decoration::{KdeDecorationHandler, KdeDecorationState};
@@ -19,12 +20,16 @@ use smithay::wayland::shell::wlr_layer::Layer;
 use smithay::wayland::shell::xdg::decoration::XdgDecorationHandler;
 use smithay::wayland::shell::xdg::{
     PopupSurface, PositionerState, ToplevelSurface, XdgPopupSurfaceData, XdgShellHandler,
-    XdgShellState, XdgToplevelSurfaceData,
+    XdgShellState,
 };
 use smithay::{delegate_kde_decoration, delegate_xdg_decoration, delegate_xdg_shell};
 
+use crate::input::move_grab::MoveGrab;
+use crate::input::resize_grab::ResizeGrab;
+use crate::layout::workspace::ColumnWidth;
 use crate::niri::{PopupGrabState, State};
-use crate::utils::clone2;
+use crate::utils::{clone2, ResizeEdge};
+use crate::window::{should_float, InitialConfigureState, ResolvedWindowRules, Unmapped};
 
 impl XdgShellHandler for State {
     fn xdg_shell_state(&mut self) -> &mut XdgShellState {
@@ -35,11 +40,6 @@ impl XdgShellHandler for State {
         let wl_surface = surface.wl_surface().clone();
         let window = Window::new(surface);
 
-        // Tell the surface the preferred size and bounds for its likely output.
-        if let Some(ws) = self.niri.layout.active_workspace() {
-            ws.configure_new_window(&window);
-        }
-
         // If the user prefers no CSD, it's a reasonable assumption that they would prefer to get
         // rid of the various client-side rounded corners also by using the tiled state.
         let config = self.niri.config.borrow();
@@ -53,7 +53,10 @@ impl XdgShellHandler for State {
         }
 
         // At the moment of creation, xdg toplevels must have no buffer.
-        let existing = self.niri.unmapped_windows.insert(wl_surface, window);
+        let existing = self
+            .niri
+            .unmapped_windows
+            .insert(wl_surface, Unmapped::new(window));
         assert!(existing.is_none());
     }
 
@@ -65,18 +68,81 @@ impl XdgShellHandler for State {
         }
     }
 
-    fn move_request(&mut self, _surface: ToplevelSurface, _seat: WlSeat, _serial: Serial) {
-        // FIXME
+    fn move_request(&mut self, surface: ToplevelSurface, _seat: WlSeat, serial: Serial) {
+        let pointer = self.niri.seat.get_pointer().unwrap();
+        if !pointer.has_grab(serial) {
+            return;
+        }
+
+        let Some(start_data) = pointer.grab_start_data() else {
+            return;
+        };
+
+        // The grab must have been started on this client's surface.
+        let Some((focus, _)) = &start_data.focus else {
+            return;
+        };
+        let wl_surface = surface.wl_surface();
+        if !focus.id().same_client_as(&wl_surface.id()) {
+            return;
+        }
+
+        let Some((window, _)) = self.niri.layout.find_window_and_output(wl_surface) else {
+            return;
+        };
+        let window = window.clone();
+
+        let grab = MoveGrab::new(start_data, window);
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        self.niri
+            .cursor_manager
+            .set_cursor_image(CursorImageStatus::Named(CursorIcon::Move));
     }
 
     fn resize_request(
         &mut self,
-        _surface: ToplevelSurface,
+        surface: ToplevelSurface,
         _seat: WlSeat,
-        _serial: Serial,
-        _edges: ResizeEdge,
+        serial: Serial,
+        edges: xdg_toplevel::ResizeEdge,
     ) {
-        // FIXME
+        let pointer = self.niri.seat.get_pointer().unwrap();
+        if !pointer.has_grab(serial) {
+            return;
+        }
+
+        let Some(start_data) = pointer.grab_start_data() else {
+            return;
+        };
+
+        // The grab must have been started on this client's surface.
+        let Some((focus, _)) = &start_data.focus else {
+            return;
+        };
+        let wl_surface = surface.wl_surface();
+        if !focus.id().same_client_as(&wl_surface.id()) {
+            return;
+        }
+
+        let Some((window, _)) = self.niri.layout.find_window_and_output(wl_surface) else {
+            return;
+        };
+        let window = window.clone();
+
+        let edges = ResizeEdge::from(edges);
+        if !self
+            .niri
+            .layout
+            .interactive_resize_begin(window.clone(), edges)
+        {
+            return;
+        }
+
+        let grab = ResizeGrab::new(start_data, window);
+        pointer.set_grab(self, grab, serial, Focus::Clear);
+        self.niri
+            .cursor_manager
+            .set_cursor_image(CursorImageStatus::Named(edges.cursor_icon()));
     }
 
     fn reposition_request(
@@ -193,15 +259,53 @@ impl XdgShellHandler for State {
     }
 
     fn maximize_request(&mut self, surface: ToplevelSurface) {
-        // FIXME
+        if let Some((window, _)) = self
+            .niri
+            .layout
+            .find_window_and_output(surface.wl_surface())
+        {
+            let window = window.clone();
+            self.niri.layout.set_maximized(&window, true);
+        } else if let Some(unmapped) = self.niri.unmapped_windows.get(surface.wl_surface()) {
+            if let Some(ws) = self.niri.layout.active_workspace() {
+                unmapped.window.toplevel().with_pending_state(|state| {
+                    state.size = Some(ws.new_window_size(Some(ColumnWidth::Proportion(1.))));
+                    state.states.set(xdg_toplevel::State::Maximized);
+                });
+            }
+        }
 
         // The protocol demands us to always reply with a configure,
         // regardless of we fulfilled the request or not
         surface.send_configure();
     }
 
-    fn unmaximize_request(&mut self, _surface: ToplevelSurface) {
-        // FIXME
+    fn unmaximize_request(&mut self, surface: ToplevelSurface) {
+        if let Some((window, _)) = self
+            .niri
+            .layout
+            .find_window_and_output(surface.wl_surface())
+        {
+            let window = window.clone();
+            self.niri.layout.set_maximized(&window, false);
+        } else if let Some(unmapped) = self.niri.unmapped_windows.get(surface.wl_surface()) {
+            if let Some(ws) = self.niri.layout.active_workspace() {
+                let width = match &unmapped.state {
+                    InitialConfigureState::Configured { rules } => rules.default_width,
+                    InitialConfigureState::NotConfigured => None,
+                };
+                let width = width.unwrap_or(ws.options.default_width);
+
+                unmapped.window.toplevel().with_pending_state(|state| {
+                    state.size = Some(ws.new_window_size(width));
+                    state.states.unset(xdg_toplevel::State::Maximized);
+                });
+            }
+        }
+
+        // The protocol demands us to always reply with a configure,
+        // regardless of we fulfilled the request or not
+        surface.send_configure();
     }
 
     fn fullscreen_request(
@@ -230,9 +334,9 @@ impl XdgShellHandler for State {
                 }
 
                 self.niri.layout.set_fullscreen(&window, true);
-            } else if let Some(window) = self.niri.unmapped_windows.get(surface.wl_surface()) {
+            } else if let Some(unmapped) = self.niri.unmapped_windows.get(surface.wl_surface()) {
                 if let Some(ws) = self.niri.layout.active_workspace() {
-                    window.toplevel().with_pending_state(|state| {
+                    unmapped.window.toplevel().with_pending_state(|state| {
                         state.size = Some(ws.view_size());
                         state.states.set(xdg_toplevel::State::Fullscreen);
                     });
@@ -253,10 +357,16 @@ impl XdgShellHandler for State {
         {
             let window = window.clone();
             self.niri.layout.set_fullscreen(&window, false);
-        } else if let Some(window) = self.niri.unmapped_windows.get(surface.wl_surface()) {
+        } else if let Some(unmapped) = self.niri.unmapped_windows.get(surface.wl_surface()) {
             if let Some(ws) = self.niri.layout.active_workspace() {
-                window.toplevel().with_pending_state(|state| {
-                    state.size = Some(ws.new_window_size());
+                let width = match &unmapped.state {
+                    InitialConfigureState::Configured { rules } => rules.default_width,
+                    InitialConfigureState::NotConfigured => None,
+                };
+                let width = width.unwrap_or(ws.options.default_width);
+
+                unmapped.window.toplevel().with_pending_state(|state| {
+                    state.size = Some(ws.new_window_size(width));
                     state.states.unset(xdg_toplevel::State::Fullscreen);
                 });
             }
@@ -264,6 +374,15 @@ impl XdgShellHandler for State {
     }
 
     fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
+        if self
+            .niri
+            .window_switcher
+            .remove_window(surface.wl_surface())
+        {
+            // FIXME: granular
+            self.niri.queue_redraw_all();
+        }
+
         if self
             .niri
             .unmapped_windows
@@ -274,6 +393,18 @@ impl XdgShellHandler for State {
             return;
         }
 
+        let hidden = self
+            .niri
+            .layout
+            .remove_hidden_scratchpad_window(surface.wl_surface());
+        if let Some(window) = hidden {
+            // A toplevel hidden in a scratchpad got destroyed.
+            if let Some(server) = &self.niri.ipc_server {
+                server.window_closed(&window);
+            }
+            return;
+        }
+
         let win_out = self
             .niri
             .layout
@@ -287,6 +418,11 @@ impl XdgShellHandler for State {
         };
 
         self.niri.layout.remove_window(&window);
+        // A window shown from a scratchpad is gone for good.
+        self.niri.layout.remove_shown_scratchpad_window(&window);
+        if let Some(server) = &self.niri.ipc_server {
+            server.window_closed(&window);
+        }
         self.niri.queue_redraw(output);
     }
 
@@ -350,25 +486,78 @@ impl KdeDecorationHandler for State {
 
 delegate_kde_decoration!(State);
 
-pub fn send_initial_configure_if_needed(toplevel: &ToplevelSurface) {
-    if !initial_configure_sent(toplevel) {
+impl State {
+    /// Sends the initial configure to an unmapped toplevel, unless it was already sent.
+    ///
+    /// Window rules are resolved at this point since the toplevel has had a chance to set its
+    /// app ID and title.
+    pub fn send_initial_configure_if_needed(&mut self, toplevel: &ToplevelSurface) {
+        let _span = tracy_client::span!("State::send_initial_configure_if_needed");
+
+        let Some(unmapped) = self.niri.unmapped_windows.get_mut(toplevel.wl_surface()) else {
+            error!("window must be present in unmapped_windows in send_initial_configure");
+            return;
+        };
+
+        if !unmapped.needs_initial_configure() {
+            return;
+        }
+
+        let config = self.niri.config.borrow();
+        let rules = ResolvedWindowRules::compute(&config.window_rules, toplevel);
+
+        // Tell the surface the preferred size and bounds for its likely output.
+        let output = rules
+            .open_on_output
+            .as_deref()
+            .and_then(|name| self.niri.output_by_name.get(name));
+        let workspace_idx = rules
+            .open_on_workspace
+            .map(|idx| usize::from(idx.saturating_sub(1)));
+        if let Some(ws) = self
+            .niri
+            .layout
+            .workspace_for_new_window(output, workspace_idx)
+        {
+            let width = rules.default_width.unwrap_or(ws.options.default_width);
+            ws.configure_new_window(&unmapped.window, width);
+
+            // The client might have requested fullscreen before the initial configure.
+            let is_fullscreen = rules.open_fullscreen.unwrap_or_else(|| {
+                toplevel.with_pending_state(|state| {
+                    state.states.contains(xdg_toplevel::State::Fullscreen)
+                })
+            });
+            // Floating windows pick their own size.
+            let is_floating = should_float(&unmapped.window);
+            toplevel.with_pending_state(|state| {
+                // The client might have requested maximize before the initial configure too.
+                let is_maximized =
+                    !is_floating && state.states.contains(xdg_toplevel::State::Maximized);
+                if !is_maximized {
+                    state.states.unset(xdg_toplevel::State::Maximized);
+                }
+
+                if is_fullscreen {
+                    state.size = Some(ws.view_size());
+                    state.states.set(xdg_toplevel::State::Fullscreen);
+                } else {
+                    state.states.unset(xdg_toplevel::State::Fullscreen);
+
+                    if is_floating {
+                        state.size = Some(Size::from((0, 0)));
+                    } else if is_maximized {
+                        state.size = Some(ws.new_window_size(Some(ColumnWidth::Proportion(1.))));
+                    }
+                }
+            });
+        }
+
         toplevel.send_configure();
-    }
-}
 
-fn initial_configure_sent(toplevel: &ToplevelSurface) -> bool {
-    with_states(toplevel.wl_surface(), |states| {
-        states
-            .data_map
-            .get::<XdgToplevelSurfaceData>()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .initial_configure_sent
-    })
-}
+        unmapped.state = InitialConfigureState::Configured { rules };
+    }
 
-impl State {
     /// Should be called on `WlSurface::commit`
     pub fn popups_handle_commit(&mut self, surface: &WlSurface) {
         self.niri.popups.commit(surface);
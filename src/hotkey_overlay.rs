@@ -215,10 +215,18 @@ fn render(config: &Config, comp_mod: CompositorMod, scale: i32) -> anyhow::Resul
         actions.push(&Action::Screenshot);
     }
 
+    // Same for the overview.
+    if binds
+        .iter()
+        .any(|bind| bind.actions.first() == Some(&Action::ToggleOverview))
+    {
+        actions.push(&Action::ToggleOverview);
+    }
+
     // Add the spawn actions.
     for bind in binds
         .iter()
-        .filter(|bind| matches!(bind.actions.first(), Some(Action::Spawn(_))))
+        .filter(|bind| matches!(bind.actions.first(), Some(Action::Spawn { .. })))
     {
         actions.push(bind.actions.first().unwrap());
     }
@@ -374,7 +382,8 @@ fn action_name(action: &Action) -> String {
         Action::ConsumeWindowIntoColumn => String::from("Consume Window Into Column"),
         Action::ExpelWindowFromColumn => String::from("Expel Window From Column"),
         Action::Screenshot => String::from("Take a Screenshot"),
-        Action::Spawn(args) => format!(
+        Action::ToggleOverview => String::from("Open the Overview"),
+        Action::Spawn { command: args } => format!(
             "Spawn <span face='monospace' bgcolor='#000000'>{}</span>",
             args.first().unwrap_or(&String::new())
         ),
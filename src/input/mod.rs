@@ -1,7 +1,8 @@
 use std::any::Any;
 use std::collections::HashSet;
+use std::time::Duration;
 
-use niri_config::{Action, Binds, LayoutAction, Modifiers};
+use niri_config::{Action, Binds, LayoutSwitchTarget, Modifiers};
 use smithay::backend::input::{
     AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, DeviceCapability, Event,
     GestureBeginEvent, GestureEndEvent, GesturePinchUpdateEvent as _, GestureSwipeUpdateEvent as _,
@@ -10,20 +11,32 @@ use smithay::backend::input::{
     TabletToolProximityEvent, TabletToolTipEvent, TabletToolTipState,
 };
 use smithay::backend::libinput::LibinputInputBackend;
+use smithay::desktop::Window;
 use smithay::input::keyboard::{keysyms, FilterResult, Keysym, ModifiersState};
 use smithay::input::pointer::{
-    AxisFrame, ButtonEvent, CursorImageStatus, GestureHoldBeginEvent, GestureHoldEndEvent,
-    GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent,
-    GestureSwipeEndEvent, GestureSwipeUpdateEvent, MotionEvent, RelativeMotionEvent,
+    AxisFrame, ButtonEvent, CursorIcon, CursorImageStatus, Focus, GestureHoldBeginEvent,
+    GestureHoldEndEvent, GesturePinchBeginEvent, GesturePinchEndEvent, GesturePinchUpdateEvent,
+    GestureSwipeBeginEvent, GestureSwipeEndEvent, GestureSwipeUpdateEvent,
+    GrabStartData as PointerGrabStartData, MotionEvent, RelativeMotionEvent,
 };
 use smithay::reexports::input;
 use smithay::utils::{Logical, Point, SERIAL_COUNTER};
 use smithay::wayland::pointer_constraints::{with_pointer_constraint, PointerConstraint};
 use smithay::wayland::tablet_manager::{TabletDescriptor, TabletSeatTrait};
 
+use self::move_grab::MoveGrab;
+use self::resize_grab::ResizeGrab;
 use crate::niri::State;
 use crate::screenshot_ui::ScreenshotUi;
-use crate::utils::{center, get_monotonic_time, spawn};
+use crate::utils::{center, get_monotonic_time, spawn, ResizeEdge};
+use crate::window_switcher::WindowSwitcher;
+
+pub mod move_grab;
+pub mod resize_grab;
+pub mod swipe_tracker;
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompositorMod {
@@ -138,10 +151,15 @@ impl State {
                     }
                 }
 
-                apply_libinput_settings(&self.niri.config.borrow().input, device);
+                let scroll_factor =
+                    apply_libinput_settings(&self.niri.config.borrow().input, device);
+                self.niri
+                    .scroll_factors
+                    .insert(device.clone(), scroll_factor);
             }
             InputEvent::DeviceRemoved { device } => {
                 self.niri.tablets.remove(device);
+                self.niri.scroll_factors.remove(device);
                 self.niri.devices.remove(device);
             }
             _ => (),
@@ -218,7 +236,9 @@ impl State {
         let time = Event::time_msec(&event);
         let pressed = event.state() == KeyState::Pressed;
 
-        let Some(Some(action)) = self.niri.seat.get_keyboard().unwrap().input(
+        let mut confirm_window_switcher = false;
+
+        let action = self.niri.seat.get_keyboard().unwrap().input(
             self,
             event.key_code(),
             event.state(),
@@ -236,6 +256,14 @@ impl State {
                     }
                 }
 
+                // Releasing the held modifiers picks the window selected in the switcher.
+                if !pressed
+                    && this.niri.window_switcher.confirm_on_release()
+                    && !(mods.ctrl || mods.alt || mods.logo)
+                {
+                    confirm_window_switcher = true;
+                }
+
                 should_intercept_key(
                     &mut this.niri.suppressed_keys,
                     bindings,
@@ -246,18 +274,30 @@ impl State {
                     pressed,
                     *mods,
                     &this.niri.screenshot_ui,
+                    &this.niri.window_switcher,
+                    this.niri.layout.is_overview_open(),
                     this.niri.config.borrow().input.disable_power_key_handling,
                 )
             },
-        ) else {
+        );
+
+        if confirm_window_switcher {
+            self.do_action(Action::ConfirmWindowSwitcher);
+        }
+
+        let Some(Some(action)) = action else {
             return;
         };
 
-        // Filter actions when the key is released or the session is locked.
+        // Filter actions when the key is released.
         if !pressed {
             return;
         }
 
+        self.do_action(action);
+    }
+
+    pub fn do_action(&mut self, action: Action) {
         if self.niri.is_locked() && !allowed_when_locked(&action) {
             return;
         }
@@ -290,7 +330,7 @@ impl State {
                 self.backend.toggle_debug_tint();
                 self.niri.queue_redraw_all();
             }
-            Action::Spawn(command) => {
+            Action::Spawn { command } => {
                 spawn(command);
             }
             Action::ScreenshotScreen => {
@@ -358,12 +398,12 @@ impl State {
                     self.niri.queue_redraw_all();
                 }
             }
-            Action::SwitchLayout(action) => {
+            Action::SwitchLayout { layout } => {
                 self.niri.seat.get_keyboard().unwrap().with_xkb_state(
                     self,
-                    |mut state| match action {
-                        LayoutAction::Next => state.cycle_next_layout(),
-                        LayoutAction::Prev => state.cycle_prev_layout(),
+                    |mut state| match layout {
+                        LayoutSwitchTarget::Next => state.cycle_next_layout(),
+                        LayoutSwitchTarget::Prev => state.cycle_prev_layout(),
                     },
                 );
             }
@@ -419,44 +459,63 @@ impl State {
             }
             Action::FocusColumnLeft => {
                 self.niri.layout.focus_left();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusColumnRight => {
                 self.niri.layout.focus_right();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusColumnFirst => {
                 self.niri.layout.focus_column_first();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusColumnLast => {
                 self.niri.layout.focus_column_last();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusWindowDown => {
                 self.niri.layout.focus_down();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusWindowUp => {
                 self.niri.layout.focus_up();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusWindowOrWorkspaceDown => {
                 self.niri.layout.focus_window_or_workspace_down();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusWindowOrWorkspaceUp => {
                 self.niri.layout.focus_window_or_workspace_up();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
+            Action::FocusWindowPrevious => {
+                let current = self.niri.layout.focus().cloned();
+                let previous = self
+                    .niri
+                    .windows_in_focus_order()
+                    .into_iter()
+                    .find(|win| Some(win) != current.as_ref());
+                if let Some(window) = previous {
+                    self.focus_window(&window);
+                }
+            }
             Action::MoveWindowToWorkspaceDown => {
                 self.niri.layout.move_to_workspace_down();
                 // FIXME: granular
@@ -467,11 +526,18 @@ impl State {
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
-            Action::MoveWindowToWorkspace(idx) => {
-                let idx = idx.saturating_sub(1) as usize;
-                self.niri.layout.move_to_workspace(idx);
-                // FIXME: granular
-                self.niri.queue_redraw_all();
+            Action::MoveWindowToWorkspace { reference } => {
+                if let Some((output, index)) = self.niri.find_output_and_workspace_index(reference)
+                {
+                    if let Some(output) = output {
+                        self.niri.layout.move_to_output(&output, Some(index));
+                        self.move_cursor_to_output(&output);
+                    } else {
+                        self.niri.layout.move_to_workspace(index);
+                    }
+                    // FIXME: granular
+                    self.niri.queue_redraw_all();
+                }
             }
             Action::MoveColumnToWorkspaceDown => {
                 self.niri.layout.move_column_to_workspace_down();
@@ -483,25 +549,59 @@ impl State {
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
-            Action::MoveColumnToWorkspace(idx) => {
-                let idx = idx.saturating_sub(1) as usize;
-                self.niri.layout.move_column_to_workspace(idx);
-                // FIXME: granular
-                self.niri.queue_redraw_all();
+            Action::MoveColumnToWorkspace { reference } => {
+                if let Some((output, index)) = self.niri.find_output_and_workspace_index(reference)
+                {
+                    if let Some(output) = output {
+                        self.niri.layout.move_column_to_output(&output, Some(index));
+                        self.move_cursor_to_output(&output);
+                    } else {
+                        self.niri.layout.move_column_to_workspace(index);
+                    }
+                    // FIXME: granular
+                    self.niri.queue_redraw_all();
+                }
             }
             Action::FocusWorkspaceDown => {
                 self.niri.layout.switch_workspace_down();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
             Action::FocusWorkspaceUp => {
                 self.niri.layout.switch_workspace_up();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
-            Action::FocusWorkspace(idx) => {
-                let idx = idx.saturating_sub(1) as usize;
-                self.niri.layout.switch_workspace(idx);
+            Action::FocusWorkspace { reference } => {
+                if let Some((output, index)) = self.niri.find_output_and_workspace_index(reference)
+                {
+                    if let Some(output) = output {
+                        self.niri.layout.focus_output(&output);
+                        self.niri.layout.switch_workspace(index);
+                        if !self.maybe_warp_cursor_to_focus() {
+                            self.move_cursor_to_output(&output);
+                        }
+                    } else {
+                        let config = self.niri.config.borrow();
+                        let auto_back_and_forth = config.input.workspace_auto_back_and_forth;
+                        drop(config);
+
+                        if auto_back_and_forth {
+                            self.niri.layout.switch_workspace_auto_back_and_forth(index);
+                        } else {
+                            self.niri.layout.switch_workspace(index);
+                        }
+                        self.maybe_warp_cursor_to_focus();
+                    }
+                    // FIXME: granular
+                    self.niri.queue_redraw_all();
+                }
+            }
+            Action::FocusWorkspacePrevious => {
+                self.niri.layout.switch_workspace_previous();
+                self.maybe_warp_cursor_to_focus();
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
@@ -515,6 +615,19 @@ impl State {
                 // FIXME: granular
                 self.niri.queue_redraw_all();
             }
+            Action::MoveWindowToScratchpad { name } => {
+                let name = name.unwrap_or_default();
+                self.niri.layout.move_window_to_scratchpad(&name);
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
+            Action::ToggleScratchpad { name } => {
+                let name = name.unwrap_or_default();
+                self.niri.layout.toggle_scratchpad(&name);
+                self.maybe_warp_cursor_to_focus();
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
             Action::ConsumeWindowIntoColumn => {
                 self.niri.layout.consume_into_column();
                 // FIXME: granular
@@ -536,89 +649,139 @@ impl State {
             Action::MaximizeColumn => {
                 self.niri.layout.toggle_full_width();
             }
+            Action::ToggleColumnTabbedDisplay => {
+                self.niri.layout.toggle_column_tabbed_display();
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
             Action::FocusMonitorLeft => {
                 if let Some(output) = self.niri.output_left() {
                     self.niri.layout.focus_output(&output);
-                    self.move_cursor_to_output(&output);
+                    if !self.maybe_warp_cursor_to_focus() {
+                        self.move_cursor_to_output(&output);
+                    }
                 }
             }
             Action::FocusMonitorRight => {
                 if let Some(output) = self.niri.output_right() {
                     self.niri.layout.focus_output(&output);
-                    self.move_cursor_to_output(&output);
+                    if !self.maybe_warp_cursor_to_focus() {
+                        self.move_cursor_to_output(&output);
+                    }
                 }
             }
             Action::FocusMonitorDown => {
                 if let Some(output) = self.niri.output_down() {
                     self.niri.layout.focus_output(&output);
-                    self.move_cursor_to_output(&output);
+                    if !self.maybe_warp_cursor_to_focus() {
+                        self.move_cursor_to_output(&output);
+                    }
                 }
             }
             Action::FocusMonitorUp => {
                 if let Some(output) = self.niri.output_up() {
                     self.niri.layout.focus_output(&output);
-                    self.move_cursor_to_output(&output);
+                    if !self.maybe_warp_cursor_to_focus() {
+                        self.move_cursor_to_output(&output);
+                    }
                 }
             }
             Action::MoveWindowToMonitorLeft => {
                 if let Some(output) = self.niri.output_left() {
-                    self.niri.layout.move_to_output(&output);
+                    self.niri.layout.move_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveWindowToMonitorRight => {
                 if let Some(output) = self.niri.output_right() {
-                    self.niri.layout.move_to_output(&output);
+                    self.niri.layout.move_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveWindowToMonitorDown => {
                 if let Some(output) = self.niri.output_down() {
-                    self.niri.layout.move_to_output(&output);
+                    self.niri.layout.move_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveWindowToMonitorUp => {
                 if let Some(output) = self.niri.output_up() {
-                    self.niri.layout.move_to_output(&output);
+                    self.niri.layout.move_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveColumnToMonitorLeft => {
                 if let Some(output) = self.niri.output_left() {
-                    self.niri.layout.move_column_to_output(&output);
+                    self.niri.layout.move_column_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveColumnToMonitorRight => {
                 if let Some(output) = self.niri.output_right() {
-                    self.niri.layout.move_column_to_output(&output);
+                    self.niri.layout.move_column_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveColumnToMonitorDown => {
                 if let Some(output) = self.niri.output_down() {
-                    self.niri.layout.move_column_to_output(&output);
+                    self.niri.layout.move_column_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
             Action::MoveColumnToMonitorUp => {
                 if let Some(output) = self.niri.output_up() {
-                    self.niri.layout.move_column_to_output(&output);
+                    self.niri.layout.move_column_to_output(&output, None);
                     self.move_cursor_to_output(&output);
                 }
             }
-            Action::SetColumnWidth(change) => {
+            Action::SetColumnWidth { change } => {
                 self.niri.layout.set_column_width(change);
             }
-            Action::SetWindowHeight(change) => {
+            Action::SetWindowHeight { change } => {
                 self.niri.layout.set_window_height(change);
             }
+            Action::ToggleWindowFloating => {
+                self.niri.layout.toggle_window_floating();
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
+            Action::SwitchFocusBetweenFloatingAndTiling => {
+                self.niri.layout.switch_focus_between_floating_and_tiling();
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
+            Action::MoveFloatingWindow { x, y } => {
+                self.niri.layout.move_floating_window(x, y);
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
             Action::ShowHotkeyOverlay => {
                 if self.niri.hotkey_overlay.show() {
                     self.niri.queue_redraw_all();
                 }
             }
+            Action::ToggleOverview => {
+                self.niri.layout.toggle_overview();
+                // FIXME: granular
+                self.niri.queue_redraw_all();
+            }
+            Action::WindowSwitcherNext => {
+                self.step_window_switcher(true);
+            }
+            Action::WindowSwitcherPrevious => {
+                self.step_window_switcher(false);
+            }
+            Action::ConfirmWindowSwitcher => {
+                if let Some(window) = self.niri.window_switcher.close() {
+                    self.focus_window(&window);
+                }
+            }
+            Action::CancelWindowSwitcher => {
+                if self.niri.window_switcher.close().is_some() {
+                    // FIXME: granular
+                    self.niri.queue_redraw_all();
+                }
+            }
             Action::MoveWorkspaceToMonitorLeft => {
                 if let Some(output) = self.niri.output_left() {
                     self.niri.layout.move_workspace_to_output(&output);
@@ -646,6 +809,53 @@ impl State {
         }
     }
 
+    /// Activates the window, switching workspaces and outputs as needed.
+    fn focus_window(&mut self, window: &Window) {
+        let output = self.niri.layout.active_output().cloned();
+        self.niri.layout.activate_window(window);
+
+        let new_output = self.niri.layout.active_output().cloned();
+        if !self.maybe_warp_cursor_to_focus() && new_output != output {
+            if let Some(new_output) = new_output {
+                self.move_cursor_to_output(&new_output);
+            }
+        }
+
+        // FIXME: granular
+        self.niri.queue_redraw_all();
+    }
+
+    /// Opens the window switcher, or moves its selection if it is already open.
+    fn step_window_switcher(&mut self, forward: bool) {
+        if !self.niri.window_switcher.is_open() {
+            let Some(output) = self.niri.layout.active_output().cloned() else {
+                return;
+            };
+            let windows = self.niri.windows_in_focus_order();
+
+            // When opened with a held modifier, releasing it picks the selected window.
+            let mods = self.niri.seat.get_keyboard().unwrap().modifier_state();
+            let confirm_on_release = mods.ctrl || mods.alt || mods.logo;
+
+            if !self
+                .niri
+                .window_switcher
+                .open(output, windows, confirm_on_release)
+            {
+                return;
+            }
+        }
+
+        if forward {
+            self.niri.window_switcher.select_next();
+        } else {
+            self.niri.window_switcher.select_previous();
+        }
+
+        // FIXME: granular
+        self.niri.queue_redraw_all();
+    }
+
     fn on_pointer_motion<I: InputBackend>(&mut self, event: I::PointerMotionEvent) {
         // We need an output to be able to move the pointer.
         if self.niri.global_space.outputs().next().is_none() {
@@ -820,6 +1030,8 @@ impl State {
 
         pointer.frame(self);
 
+        self.focus_window_under_cursor_if_needed();
+
         // Redraw to update the cursor position.
         // FIXME: redraw only outputs overlapping the cursor.
         self.niri.queue_redraw_all();
@@ -876,11 +1088,75 @@ impl State {
         // We moved the regular pointer, so show it now.
         self.niri.tablet_cursor_location = None;
 
+        self.focus_window_under_cursor_if_needed();
+
         // Redraw to update the cursor position.
         // FIXME: redraw only outputs overlapping the cursor.
         self.niri.queue_redraw_all();
     }
 
+    /// Activates the window under the cursor if focus-follows-mouse is enabled.
+    fn focus_window_under_cursor_if_needed(&mut self) {
+        let Some(ffm) = self.niri.config.borrow().input.focus_follows_mouse else {
+            return;
+        };
+
+        let pointer = self.niri.seat.get_pointer().unwrap();
+        if pointer.is_grabbed() || self.niri.layout.is_overview_open() {
+            return;
+        }
+
+        let Some(window) = self.niri.window_under_cursor() else {
+            return;
+        };
+
+        if self.niri.layout.focus() == Some(window) {
+            return;
+        }
+
+        if let Some(max) = ffm.max_scroll_amount {
+            if self.niri.layout.scroll_amount_to_activate(window) > max.0 {
+                return;
+            }
+        }
+
+        let window = window.clone();
+        self.niri.layout.activate_window(&window);
+    }
+
+    /// Returns the edges to resize the window by, based on which part of it the pointer is in.
+    fn resize_edges_under(&self, window: &Window, location: Point<f64, Logical>) -> ResizeEdge {
+        let mut edges = ResizeEdge::empty();
+
+        if let Some((output, pos_within_output)) = self.niri.output_under(location) {
+            if let Some((_, Some(surface_loc))) =
+                self.niri.layout.window_under(output, pos_within_output)
+            {
+                // Split the window into thirds along each axis.
+                let geom = window.geometry();
+                let pos = pos_within_output - (surface_loc + geom.loc).to_f64();
+                let size = geom.size.to_f64();
+
+                if pos.x < size.w / 3. {
+                    edges |= ResizeEdge::LEFT;
+                } else if size.w * 2. / 3. < pos.x {
+                    edges |= ResizeEdge::RIGHT;
+                }
+                if pos.y < size.h / 3. {
+                    edges |= ResizeEdge::TOP;
+                } else if size.h * 2. / 3. < pos.y {
+                    edges |= ResizeEdge::BOTTOM;
+                }
+            }
+        }
+
+        if edges.is_empty() {
+            edges = ResizeEdge::BOTTOM_RIGHT;
+        }
+
+        edges
+    }
+
     fn on_pointer_button<I: InputBackend>(&mut self, event: I::PointerButtonEvent) {
         let pointer = self.niri.seat.get_pointer().unwrap();
 
@@ -893,12 +1169,60 @@ impl State {
         if ButtonState::Pressed == button_state {
             if let Some(window) = self.niri.window_under_cursor() {
                 let window = window.clone();
+
+                // Check if we need to start an interactive move or resize.
+                let mods = self.niri.seat.get_keyboard().unwrap().modifier_state();
+                let mod_down = match self.backend.mod_key() {
+                    CompositorMod::Super => mods.logo,
+                    CompositorMod::Alt => mods.alt,
+                };
+                // In the overview, windows can be dragged around without the modifier.
+                let is_overview_open = self.niri.layout.is_overview_open();
+                if (mod_down || is_overview_open) && !pointer.is_grabbed() {
+                    let location = pointer.current_location();
+                    let start_data = PointerGrabStartData {
+                        focus: None,
+                        button,
+                        location,
+                    };
+
+                    if button == BTN_LEFT {
+                        let grab = MoveGrab::new(start_data, window.clone());
+                        pointer.set_grab(self, grab, serial, Focus::Clear);
+                        self.niri
+                            .cursor_manager
+                            .set_cursor_image(CursorImageStatus::Named(CursorIcon::Move));
+                    } else if button == BTN_RIGHT && mod_down {
+                        let edges = self.resize_edges_under(&window, location);
+                        if self
+                            .niri
+                            .layout
+                            .interactive_resize_begin(window.clone(), edges)
+                        {
+                            let grab = ResizeGrab::new(start_data, window.clone());
+                            pointer.set_grab(self, grab, serial, Focus::Clear);
+                            self.niri
+                                .cursor_manager
+                                .set_cursor_image(CursorImageStatus::Named(edges.cursor_icon()));
+                        }
+                    }
+                }
+
                 self.niri.layout.activate_window(&window);
 
                 // FIXME: granular.
                 self.niri.queue_redraw_all();
             } else if let Some(output) = self.niri.output_under_cursor() {
-                self.niri.layout.activate_output(&output);
+                if self.niri.layout.is_overview_open() {
+                    let pos = pointer.current_location();
+                    let output_geo = self.niri.global_space.output_geometry(&output).unwrap();
+                    let pos_within_output = pos - output_geo.loc.to_f64();
+                    self.niri
+                        .layout
+                        .overview_pick_workspace(&output, pos_within_output);
+                } else {
+                    self.niri.layout.activate_output(&output);
+                }
 
                 // FIXME: granular.
                 self.niri.queue_redraw_all();
@@ -946,17 +1270,47 @@ impl State {
         pointer.frame(self);
     }
 
-    fn on_pointer_axis<I: InputBackend>(&mut self, event: I::PointerAxisEvent) {
+    fn on_pointer_axis<I: InputBackend>(&mut self, event: I::PointerAxisEvent)
+    where
+        I::Device: 'static, // Needed for downcasting.
+    {
         let source = event.source();
 
+        let device = event.device();
+        let scroll_factor = (&device as &dyn Any)
+            .downcast_ref::<input::Device>()
+            .and_then(|device| self.niri.scroll_factors.get(device).copied())
+            .unwrap_or(1.);
+
         let horizontal_amount = event
             .amount(Axis::Horizontal)
-            .unwrap_or_else(|| event.amount_v120(Axis::Horizontal).unwrap_or(0.0) * 3.0 / 120.);
+            .unwrap_or_else(|| event.amount_v120(Axis::Horizontal).unwrap_or(0.0) * 3.0 / 120.)
+            * scroll_factor;
         let vertical_amount = event
             .amount(Axis::Vertical)
-            .unwrap_or_else(|| event.amount_v120(Axis::Vertical).unwrap_or(0.0) * 3.0 / 120.);
-        let horizontal_amount_discrete = event.amount_v120(Axis::Horizontal);
-        let vertical_amount_discrete = event.amount_v120(Axis::Vertical);
+            .unwrap_or_else(|| event.amount_v120(Axis::Vertical).unwrap_or(0.0) * 3.0 / 120.)
+            * scroll_factor;
+        let horizontal_amount_discrete = event
+            .amount_v120(Axis::Horizontal)
+            .map(|v| v * scroll_factor);
+        let vertical_amount_discrete = event.amount_v120(Axis::Vertical).map(|v| v * scroll_factor);
+
+        // In the overview, scrolling pans across the workspaces instead of going to windows.
+        if self.niri.layout.is_overview_open() {
+            if let Some(output) = self.niri.output_under_cursor() {
+                // Wheel clicks are 15 units each, which is too slow for panning.
+                let delta = if source == AxisSource::Finger {
+                    vertical_amount
+                } else {
+                    vertical_amount * 8.
+                };
+                self.niri.layout.overview_pan(&output, delta);
+
+                // FIXME: granular.
+                self.niri.queue_redraw_all();
+                return;
+            }
+        }
 
         let mut frame = AxisFrame::new(event.time_msec()).source(source);
         if horizontal_amount != 0.0 {
@@ -1179,7 +1533,11 @@ impl State {
             }
         }
 
-        let res = self.niri.layout.workspace_switch_gesture_update(delta_y);
+        let timestamp = Duration::from_millis(u64::from(event.time_msec()));
+        let res = self
+            .niri
+            .layout
+            .workspace_switch_gesture_update(delta_y, timestamp);
         if let Some(output) = res {
             if let Some(output) = output {
                 self.niri.queue_redraw(output);
@@ -1338,6 +1696,8 @@ fn should_intercept_key(
     pressed: bool,
     mods: ModifiersState,
     screenshot_ui: &ScreenshotUi,
+    window_switcher: &WindowSwitcher,
+    is_overview_open: bool,
     disable_power_key_handling: bool,
 ) -> FilterResult<Option<Action>> {
     // Actions are only triggered on presses, release of the key
@@ -1356,6 +1716,16 @@ fn should_intercept_key(
         disable_power_key_handling,
     );
 
+    // Plain navigation keys pick a window or a workspace in the overview.
+    if final_action.is_none() && is_overview_open {
+        final_action = overview_action(raw, mods);
+    }
+
+    // Same for the window switcher.
+    if final_action.is_none() {
+        final_action = window_switcher.action(raw);
+    }
+
     // Allow only a subset of compositor actions while the screenshot UI is open, since the user
     // cannot see the screen.
     if screenshot_ui.is_open() {
@@ -1385,6 +1755,21 @@ fn should_intercept_key(
     }
 }
 
+fn overview_action(raw: Option<Keysym>, mods: ModifiersState) -> Option<Action> {
+    if mods.ctrl || mods.shift || mods.alt || mods.logo {
+        return None;
+    }
+
+    match raw? {
+        Keysym::Escape | Keysym::Return => Some(Action::ToggleOverview),
+        Keysym::Left => Some(Action::FocusColumnLeft),
+        Keysym::Right => Some(Action::FocusColumnRight),
+        Keysym::Up => Some(Action::FocusWindowOrWorkspaceUp),
+        Keysym::Down => Some(Action::FocusWindowOrWorkspaceDown),
+        _ => None,
+    }
+}
+
 fn action(
     bindings: &Binds,
     comp_mod: CompositorMod,
@@ -1504,7 +1889,7 @@ fn allowed_when_locked(action: &Action) -> bool {
             | Action::ChangeVt(_)
             | Action::Suspend
             | Action::PowerOffMonitors
-            | Action::SwitchLayout(_)
+            | Action::SwitchLayout { .. }
     )
 }
 
@@ -1515,61 +1900,284 @@ fn allowed_during_screenshot(action: &Action) -> bool {
     )
 }
 
-pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::Device) {
-    // According to Mutter code, this setting is specific to touchpads.
-    let is_touchpad = device.config_tap_finger_count() > 0;
-    if is_touchpad {
-        let c = &config.touchpad;
-        let _ = device.config_tap_set_enabled(c.tap);
-        let _ = device.config_dwt_set_enabled(c.dwt);
-        let _ = device.config_scroll_set_natural_scroll_enabled(c.natural_scroll);
-        let _ = device.config_accel_set_speed(c.accel_speed);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PointerKind {
+    Touchpad,
+    Trackball,
+    Trackpoint,
+    Mouse,
+}
 
-        if let Some(accel_profile) = c.accel_profile {
-            let _ = device.config_accel_set_profile(accel_profile.into());
-        } else if let Some(default) = device.config_accel_default_profile() {
-            let _ = device.config_accel_set_profile(default);
-        }
+fn pointer_kind(device: &input::Device) -> Option<PointerKind> {
+    // According to Mutter code, this setting is specific to touchpads.
+    if device.config_tap_finger_count() > 0 {
+        return Some(PointerKind::Touchpad);
+    }
 
-        if let Some(tap_button_map) = c.tap_button_map {
-            let _ = device.config_tap_set_button_map(tap_button_map.into());
-        } else if let Some(default) = device.config_tap_default_button_map() {
-            let _ = device.config_tap_set_button_map(default);
-        }
+    if !device.has_capability(input::DeviceCapability::Pointer) {
+        return None;
     }
 
     // This is how Mutter tells apart mice.
-    let mut is_trackball = false;
-    let mut is_trackpoint = false;
     if let Some(udev_device) = unsafe { device.udev_device() } {
         if udev_device.property_value("ID_INPUT_TRACKBALL").is_some() {
-            is_trackball = true;
+            return Some(PointerKind::Trackball);
         }
         if udev_device
             .property_value("ID_INPUT_POINTINGSTICK")
             .is_some()
         {
-            is_trackpoint = true;
+            return Some(PointerKind::Trackpoint);
         }
     }
 
-    let is_mouse = device.has_capability(input::DeviceCapability::Pointer)
-        && !is_touchpad
-        && !is_trackball
-        && !is_trackpoint;
-    if is_mouse {
-        let c = &config.mouse;
-        let _ = device.config_scroll_set_natural_scroll_enabled(c.natural_scroll);
-        let _ = device.config_accel_set_speed(c.accel_speed);
+    Some(PointerKind::Mouse)
+}
+
+fn device_config<'a>(
+    config: &'a niri_config::Input,
+    device: &input::Device,
+) -> Option<&'a niri_config::InputDevice> {
+    let (name, vendor, product) = (device.name(), device.id_vendor(), device.id_product());
+    config
+        .devices
+        .iter()
+        .find(|c| c.matches(name, vendor, product))
+}
+
+struct PointerSettings {
+    off: bool,
+    natural_scroll: bool,
+    accel_speed: f64,
+    accel_profile: Option<niri_config::AccelProfile>,
+    scroll_method: Option<niri_config::ScrollMethod>,
+    scroll_button: Option<u32>,
+    left_handed: bool,
+    middle_emulation: bool,
+    disabled_on_external_mouse: bool,
+}
+
+struct TouchpadSettings {
+    tap: bool,
+    dwt: bool,
+    dwtp: bool,
+    drag: Option<bool>,
+    drag_lock: bool,
+    click_method: Option<niri_config::ClickMethod>,
+    tap_button_map: Option<niri_config::TapButtonMap>,
+}
 
-        if let Some(accel_profile) = c.accel_profile {
-            let _ = device.config_accel_set_profile(accel_profile.into());
-        } else if let Some(default) = device.config_accel_default_profile() {
-            let _ = device.config_accel_set_profile(default);
+impl From<&niri_config::Touchpad> for PointerSettings {
+    fn from(c: &niri_config::Touchpad) -> Self {
+        Self {
+            off: c.off,
+            natural_scroll: c.natural_scroll,
+            accel_speed: c.accel_speed,
+            accel_profile: c.accel_profile,
+            scroll_method: c.scroll_method,
+            scroll_button: c.scroll_button,
+            left_handed: c.left_handed,
+            middle_emulation: c.middle_emulation,
+            disabled_on_external_mouse: c.disabled_on_external_mouse,
         }
     }
 }
 
+impl From<&niri_config::Mouse> for PointerSettings {
+    fn from(c: &niri_config::Mouse) -> Self {
+        Self {
+            off: c.off,
+            natural_scroll: c.natural_scroll,
+            accel_speed: c.accel_speed,
+            accel_profile: c.accel_profile,
+            scroll_method: c.scroll_method,
+            scroll_button: c.scroll_button,
+            left_handed: c.left_handed,
+            middle_emulation: c.middle_emulation,
+            disabled_on_external_mouse: false,
+        }
+    }
+}
+
+impl From<&niri_config::Trackpoint> for PointerSettings {
+    fn from(c: &niri_config::Trackpoint) -> Self {
+        Self {
+            off: c.off,
+            natural_scroll: c.natural_scroll,
+            accel_speed: c.accel_speed,
+            accel_profile: c.accel_profile,
+            scroll_method: c.scroll_method,
+            scroll_button: c.scroll_button,
+            left_handed: c.left_handed,
+            middle_emulation: c.middle_emulation,
+            disabled_on_external_mouse: false,
+        }
+    }
+}
+
+impl From<&niri_config::Trackball> for PointerSettings {
+    fn from(c: &niri_config::Trackball) -> Self {
+        Self {
+            off: c.off,
+            natural_scroll: c.natural_scroll,
+            accel_speed: c.accel_speed,
+            accel_profile: c.accel_profile,
+            scroll_method: c.scroll_method,
+            scroll_button: c.scroll_button,
+            left_handed: c.left_handed,
+            middle_emulation: c.middle_emulation,
+            disabled_on_external_mouse: false,
+        }
+    }
+}
+
+impl From<&niri_config::InputDevice> for PointerSettings {
+    fn from(c: &niri_config::InputDevice) -> Self {
+        Self {
+            off: c.off,
+            natural_scroll: c.natural_scroll,
+            accel_speed: c.accel_speed,
+            accel_profile: c.accel_profile,
+            scroll_method: c.scroll_method,
+            scroll_button: c.scroll_button,
+            left_handed: c.left_handed,
+            middle_emulation: c.middle_emulation,
+            disabled_on_external_mouse: c.disabled_on_external_mouse,
+        }
+    }
+}
+
+impl From<&niri_config::Touchpad> for TouchpadSettings {
+    fn from(c: &niri_config::Touchpad) -> Self {
+        Self {
+            tap: c.tap,
+            dwt: c.dwt,
+            dwtp: c.dwtp,
+            drag: c.drag,
+            drag_lock: c.drag_lock,
+            click_method: c.click_method,
+            tap_button_map: c.tap_button_map,
+        }
+    }
+}
+
+impl From<&niri_config::InputDevice> for TouchpadSettings {
+    fn from(c: &niri_config::InputDevice) -> Self {
+        Self {
+            tap: c.tap,
+            dwt: c.dwt,
+            dwtp: c.dwtp,
+            drag: c.drag,
+            drag_lock: c.drag_lock,
+            click_method: c.click_method,
+            tap_button_map: c.tap_button_map,
+        }
+    }
+}
+
+/// Applies the libinput settings from the config to the device.
+///
+/// Returns the compositor-side scroll factor for the device, which libinput has no setting for.
+pub fn apply_libinput_settings(config: &niri_config::Input, device: &mut input::Device) -> f64 {
+    let Some(kind) = pointer_kind(device) else {
+        return 1.;
+    };
+    let is_touchpad = kind == PointerKind::Touchpad;
+
+    // A section for this specific device replaces the one for its type.
+    if let Some(c) = device_config(config, device) {
+        if is_touchpad {
+            apply_touchpad_settings(device, c.into());
+        }
+        let mut settings = PointerSettings::from(c);
+        // Only touchpads get disabled when an external mouse is connected.
+        settings.disabled_on_external_mouse &= is_touchpad;
+        apply_pointer_settings(device, settings);
+        return c.scroll_factor.unwrap_or(1.);
+    }
+
+    let scroll_factor = match kind {
+        PointerKind::Touchpad => {
+            apply_touchpad_settings(device, (&config.touchpad).into());
+            apply_pointer_settings(device, (&config.touchpad).into());
+            config.touchpad.scroll_factor
+        }
+        PointerKind::Trackball => {
+            apply_pointer_settings(device, (&config.trackball).into());
+            config.trackball.scroll_factor
+        }
+        PointerKind::Trackpoint => {
+            apply_pointer_settings(device, (&config.trackpoint).into());
+            config.trackpoint.scroll_factor
+        }
+        PointerKind::Mouse => {
+            apply_pointer_settings(device, (&config.mouse).into());
+            config.mouse.scroll_factor
+        }
+    };
+    scroll_factor.unwrap_or(1.)
+}
+
+fn apply_touchpad_settings(device: &mut input::Device, c: TouchpadSettings) {
+    let _ = device.config_tap_set_enabled(c.tap);
+    let _ = device.config_dwt_set_enabled(c.dwt);
+    let _ = device.config_dwtp_set_enabled(c.dwtp);
+    let _ = device.config_tap_set_drag_lock_enabled(c.drag_lock);
+
+    if let Some(drag) = c.drag {
+        let _ = device.config_tap_set_drag_enabled(drag);
+    } else {
+        let default = device.config_tap_default_drag_enabled();
+        let _ = device.config_tap_set_drag_enabled(default);
+    }
+
+    if let Some(click_method) = c.click_method {
+        let _ = device.config_click_set_method(click_method.into());
+    } else if let Some(default) = device.config_click_default_method() {
+        let _ = device.config_click_set_method(default);
+    }
+
+    if let Some(tap_button_map) = c.tap_button_map {
+        let _ = device.config_tap_set_button_map(tap_button_map.into());
+    } else if let Some(default) = device.config_tap_default_button_map() {
+        let _ = device.config_tap_set_button_map(default);
+    }
+}
+
+fn apply_pointer_settings(device: &mut input::Device, c: PointerSettings) {
+    let send_events_mode = if c.off {
+        input::SendEventsMode::DISABLED
+    } else if c.disabled_on_external_mouse {
+        input::SendEventsMode::DISABLED_ON_EXTERNAL_MOUSE
+    } else {
+        input::SendEventsMode::ENABLED
+    };
+    let _ = device.config_send_events_set_mode(send_events_mode);
+
+    let _ = device.config_scroll_set_natural_scroll_enabled(c.natural_scroll);
+    let _ = device.config_accel_set_speed(c.accel_speed);
+    let _ = device.config_left_handed_set(c.left_handed);
+    let _ = device.config_middle_emulation_set_enabled(c.middle_emulation);
+
+    if let Some(accel_profile) = c.accel_profile {
+        let _ = device.config_accel_set_profile(accel_profile.into());
+    } else if let Some(default) = device.config_accel_default_profile() {
+        let _ = device.config_accel_set_profile(default);
+    }
+
+    if let Some(scroll_method) = c.scroll_method {
+        let _ = device.config_scroll_set_method(scroll_method.into());
+    } else if let Some(default) = device.config_scroll_default_method() {
+        let _ = device.config_scroll_set_method(default);
+    }
+
+    // Only used with the on-button-down scroll method.
+    let scroll_button = c
+        .scroll_button
+        .unwrap_or_else(|| device.config_scroll_default_button());
+    let _ = device.config_scroll_set_button(scroll_button);
+}
+
 #[cfg(test)]
 mod tests {
     use niri_config::{Action, Bind, Binds, Key, Modifiers};
@@ -1591,6 +2199,7 @@ mod tests {
         let mut suppressed_keys = HashSet::new();
 
         let screenshot_ui = ScreenshotUi::new();
+        let window_switcher = WindowSwitcher::default();
         let disable_power_key_handling = false;
 
         // The key_code we pick is arbitrary, the only thing
@@ -1608,6 +2217,8 @@ mod tests {
                 pressed,
                 mods,
                 &screenshot_ui,
+                &window_switcher,
+                false,
                 disable_power_key_handling,
             )
         };
@@ -1624,6 +2235,8 @@ mod tests {
                 pressed,
                 mods,
                 &screenshot_ui,
+                &window_switcher,
+                false,
                 disable_power_key_handling,
             )
         };
@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How far back the swipe history goes for computing the velocity.
+const HISTORY_LIMIT: Duration = Duration::from_millis(150);
+/// Deceleration of the projected motion per millisecond, same as in GNOME Shell.
+const DECELERATION_TOUCHPAD: f64 = 0.997;
+
+/// Keeps track of recent swipe gesture deltas to compute its velocity on release.
+#[derive(Debug, Clone, Default)]
+pub struct SwipeTracker {
+    history: VecDeque<Event>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Event {
+    delta: f64,
+    timestamp: Duration,
+}
+
+impl SwipeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a gesture event.
+    pub fn push(&mut self, delta: f64, timestamp: Duration) {
+        self.history.push_back(Event { delta, timestamp });
+
+        // Drop events that are too old to matter.
+        while let Some(first) = self.history.front() {
+            if first.timestamp + HISTORY_LIMIT >= timestamp {
+                break;
+            }
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns the current gesture velocity, in units per second.
+    pub fn velocity(&self) -> f64 {
+        let (Some(first), Some(last)) = (self.history.front(), self.history.back()) else {
+            return 0.;
+        };
+
+        let total_time = last.timestamp.saturating_sub(first.timestamp).as_secs_f64();
+        if total_time == 0. {
+            return 0.;
+        }
+
+        let total_delta = self.history.iter().map(|event| event.delta).sum::<f64>();
+        total_delta / total_time
+    }
+
+    /// Returns how much further the motion would go if it decelerated naturally.
+    pub fn projected_offset(&self) -> f64 {
+        // The velocity is per second, while the deceleration is per millisecond.
+        -self.velocity() / (1000. * DECELERATION_TOUCHPAD.ln())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn velocity_uses_recent_events() {
+        let mut tracker = SwipeTracker::new();
+        assert_eq!(tracker.velocity(), 0.);
+
+        // A slow start that falls out of the history.
+        tracker.push(0.1, Duration::from_millis(0));
+        tracker.push(1., Duration::from_millis(500));
+        tracker.push(1., Duration::from_millis(510));
+        tracker.push(1., Duration::from_millis(520));
+
+        assert!((tracker.velocity() - 150.).abs() < 1e-9);
+        assert!(tracker.projected_offset() > 0.);
+    }
+}
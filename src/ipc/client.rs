@@ -1,10 +1,10 @@
 use std::env;
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Write};
 use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
 
 use anyhow::{bail, Context};
-use niri_ipc::{Mode, Output, Request, Response};
+use niri_ipc::{Event, Mode, Output, OutputConfigChanged, Request, Response, Window, Workspace};
 
 use crate::Msg;
 
@@ -19,10 +19,18 @@ pub fn handle_msg(msg: Msg, json: bool) -> anyhow::Result<()> {
     let mut stream =
         UnixStream::connect(socket_path).context("error connecting to {socket_path}")?;
 
-    let request = match msg {
+    let request = match &msg {
         Msg::Outputs => Request::Outputs,
+        Msg::Windows => Request::Windows,
+        Msg::Workspaces => Request::Workspaces,
+        Msg::Action { action } => Request::Action(action.clone()),
+        Msg::Output { output, action } => Request::Output {
+            output: output.clone(),
+            action: action.clone(),
+        },
+        Msg::EventStream => Request::EventStream,
     };
-    let mut buf = serde_json::to_vec(&request).unwrap();
+    let buf = serde_json::to_vec(&request).unwrap();
     stream
         .write_all(&buf)
         .context("error writing IPC request")?;
@@ -30,17 +38,16 @@ pub fn handle_msg(msg: Msg, json: bool) -> anyhow::Result<()> {
         .shutdown(Shutdown::Write)
         .context("error closing IPC stream for writing")?;
 
-    buf.clear();
-    stream
-        .read_to_end(&mut buf)
+    let mut reader = BufReader::new(stream);
+    let mut buf = String::new();
+    reader
+        .read_line(&mut buf)
         .context("error reading IPC response")?;
 
-    let response = serde_json::from_slice(&buf).context("error parsing IPC response")?;
+    let response = serde_json::from_str(&buf).context("error parsing IPC response")?;
     match msg {
         Msg::Outputs => {
-            #[allow(irrefutable_let_patterns)]
-            let Response::Outputs(outputs) = response
-            else {
+            let Response::Outputs(outputs) = response else {
                 bail!("unexpected response: expected Outputs, got {response:?}");
             };
 
@@ -100,6 +107,190 @@ pub fn handle_msg(msg: Msg, json: bool) -> anyhow::Result<()> {
                 println!();
             }
         }
+        Msg::Windows => {
+            let Response::Windows(windows) = response else {
+                bail!("unexpected response: expected Windows, got {response:?}");
+            };
+
+            if json {
+                let windows =
+                    serde_json::to_string(&windows).context("error formatting response")?;
+                println!("{windows}");
+                return Ok(());
+            }
+
+            for window in windows {
+                let Window {
+                    id,
+                    title,
+                    app_id,
+                    pid,
+                    output,
+                    workspace_index,
+                    column_index,
+                    tile_index,
+                    is_floating,
+                    size,
+                    is_fullscreen,
+                    is_focused,
+                } = window;
+
+                let focused = if is_focused { " (focused)" } else { "" };
+                println!("Window {id}{focused}");
+
+                println!(r#"  Title: "{}""#, title.as_deref().unwrap_or("(unset)"));
+                println!(r#"  App ID: "{}""#, app_id.as_deref().unwrap_or("(unset)"));
+
+                if let Some(pid) = pid {
+                    println!("  PID: {pid}");
+                } else {
+                    println!("  PID: unknown");
+                }
+
+                let output = output.as_deref().unwrap_or("(none)");
+                println!("  Output: {output}");
+                match (column_index, tile_index) {
+                    (Some(column_index), Some(tile_index)) => println!(
+                        "  Workspace {workspace_index}, column {column_index}, tile {tile_index}"
+                    ),
+                    _ => println!("  Workspace {workspace_index}"),
+                }
+
+                let (width, height) = size;
+                println!("  Size: {width}x{height}");
+
+                if is_fullscreen {
+                    println!("  Fullscreen");
+                }
+                if is_floating {
+                    println!("  Floating");
+                }
+                println!();
+            }
+        }
+        Msg::Workspaces => {
+            let Response::Workspaces(workspaces) = response else {
+                bail!("unexpected response: expected Workspaces, got {response:?}");
+            };
+
+            if json {
+                let workspaces =
+                    serde_json::to_string(&workspaces).context("error formatting response")?;
+                println!("{workspaces}");
+                return Ok(());
+            }
+
+            let mut current_output = None;
+            for workspace in workspaces {
+                let Workspace {
+                    index,
+                    name,
+                    output,
+                    original_output,
+                    is_active,
+                    is_empty,
+                    window_count,
+                } = workspace;
+
+                if current_output.as_ref() != Some(&output) {
+                    match &output {
+                        Some(output) => println!(r#"Output "{output}":"#),
+                        None => println!("No outputs:"),
+                    }
+                    current_output = Some(output.clone());
+                }
+
+                let active = if is_active { "*" } else { " " };
+                let windows = if is_empty {
+                    String::from("empty")
+                } else if window_count == 1 {
+                    String::from("1 window")
+                } else {
+                    format!("{window_count} windows")
+                };
+                match name {
+                    Some(name) => print!(r#"{active} {index} "{name}": {windows}"#),
+                    None => print!("{active} {index}: {windows}"),
+                }
+
+                if let Some(original_output) = original_output {
+                    if Some(&original_output) != output.as_ref() {
+                        print!(r#", originally from "{original_output}""#);
+                    }
+                }
+                println!();
+            }
+        }
+        Msg::Action { .. } => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+        }
+        Msg::Output { output, .. } => {
+            let Response::OutputConfigChanged(response) = response else {
+                bail!("unexpected response: expected OutputConfigChanged, got {response:?}");
+            };
+
+            if json {
+                let response =
+                    serde_json::to_string(&response).context("error formatting response")?;
+                println!("{response}");
+                return Ok(());
+            }
+
+            match response {
+                OutputConfigChanged::Applied => (),
+                OutputConfigChanged::OutputWasMissing => {
+                    println!("Output \"{output}\" is not connected.");
+                    println!("The change will apply when it is connected.");
+                }
+                OutputConfigChanged::FractionalScale => {
+                    bail!("fractional scales are not supported, nothing was changed");
+                }
+            }
+        }
+        Msg::EventStream => {
+            let Response::Handled = response else {
+                bail!("unexpected response: expected Handled, got {response:?}");
+            };
+
+            for line in reader.lines() {
+                let line = line.context("error reading IPC event")?;
+
+                if json {
+                    println!("{line}");
+                    continue;
+                }
+
+                let event: Event =
+                    serde_json::from_str(&line).context("error parsing IPC event")?;
+                match event {
+                    Event::OutputAdded { name } => println!("Output added: {name}"),
+                    Event::OutputRemoved { name } => println!("Output removed: {name}"),
+                    Event::WorkspaceActivated { output, index } => {
+                        println!("Workspace activated: {index} on {output}");
+                    }
+                    Event::WindowOpened { id, title, app_id } => {
+                        let title = title.as_deref().unwrap_or("(unset)");
+                        let app_id = app_id.as_deref().unwrap_or("(unset)");
+                        println!(r#"Window opened: {id}, title "{title}", app ID "{app_id}""#);
+                    }
+                    Event::WindowClosed { id } => println!("Window closed: {id}"),
+                    Event::WindowTitleChanged { id, title } => {
+                        let title = title.as_deref().unwrap_or("(unset)");
+                        println!(r#"Window title changed: {id}, title "{title}""#);
+                    }
+                    Event::WindowFocusChanged { id } => match id {
+                        Some(id) => println!("Window focus changed: {id}"),
+                        None => println!("Window focus changed: none"),
+                    },
+                    Event::KeyboardLayoutSwitched { index } => {
+                        println!("Keyboard layout switched: {index}");
+                    }
+                    Event::ConfigReloaded => println!("Config reloaded"),
+                }
+            }
+        }
     }
 
     Ok(())
@@ -6,25 +6,54 @@ use std::rc::Rc;
 use std::{env, io, process};
 
 use anyhow::Context;
+use async_channel::{Receiver, Sender, TrySendError};
 use calloop::io::Async;
 use directories::BaseDirs;
 use futures_util::io::{AsyncReadExt, BufReader};
 use futures_util::{AsyncBufReadExt, AsyncWriteExt};
-use niri_ipc::{Request, Response};
+use niri_ipc::{Event, Request, Response};
+use smithay::desktop::Window;
 use smithay::reexports::calloop::generic::Generic;
 use smithay::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
 use smithay::reexports::rustix::fs::unlink;
+use smithay::reexports::wayland_server::Resource;
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::XdgToplevelSurfaceData;
 
 use crate::niri::State;
+use crate::utils::window_id;
+
+/// How many events can queue up for an event stream client before it is disconnected.
+const EVENT_STREAM_BUFFER_SIZE: usize = 256;
 
 pub struct IpcServer {
     pub socket_path: PathBuf,
+    event_streams: RefCell<EventStreams>,
 }
 
 struct ClientCtx {
+    event_loop: LoopHandle<'static, State>,
     ipc_outputs: Rc<RefCell<HashMap<String, niri_ipc::Output>>>,
 }
 
+/// Event stream clients along with the state needed to tell when something changed.
+///
+/// The state is only kept up to date while there are clients, and is reset from scratch when a
+/// new client connects.
+#[derive(Default)]
+struct EventStreams {
+    senders: Vec<Sender<Event>>,
+    /// Titles of mapped windows, to tell title changes apart from other commits.
+    titles: HashMap<u64, Option<String>>,
+    keyboard_layout: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct WindowState {
+    title: Option<String>,
+    app_id: Option<String>,
+}
+
 impl IpcServer {
     pub fn start(
         event_loop: &LoopHandle<'static, State>,
@@ -54,7 +83,90 @@ impl IpcServer {
             })
             .unwrap();
 
-        Ok(Self { socket_path })
+        Ok(Self {
+            socket_path,
+            event_streams: Default::default(),
+        })
+    }
+
+    /// Sends an event to all event stream clients.
+    pub fn send_event(&self, event: Event) {
+        self.event_streams.borrow_mut().send(event);
+    }
+
+    /// Tells event stream clients that a window was mapped.
+    pub fn window_opened(&self, window: &Window) {
+        let mut streams = self.event_streams.borrow_mut();
+        if streams.senders.is_empty() {
+            return;
+        }
+
+        let WindowState { title, app_id } = window_state(window);
+        streams.send(Event::WindowOpened {
+            id: window_id(window),
+            title,
+            app_id,
+        });
+    }
+
+    /// Tells event stream clients that a mapped window was unmapped or destroyed.
+    pub fn window_closed(&self, window: &Window) {
+        self.send_event(Event::WindowClosed {
+            id: window_id(window),
+        });
+    }
+
+    /// Tells event stream clients about the new title of a mapped window, if it changed.
+    ///
+    /// Should be called on commits of mapped windows.
+    pub fn check_window_title(&self, window: &Window) {
+        let mut streams = self.event_streams.borrow_mut();
+        if streams.senders.is_empty() {
+            return;
+        }
+
+        let id = window_id(window);
+        let title = window_state(window).title;
+        match streams.titles.get(&id) {
+            Some(old) if *old != title => streams.send(Event::WindowTitleChanged { id, title }),
+            _ => (),
+        }
+    }
+}
+
+impl EventStreams {
+    fn send(&mut self, event: Event) {
+        if self.senders.is_empty() {
+            return;
+        }
+
+        self.apply(&event);
+
+        self.senders
+            .retain(|sender| match sender.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("IPC event stream client is not reading events, disconnecting");
+                    false
+                }
+                Err(TrySendError::Closed(_)) => false,
+            });
+    }
+
+    /// Updates the known state according to the event.
+    fn apply(&mut self, event: &Event) {
+        match event {
+            Event::WindowOpened { id, title, .. } | Event::WindowTitleChanged { id, title } => {
+                self.titles.insert(*id, title.clone());
+            }
+            Event::WindowClosed { id } => {
+                self.titles.remove(id);
+            }
+            Event::KeyboardLayoutSwitched { index } => {
+                self.keyboard_layout = Some(*index);
+            }
+            _ => (),
+        }
     }
 }
 
@@ -85,6 +197,7 @@ fn on_new_ipc_client(state: &mut State, stream: UnixStream) {
     };
 
     let ctx = ClientCtx {
+        event_loop: state.niri.event_loop.clone(),
         ipc_outputs: state.backend.ipc_outputs(),
     };
 
@@ -110,18 +223,249 @@ async fn handle_client(ctx: ClientCtx, stream: Async<'_, UnixStream>) -> anyhow:
 
     let request: Request = serde_json::from_str(&buf).context("error parsing request")?;
 
+    let mut events = None;
     let response = match request {
         Request::Outputs => {
             let ipc_outputs = ctx.ipc_outputs.borrow().clone();
             Response::Outputs(ipc_outputs)
         }
+        Request::Windows => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(ipc_windows(state));
+            });
+            let windows = rx.recv().await.context("error getting window info")?;
+            Response::Windows(windows)
+        }
+        Request::Workspaces => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(ipc_workspaces(state));
+            });
+            let workspaces = rx.recv().await.context("error getting workspace info")?;
+            Response::Workspaces(workspaces)
+        }
+        Request::Action(action) => {
+            ctx.event_loop.insert_idle(move |state| {
+                state.do_action(action);
+            });
+            Response::Handled
+        }
+        Request::Output { output, action } => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(state.apply_transient_output_config(&output, action));
+            });
+            let response = rx.recv().await.context("error applying output config")?;
+            Response::OutputConfigChanged(response)
+        }
+        Request::EventStream => {
+            let (tx, rx) = async_channel::bounded(1);
+            ctx.event_loop.insert_idle(move |state| {
+                let _ = tx.send_blocking(add_event_stream(state));
+            });
+            let receiver = rx.recv().await.context("error starting event stream")?;
+            events = Some(receiver);
+            Response::Handled
+        }
     };
 
-    let buf = serde_json::to_vec(&response).context("error formatting response")?;
+    let mut buf = serde_json::to_vec(&response).context("error formatting response")?;
+    buf.push(b'\n');
     write
         .write_all(&buf)
         .await
         .context("error writing response")?;
 
+    let Some(events) = events else {
+        return Ok(());
+    };
+
+    // The sender is dropped when the client falls too far behind or when niri exits.
+    while let Ok(event) = events.recv().await {
+        let mut buf = serde_json::to_vec(&event).context("error formatting event")?;
+        buf.push(b'\n');
+        write.write_all(&buf).await.context("error writing event")?;
+    }
+
     Ok(())
 }
+
+fn ipc_windows(state: &State) -> Vec<niri_ipc::Window> {
+    let niri = &state.niri;
+    let mut windows = vec![];
+
+    niri.layout.with_workspaces(|ws, output, ws_idx| {
+        let mut push = |window: &Window, position: Option<(usize, usize)>, is_fullscreen: bool| {
+            let wl_surface = window.toplevel().wl_surface();
+            let WindowState { title, app_id } = window_state(window);
+
+            let pid = wl_surface
+                .client()
+                .and_then(|client| client.get_credentials(&niri.display_handle).ok())
+                .map(|credentials| credentials.pid);
+
+            let size = window.geometry().size;
+
+            windows.push(niri_ipc::Window {
+                id: window_id(window),
+                title,
+                app_id,
+                pid,
+                output: output.map(|output| output.name()),
+                workspace_index: ws_idx + 1,
+                column_index: position.map(|(col_idx, _)| col_idx + 1),
+                tile_index: position.map(|(_, tile_idx)| tile_idx + 1),
+                is_floating: position.is_none(),
+                size: (size.w, size.h),
+                is_fullscreen,
+                is_focused: niri.keyboard_focus.as_ref() == Some(wl_surface),
+            });
+        };
+
+        for (col_idx, col) in ws.columns.iter().enumerate() {
+            for (tile_idx, tile) in col.tiles.iter().enumerate() {
+                push(tile.window(), Some((col_idx, tile_idx)), col.is_fullscreen);
+            }
+        }
+
+        for window in ws.floating_windows() {
+            push(window, None, false);
+        }
+    });
+
+    windows
+}
+
+fn ipc_workspaces(state: &State) -> Vec<niri_ipc::Workspace> {
+    let layout = &state.niri.layout;
+    let mut workspaces = vec![];
+
+    layout.with_workspaces(|ws, output, idx| {
+        let is_active = output
+            .and_then(|output| layout.monitor_for_output(output))
+            .is_some_and(|mon| mon.active_workspace_idx == idx);
+        let window_count = ws.windows().count();
+
+        workspaces.push(niri_ipc::Workspace {
+            index: idx + 1,
+            name: ws.name.clone(),
+            output: output.map(|output| output.name()),
+            original_output: ws.original_output.name().map(String::from),
+            is_active,
+            is_empty: window_count == 0,
+            window_count,
+        });
+    });
+
+    workspaces
+}
+
+fn window_state(window: &Window) -> WindowState {
+    with_states(window.toplevel().wl_surface(), |states| {
+        let role = states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .unwrap()
+            .lock()
+            .unwrap();
+
+        WindowState {
+            title: role.title.clone(),
+            app_id: role.app_id.clone(),
+        }
+    })
+}
+
+/// Registers a new event stream client, starting its stream with events describing the current
+/// state.
+fn add_event_stream(state: &mut State) -> Receiver<Event> {
+    let keyboard = state.niri.seat.get_keyboard().unwrap();
+    let keyboard_layout = keyboard.with_xkb_state(state, |context| context.active_layout().0);
+
+    let niri = &state.niri;
+    let mut events = vec![];
+
+    let mut outputs: Vec<_> = niri.output_by_name.keys().cloned().collect();
+    outputs.sort_unstable();
+    for name in outputs {
+        events.push(Event::OutputAdded { name });
+    }
+
+    for output in niri.layout.outputs() {
+        let mon = niri.layout.monitor_for_output(output).unwrap();
+        events.push(Event::WorkspaceActivated {
+            output: output.name(),
+            index: mon.active_workspace_idx + 1,
+        });
+    }
+
+    let mut titles = HashMap::new();
+    niri.layout.with_windows(|window, _| {
+        let id = window_id(window);
+        let WindowState { title, app_id } = window_state(window);
+        titles.insert(id, title.clone());
+        events.push(Event::WindowOpened { id, title, app_id });
+    });
+
+    let focused_window = niri
+        .keyboard_focus
+        .as_ref()
+        .and_then(|surface| niri.layout.find_window_and_output(surface))
+        .map(|(window, _)| window_id(window));
+    events.push(Event::WindowFocusChanged { id: focused_window });
+
+    events.push(Event::KeyboardLayoutSwitched {
+        index: keyboard_layout,
+    });
+
+    let (tx, rx) = async_channel::bounded(events.len() + EVENT_STREAM_BUFFER_SIZE);
+    for event in events {
+        tx.try_send(event).unwrap();
+    }
+
+    let mut streams = niri.ipc_server.as_ref().unwrap().event_streams.borrow_mut();
+    streams.titles = titles;
+    streams.keyboard_layout = Some(keyboard_layout);
+    streams.senders.push(tx);
+
+    rx
+}
+
+/// Sends IPC events for workspace activations and keyboard layout switches.
+///
+/// Should be called periodically. Window events are sent right where windows change.
+pub fn refresh(state: &mut State) {
+    let _span = tracy_client::span!("ipc::server::refresh");
+
+    // Take the activations even without clients so that they don't pile up.
+    let activations = state.niri.layout.take_workspace_activations();
+
+    let Some(server) = &state.niri.ipc_server else {
+        return;
+    };
+    if server.event_streams.borrow().senders.is_empty() {
+        return;
+    }
+
+    // The layout can also be switched by xkb on its own, for example with grp: options, so there's
+    // no single place to send this event from.
+    let keyboard = state.niri.seat.get_keyboard().unwrap();
+    let keyboard_layout = keyboard.with_xkb_state(state, |context| context.active_layout().0);
+
+    let server = state.niri.ipc_server.as_ref().unwrap();
+    let mut streams = server.event_streams.borrow_mut();
+
+    for (output, idx) in activations {
+        streams.send(Event::WorkspaceActivated {
+            output: output.name(),
+            index: idx + 1,
+        });
+    }
+
+    if streams.keyboard_layout != Some(keyboard_layout) {
+        streams.send(Event::KeyboardLayoutSwitched {
+            index: keyboard_layout,
+        });
+    }
+}
@@ -0,0 +1,309 @@
+use std::cmp::{max, min};
+use std::rc::Rc;
+use std::time::Duration;
+
+use niri_config::SizeChange;
+use smithay::utils::{Logical, Point, Rectangle, Size};
+
+use super::tile::Tile;
+use super::{LayoutElement, Options};
+
+/// How much of a floating tile must remain within the working area, in logical pixels.
+const MIN_VISIBLE: i32 = 50;
+
+/// Floating layer of a workspace.
+///
+/// Windows here have a free position and size, and are drawn on top of the tiled columns.
+#[derive(Debug)]
+pub struct FloatingSpace<W: LayoutElement> {
+    /// Tiles in the floating layer, topmost first.
+    ///
+    /// The first tile is the active one.
+    tiles: Vec<Tile<W>>,
+
+    /// Positions of the tiles within the view, matching `tiles`.
+    positions: Vec<Point<i32, Logical>>,
+
+    /// Latest known view size for this space.
+    view_size: Size<i32, Logical>,
+
+    /// Latest known working area for this space.
+    working_area: Rectangle<i32, Logical>,
+
+    /// Configurable properties of the layout.
+    options: Rc<Options>,
+}
+
+impl<W: LayoutElement> FloatingSpace<W> {
+    pub fn new(
+        view_size: Size<i32, Logical>,
+        working_area: Rectangle<i32, Logical>,
+        options: Rc<Options>,
+    ) -> Self {
+        Self {
+            tiles: vec![],
+            positions: vec![],
+            view_size,
+            working_area,
+            options,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &W> + '_ {
+        self.tiles.iter().map(Tile::window)
+    }
+
+    pub fn has_window(&self, window: &W) -> bool {
+        self.windows().any(|win| win == window)
+    }
+
+    fn idx_of(&self, window: &W) -> Option<usize> {
+        self.windows().position(|win| win == window)
+    }
+
+    pub fn active_window(&self) -> Option<&W> {
+        self.tiles.first().map(Tile::window)
+    }
+
+    /// Returns the position and size of the active tile.
+    pub fn active_tile_geometry(&self) -> Option<(Point<i32, Logical>, Size<i32, Logical>, bool)> {
+        let tile = self.tiles.first()?;
+        Some((self.positions[0], tile.tile_size(), tile.has_ssd()))
+    }
+
+    pub fn update_config(&mut self, options: Rc<Options>) {
+        for tile in &mut self.tiles {
+            tile.update_config(options.clone());
+        }
+
+        self.options = options;
+    }
+
+    pub fn set_view_size(
+        &mut self,
+        view_size: Size<i32, Logical>,
+        working_area: Rectangle<i32, Logical>,
+    ) {
+        self.view_size = view_size;
+        self.working_area = working_area;
+
+        for idx in 0..self.tiles.len() {
+            self.positions[idx] = self.clamp_position(idx, self.positions[idx]);
+        }
+    }
+
+    pub fn advance_animations(&mut self, current_time: Duration, is_active: bool) {
+        for (idx, tile) in self.tiles.iter_mut().enumerate() {
+            tile.advance_animations(current_time, is_active && idx == 0);
+        }
+    }
+
+    /// Adds a window to the floating layer, centering it in the working area.
+    ///
+    /// An activated window goes on top, otherwise it goes right below the active window.
+    pub fn add_window(&mut self, window: W, activate: bool) {
+        // Floating windows are never maximized.
+        window.request_maximized(false);
+        let tile = Tile::new(window, self.options.clone());
+
+        let size = tile.tile_size();
+        let area = self.working_area;
+        let pos = Point::from((
+            area.loc.x + (area.size.w - size.w) / 2,
+            area.loc.y + (area.size.h - size.h) / 2,
+        ));
+
+        let idx = if activate {
+            0
+        } else {
+            min(1, self.tiles.len())
+        };
+        self.tiles.insert(idx, tile);
+        self.positions.insert(idx, pos);
+        self.positions[idx] = self.clamp_position(idx, pos);
+    }
+
+    pub fn remove_window(&mut self, window: &W) -> W {
+        let idx = self.idx_of(window).unwrap();
+        self.positions.remove(idx);
+        self.tiles.remove(idx).into_window()
+    }
+
+    /// Removes the active window, if any.
+    pub fn remove_active_window(&mut self) -> Option<W> {
+        if self.tiles.is_empty() {
+            return None;
+        }
+
+        self.positions.remove(0);
+        Some(self.tiles.remove(0).into_window())
+    }
+
+    pub fn update_window(&mut self, window: &W) {
+        let idx = self.idx_of(window).unwrap();
+        self.tiles[idx].update_window();
+        self.positions[idx] = self.clamp_position(idx, self.positions[idx]);
+    }
+
+    /// Raises the window to the top and makes it active.
+    pub fn activate_window(&mut self, window: &W) {
+        let idx = self.idx_of(window).unwrap();
+        let tile = self.tiles.remove(idx);
+        let pos = self.positions.remove(idx);
+        self.tiles.insert(0, tile);
+        self.positions.insert(0, pos);
+    }
+
+    /// Returns the Y position of the window's visual geometry within the view.
+    pub fn window_y(&self, window: &W) -> Option<i32> {
+        let idx = self.idx_of(window)?;
+        Some(self.positions[idx].y + self.tiles[idx].window_loc().y)
+    }
+
+    /// Moves the active window by the given amount of logical pixels.
+    pub fn move_active_by(&mut self, delta: Point<i32, Logical>) {
+        if self.tiles.is_empty() {
+            return;
+        }
+
+        let pos = self.positions[0] + delta;
+        self.positions[0] = self.clamp_position(0, pos);
+    }
+
+    /// Moves the window to the given position, keeping it within the working area.
+    pub fn set_window_position(&mut self, window: &W, pos: Point<i32, Logical>) {
+        let idx = self.idx_of(window).unwrap();
+        self.positions[idx] = self.clamp_position(idx, pos);
+    }
+
+    pub fn move_window_by(&mut self, window: &W, delta: Point<i32, Logical>) {
+        let idx = self.idx_of(window).unwrap();
+        let pos = self.positions[idx] + delta;
+        self.positions[idx] = self.clamp_position(idx, pos);
+    }
+
+    pub fn set_window_width(&mut self, change: SizeChange) {
+        let Some(tile) = self.tiles.first() else {
+            return;
+        };
+
+        let current = tile.window_size();
+        let available = self.working_area.size.w - self.options.gaps * 2;
+        let width = resolve_size_change(change, current.w, available);
+
+        let win = tile.window();
+        let width = clamp_to_constraints(width, win.min_size().w, win.max_size().w);
+        tile.window().request_size(Size::from((width, current.h)));
+    }
+
+    pub fn set_window_height(&mut self, change: SizeChange) {
+        let Some(tile) = self.tiles.first() else {
+            return;
+        };
+
+        let current = tile.window_size();
+        let available = self.working_area.size.h - self.options.gaps * 2;
+        let height = resolve_size_change(change, current.h, available);
+
+        let win = tile.window();
+        let height = clamp_to_constraints(height, win.min_size().h, win.max_size().h);
+        tile.window().request_size(Size::from((current.w, height)));
+    }
+
+    pub fn tile(&self, window: &W) -> Option<&Tile<W>> {
+        let idx = self.idx_of(window)?;
+        Some(&self.tiles[idx])
+    }
+
+    /// Requests a tile size for the window, clamped to its size constraints.
+    pub fn request_tile_size(&mut self, window: &W, size: Size<i32, Logical>) {
+        let idx = self.idx_of(window).unwrap();
+        let tile = &mut self.tiles[idx];
+
+        let min_size = tile.min_size();
+        let max_size = tile.max_size();
+        let w = clamp_to_constraints(size.w.max(1), min_size.w, max_size.w);
+        let h = clamp_to_constraints(size.h.max(1), min_size.h, max_size.h);
+        tile.request_tile_size(Size::from((w, h)));
+    }
+
+    /// Keeps at least some part of the tile within the working area.
+    fn clamp_position(&self, idx: usize, pos: Point<i32, Logical>) -> Point<i32, Logical> {
+        let size = self.tiles[idx].tile_size();
+        let area = self.working_area;
+
+        let min_x = area.loc.x - size.w + min(MIN_VISIBLE, size.w);
+        let max_x = area.loc.x + area.size.w - min(MIN_VISIBLE, size.w);
+        let min_y = area.loc.y;
+        let max_y = area.loc.y + area.size.h - min(MIN_VISIBLE, size.h);
+
+        Point::from((
+            pos.x.clamp(min_x, max(min_x, max_x)),
+            pos.y.clamp(min_y, max(min_y, max_y)),
+        ))
+    }
+
+    /// Returns tiles with their positions, topmost first.
+    pub fn tiles_with_positions(&self) -> impl Iterator<Item = (&Tile<W>, Point<i32, Logical>)> {
+        self.tiles.iter().zip(self.positions.iter().copied())
+    }
+
+    pub fn window_under(
+        &self,
+        pos: Point<f64, Logical>,
+    ) -> Option<(&W, Option<Point<i32, Logical>>)> {
+        self.tiles_with_positions().find_map(|(tile, tile_pos)| {
+            let pos_within_tile = pos - tile_pos.to_f64();
+
+            if tile.is_in_input_region(pos_within_tile) {
+                let pos_within_surface = tile_pos + tile.buf_loc();
+                return Some((tile.window(), Some(pos_within_surface)));
+            } else if tile.is_in_activation_region(pos_within_tile) {
+                return Some((tile.window(), None));
+            }
+
+            None
+        })
+    }
+
+    #[cfg(test)]
+    pub fn verify_invariants(&self) {
+        assert_eq!(self.tiles.len(), self.positions.len());
+
+        for tile in &self.tiles {
+            assert!(!tile.window().is_pending_maximized());
+        }
+    }
+}
+
+fn resolve_size_change(change: SizeChange, current: i32, available: i32) -> i32 {
+    // FIXME: fix overflows then remove limits.
+    const MAX_PX: i32 = 100000;
+
+    let proportion_to_px = |proportion: f64| (available as f64 * proportion).round() as i32;
+
+    let size = match change {
+        SizeChange::SetFixed(fixed) => fixed,
+        SizeChange::SetProportion(proportion) => proportion_to_px(proportion / 100.),
+        SizeChange::AdjustFixed(delta) => current.saturating_add(delta),
+        SizeChange::AdjustProportion(delta) => {
+            current.saturating_add(proportion_to_px(delta / 100.))
+        }
+    };
+
+    size.clamp(1, MAX_PX)
+}
+
+fn clamp_to_constraints(mut size: i32, min_size: i32, max_size: i32) -> i32 {
+    if max_size > 0 {
+        size = size.min(max_size);
+    }
+    if min_size > 0 {
+        size = size.max(min_size);
+    }
+    size
+}
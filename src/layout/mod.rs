@@ -46,18 +46,25 @@ use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::utils::{Logical, Point, Rectangle, Scale, Size, Transform};
 use smithay::wayland::compositor::{send_surface_state, with_states};
-use smithay::wayland::shell::xdg::SurfaceCachedState;
+use smithay::wayland::shell::xdg::{SurfaceCachedState, XdgToplevelSurfaceData};
 
 pub use self::monitor::MonitorRenderElement;
 use self::monitor::{Monitor, WorkspaceSwitch, WorkspaceSwitchGesture};
-use self::workspace::{compute_working_area, Column, ColumnWidth, OutputId, Workspace};
+use self::snapshot::Restore;
+use self::workspace::{
+    compute_working_area, Column, ColumnWidth, InsertPosition, OutputId, Workspace,
+};
 use crate::animation::Animation;
+use crate::input::swipe_tracker::SwipeTracker;
 use crate::niri_render_elements;
 use crate::render_helpers::NiriRenderer;
-use crate::utils::output_size;
+use crate::utils::{output_size, ResizeEdge};
 
+pub mod floating;
 pub mod focus_ring;
 pub mod monitor;
+pub mod snapshot;
+pub mod tab_bar;
 pub mod tile;
 pub mod workspace;
 
@@ -98,6 +105,7 @@ pub trait LayoutElement: PartialEq {
 
     fn request_size(&self, size: Size<i32, Logical>);
     fn request_fullscreen(&self, size: Size<i32, Logical>);
+    fn request_maximized(&self, maximized: bool);
     fn min_size(&self) -> Size<i32, Logical>;
     fn max_size(&self) -> Size<i32, Logical>;
     fn is_wl_surface(&self, wl_surface: &WlSurface) -> bool;
@@ -115,6 +123,12 @@ pub trait LayoutElement: PartialEq {
     ///
     /// This *will* switch immediately after a [`LayoutElement::request_fullscreen()`] call.
     fn is_pending_fullscreen(&self) -> bool;
+
+    /// Whether we're requesting the element to be maximized.
+    fn is_pending_maximized(&self) -> bool;
+
+    /// Title of the element, if it has one.
+    fn title(&self) -> Option<String>;
 }
 
 #[derive(Debug)]
@@ -123,6 +137,23 @@ pub struct Layout<W: LayoutElement> {
     monitor_set: MonitorSet<W>,
     /// Configurable properties of the layout.
     options: Rc<Options>,
+    /// Named scratchpads holding windows set aside from the workspaces.
+    scratchpads: Vec<Scratchpad<W>>,
+    /// Saved layout that new windows are put back into, if any.
+    restore: Option<Restore<W>>,
+}
+
+/// Window set aside in a named scratchpad.
+#[derive(Debug)]
+struct Scratchpad<W: LayoutElement> {
+    /// Name of the scratchpad, empty for the default one.
+    name: String,
+    /// The window.
+    ///
+    /// While the scratchpad is shown, this is a clone of the window in the layout.
+    window: W,
+    /// Whether the window is hidden rather than shown on some workspace.
+    is_hidden: bool,
 }
 
 #[derive(Debug)]
@@ -156,6 +187,7 @@ pub struct Options {
     pub preset_widths: Vec<ColumnWidth>,
     /// Initial width for new columns.
     pub default_width: Option<ColumnWidth>,
+    pub animations: niri_config::Animations,
 }
 
 impl Default for Options {
@@ -172,6 +204,7 @@ impl Default for Options {
                 ColumnWidth::Proportion(2. / 3.),
             ],
             default_width: None,
+            animations: Default::default(),
         }
     }
 }
@@ -207,6 +240,7 @@ impl Options {
             center_focused_column: layout.center_focused_column,
             preset_widths,
             default_width,
+            animations: config.animations,
         }
     }
 }
@@ -254,6 +288,16 @@ impl LayoutElement for Window {
         });
     }
 
+    fn request_maximized(&self, maximized: bool) {
+        self.toplevel().with_pending_state(|state| {
+            if maximized {
+                state.states.set(xdg_toplevel::State::Maximized);
+            } else {
+                state.states.unset(xdg_toplevel::State::Maximized);
+            }
+        });
+    }
+
     fn min_size(&self) -> Size<i32, Logical> {
         with_states(self.toplevel().wl_surface(), |state| {
             let curr = state.cached_state.current::<SurfaceCachedState>();
@@ -303,13 +347,40 @@ impl LayoutElement for Window {
         self.toplevel()
             .with_pending_state(|state| state.states.contains(xdg_toplevel::State::Fullscreen))
     }
+
+    fn is_pending_maximized(&self) -> bool {
+        self.toplevel()
+            .with_pending_state(|state| state.states.contains(xdg_toplevel::State::Maximized))
+    }
+
+    fn title(&self) -> Option<String> {
+        with_states(self.toplevel().wl_surface(), |states| {
+            let role = states
+                .data_map
+                .get::<XdgToplevelSurfaceData>()
+                .unwrap()
+                .lock()
+                .unwrap();
+            role.title.clone()
+        })
+    }
 }
 
 impl<W: LayoutElement> Layout<W> {
     pub fn new(config: &Config) -> Self {
+        let options = Rc::new(Options::from_config(config));
+
+        let workspaces = config
+            .workspaces
+            .iter()
+            .map(|ws_config| Workspace::new_with_config(None, ws_config, options.clone()))
+            .collect();
+
         Self {
-            monitor_set: MonitorSet::NoOutputs { workspaces: vec![] },
-            options: Rc::new(Options::from_config(config)),
+            monitor_set: MonitorSet::NoOutputs { workspaces },
+            options,
+            scratchpads: Vec::new(),
+            restore: None,
         }
     }
 
@@ -341,14 +412,15 @@ impl<W: LayoutElement> Layout<W> {
 
                         // The user could've closed a window while remaining on this workspace, on
                         // another monitor. However, we will add an empty workspace in the end
-                        // instead.
-                        if ws.has_windows() {
+                        // instead. Named workspaces are kept regardless.
+                        if ws.has_windows_or_name() {
                             workspaces.push(ws);
                         }
 
                         if i <= primary.active_workspace_idx {
-                            primary.active_workspace_idx =
-                                primary.active_workspace_idx.saturating_sub(1);
+                            primary.set_active_workspace_idx(
+                                primary.active_workspace_idx.saturating_sub(1),
+                            );
                         }
                     }
                 }
@@ -367,7 +439,10 @@ impl<W: LayoutElement> Layout<W> {
                     ws.set_output(Some(output.clone()));
                 }
 
-                monitors.push(Monitor::new(output, workspaces, self.options.clone()));
+                let overview_open = monitors.iter().any(|mon| mon.is_overview_open());
+                let mut monitor = Monitor::new(output, workspaces, self.options.clone());
+                monitor.set_overview_open(overview_open);
+                monitors.push(monitor);
                 MonitorSet::Normal {
                     monitors,
                     primary_idx,
@@ -375,7 +450,7 @@ impl<W: LayoutElement> Layout<W> {
                 }
             }
             MonitorSet::NoOutputs { mut workspaces } => {
-                // We know there are no empty workspaces there, so add one.
+                // We know there are no empty unnamed workspaces there, so add one.
                 workspaces.push(Workspace::new(output.clone(), self.options.clone()));
 
                 for workspace in &mut workspaces {
@@ -411,8 +486,8 @@ impl<W: LayoutElement> Layout<W> {
                     ws.set_output(None);
                 }
 
-                // Get rid of empty workspaces.
-                workspaces.retain(|ws| ws.has_windows());
+                // Get rid of empty workspaces, keeping the named ones.
+                workspaces.retain(|ws| ws.has_windows_or_name());
 
                 if monitors.is_empty() {
                     // Removed the last monitor.
@@ -446,7 +521,7 @@ impl<W: LayoutElement> Layout<W> {
 
                     // If the empty workspace was focused on the primary monitor, keep it focused.
                     if empty_was_focused {
-                        primary.active_workspace_idx = primary.workspaces.len() - 1;
+                        primary.set_active_workspace_idx(primary.workspaces.len() - 1);
                     }
 
                     MonitorSet::Normal {
@@ -487,6 +562,29 @@ impl<W: LayoutElement> Layout<W> {
         }
     }
 
+    pub fn add_floating_window_by_idx(
+        &mut self,
+        monitor_idx: usize,
+        workspace_idx: usize,
+        window: W,
+        activate: bool,
+    ) {
+        let MonitorSet::Normal {
+            monitors,
+            active_monitor_idx,
+            ..
+        } = &mut self.monitor_set
+        else {
+            panic!()
+        };
+
+        monitors[monitor_idx].add_floating_window(workspace_idx, window, activate);
+
+        if activate {
+            *active_monitor_idx = monitor_idx;
+        }
+    }
+
     pub fn add_column_by_idx(
         &mut self,
         monitor_idx: usize,
@@ -518,6 +616,26 @@ impl<W: LayoutElement> Layout<W> {
         window: W,
         width: Option<ColumnWidth>,
         is_full_width: bool,
+    ) -> Option<&Output> {
+        self.add_window_to(window, None, None, None, width, is_full_width, false)
+    }
+
+    /// Adds a new window to the layout on a specific output and workspace.
+    ///
+    /// `output` and `workspace_idx` default to the active ones. `activate` defaults to activating
+    /// the window unless that would steal focus from a fullscreen window. With `is_floating` the
+    /// window goes to the floating layer instead of a new column.
+    ///
+    /// Returns an output that the window was added to, if there were any outputs.
+    pub fn add_window_to(
+        &mut self,
+        window: W,
+        output: Option<&Output>,
+        workspace_idx: Option<usize>,
+        activate: Option<bool>,
+        width: Option<ColumnWidth>,
+        is_full_width: bool,
+        is_floating: bool,
     ) -> Option<&Output> {
         let width = width
             .or(self.options.default_width)
@@ -529,47 +647,62 @@ impl<W: LayoutElement> Layout<W> {
                 active_monitor_idx,
                 ..
             } => {
-                let mon = &mut monitors[*active_monitor_idx];
+                let mon_idx = output
+                    .and_then(|output| monitors.iter().position(|mon| &mon.output == output))
+                    .unwrap_or(*active_monitor_idx);
+                let mon = &mut monitors[mon_idx];
+
+                let workspace_idx = workspace_idx
+                    .map(|idx| min(idx, mon.workspaces.len() - 1))
+                    .unwrap_or(mon.active_workspace_idx);
 
                 // Don't steal focus from an active fullscreen window.
-                let mut activate = true;
-                let ws = &mon.workspaces[mon.active_workspace_idx];
-                if !ws.columns.is_empty() && ws.columns[ws.active_column_idx].is_fullscreen {
-                    activate = false;
+                let activate = activate.unwrap_or_else(|| {
+                    let ws = &mon.workspaces[mon.active_workspace_idx];
+                    ws.floating_is_active()
+                        || ws.columns.is_empty()
+                        || !ws.columns[ws.active_column_idx].is_fullscreen
+                });
+
+                if is_floating {
+                    mon.add_floating_window(workspace_idx, window, activate);
+                } else {
+                    mon.add_window(workspace_idx, window, activate, width, is_full_width);
+                }
+
+                if activate {
+                    *active_monitor_idx = mon_idx;
                 }
 
-                mon.add_window(
-                    mon.active_workspace_idx,
-                    window,
-                    activate,
-                    width,
-                    is_full_width,
-                );
                 Some(&mon.output)
             }
             MonitorSet::NoOutputs { workspaces } => {
-                let ws = if let Some(ws) = workspaces.get_mut(0) {
-                    ws
-                } else {
+                if workspaces.is_empty() {
                     workspaces.push(Workspace::new_no_outputs(self.options.clone()));
-                    &mut workspaces[0]
-                };
-                ws.add_window(window, true, width, is_full_width);
+                }
+
+                let workspace_idx = workspace_idx.map_or(0, |idx| min(idx, workspaces.len() - 1));
+                let ws = &mut workspaces[workspace_idx];
+                if is_floating {
+                    ws.add_floating_window(window, activate.unwrap_or(true));
+                } else {
+                    ws.add_window(window, activate.unwrap_or(true), width, is_full_width);
+                }
                 None
             }
         }
     }
 
-    pub fn remove_window(&mut self, window: &W) {
+    pub fn remove_window(&mut self, window: &W) -> Option<W> {
         match &mut self.monitor_set {
             MonitorSet::Normal { monitors, .. } => {
                 for mon in monitors {
                     for (idx, ws) in mon.workspaces.iter_mut().enumerate() {
                         if ws.has_window(window) {
-                            ws.remove_window(window);
+                            let window = ws.remove_window(window);
 
                             // Clean up empty workspaces that are not active and not last.
-                            if !ws.has_windows()
+                            if !ws.has_windows_or_name()
                                 && idx != mon.active_workspace_idx
                                 && idx != mon.workspaces.len() - 1
                                 && mon.workspace_switch.is_none()
@@ -577,11 +710,11 @@ impl<W: LayoutElement> Layout<W> {
                                 mon.workspaces.remove(idx);
 
                                 if idx < mon.active_workspace_idx {
-                                    mon.active_workspace_idx -= 1;
+                                    mon.set_active_workspace_idx(mon.active_workspace_idx - 1);
                                 }
                             }
 
-                            break;
+                            return Some(window);
                         }
                     }
                 }
@@ -589,18 +722,20 @@ impl<W: LayoutElement> Layout<W> {
             MonitorSet::NoOutputs { workspaces, .. } => {
                 for (idx, ws) in workspaces.iter_mut().enumerate() {
                     if ws.has_window(window) {
-                        ws.remove_window(window);
+                        let window = ws.remove_window(window);
 
                         // Clean up empty workspaces.
-                        if !ws.has_windows() {
+                        if !ws.has_windows_or_name() {
                             workspaces.remove(idx);
                         }
 
-                        break;
+                        return Some(window);
                     }
                 }
             }
         }
+
+        None
     }
 
     pub fn update_window(&mut self, window: &W) {
@@ -640,31 +775,43 @@ impl<W: LayoutElement> Layout<W> {
         None
     }
 
-    pub fn window_y(&self, window: &W) -> Option<i32> {
+    /// Returns whether the window is in a floating layer.
+    pub fn is_floating(&self, window: &W) -> bool {
         match &self.monitor_set {
-            MonitorSet::Normal { monitors, .. } => {
-                for mon in monitors {
-                    for ws in &mon.workspaces {
-                        for col in &ws.columns {
-                            if let Some(idx) = col.position(window) {
-                                return Some(col.window_y(idx));
-                            }
-                        }
-                    }
-                }
+            MonitorSet::Normal { monitors, .. } => monitors
+                .iter()
+                .flat_map(|mon| &mon.workspaces)
+                .any(|ws| ws.is_floating(window)),
+            MonitorSet::NoOutputs { workspaces, .. } => {
+                workspaces.iter().any(|ws| ws.is_floating(window))
             }
+        }
+    }
+
+    pub fn window_y(&self, window: &W) -> Option<i32> {
+        match &self.monitor_set {
+            MonitorSet::Normal { monitors, .. } => monitors
+                .iter()
+                .flat_map(|mon| &mon.workspaces)
+                .find_map(|ws| ws.window_y(window)),
             MonitorSet::NoOutputs { workspaces, .. } => {
-                for ws in workspaces {
-                    for col in &ws.columns {
-                        if let Some(idx) = col.position(window) {
-                            return Some(col.window_y(idx));
-                        }
-                    }
-                }
+                workspaces.iter().find_map(|ws| ws.window_y(window))
             }
         }
+    }
 
-        None
+    /// Returns how much activating the window would scroll the view, as a fraction of the working
+    /// area width.
+    pub fn scroll_amount_to_activate(&self, window: &W) -> f64 {
+        let MonitorSet::Normal { monitors, .. } = &self.monitor_set else {
+            return 0.;
+        };
+
+        monitors
+            .iter()
+            .flat_map(|mon| &mon.workspaces)
+            .find(|ws| ws.has_window(window))
+            .map_or(0., |ws| ws.scroll_amount_to_activate(window))
     }
 
     pub fn update_output_size(&mut self, output: &Output) {
@@ -746,6 +893,30 @@ impl<W: LayoutElement> Layout<W> {
         Some(&monitors[*active_monitor_idx].output)
     }
 
+    /// Returns the workspace that [`Self::add_window_to`] would add a window to.
+    pub fn workspace_for_new_window(
+        &self,
+        output: Option<&Output>,
+        workspace_idx: Option<usize>,
+    ) -> Option<&Workspace<W>> {
+        let MonitorSet::Normal {
+            monitors,
+            active_monitor_idx,
+            ..
+        } = &self.monitor_set
+        else {
+            return None;
+        };
+
+        let mon = output
+            .and_then(|output| monitors.iter().find(|mon| &mon.output == output))
+            .unwrap_or(&monitors[*active_monitor_idx]);
+        let workspace_idx = workspace_idx
+            .map(|idx| min(idx, mon.workspaces.len() - 1))
+            .unwrap_or(mon.active_workspace_idx);
+        Some(&mon.workspaces[workspace_idx])
+    }
+
     pub fn active_workspace(&self) -> Option<&Workspace<W>> {
         let MonitorSet::Normal {
             monitors,
@@ -772,13 +943,7 @@ impl<W: LayoutElement> Layout<W> {
 
         let mon = &monitors[*active_monitor_idx];
         let ws = &mon.workspaces[mon.active_workspace_idx];
-
-        if ws.columns.is_empty() {
-            return None;
-        }
-
-        let col = &ws.columns[ws.active_column_idx];
-        Some((&col.tiles[col.active_tile_idx].window(), &mon.output))
+        ws.active_window().map(|win| (win, &mon.output))
     }
 
     pub fn windows_for_output(&self, output: &Output) -> impl Iterator<Item = &W> + '_ {
@@ -790,6 +955,9 @@ impl<W: LayoutElement> Layout<W> {
         mon.workspaces.iter().flat_map(|ws| ws.windows())
     }
 
+    /// Calls the closure for every window along with its output.
+    ///
+    /// Windows hidden in a scratchpad are alive but not on any output, so they come with `None`.
     pub fn with_windows(&self, mut f: impl FnMut(&W, Option<&Output>)) {
         match &self.monitor_set {
             MonitorSet::Normal { monitors, .. } => {
@@ -809,6 +977,141 @@ impl<W: LayoutElement> Layout<W> {
                 }
             }
         }
+
+        for scratchpad in &self.scratchpads {
+            if scratchpad.is_hidden {
+                f(&scratchpad.window, None);
+            }
+        }
+    }
+
+    /// Calls the closure for every workspace along with its output and its index on that output.
+    pub fn with_workspaces(&self, mut f: impl FnMut(&Workspace<W>, Option<&Output>, usize)) {
+        match &self.monitor_set {
+            MonitorSet::Normal { monitors, .. } => {
+                for mon in monitors {
+                    for (idx, ws) in mon.workspaces.iter().enumerate() {
+                        f(ws, Some(&mon.output), idx);
+                    }
+                }
+            }
+            MonitorSet::NoOutputs { workspaces } => {
+                for (idx, ws) in workspaces.iter().enumerate() {
+                    f(ws, None, idx);
+                }
+            }
+        }
+    }
+
+    /// Returns the output and the index of the workspace with the given name.
+    pub fn find_workspace_by_name(&self, workspace_name: &str) -> Option<(&Output, usize)> {
+        let MonitorSet::Normal { monitors, .. } = &self.monitor_set else {
+            return None;
+        };
+
+        monitors.iter().find_map(|mon| {
+            mon.workspaces
+                .iter()
+                .position(|ws| ws.name.as_deref() == Some(workspace_name))
+                .map(|idx| (&mon.output, idx))
+        })
+    }
+
+    /// Creates the named workspace from the config if it doesn't exist yet.
+    ///
+    /// The workspace is created on its configured output if it's connected, and on the primary
+    /// output otherwise.
+    pub fn ensure_named_workspace(&mut self, ws_config: &niri_config::Workspace) {
+        let options = self.options.clone();
+
+        match &mut self.monitor_set {
+            MonitorSet::Normal {
+                monitors,
+                primary_idx,
+                ..
+            } => {
+                if monitors
+                    .iter()
+                    .flat_map(|mon| &mon.workspaces)
+                    .any(|ws| ws.name.as_ref() == Some(&ws_config.name))
+                {
+                    return;
+                }
+
+                let mon_idx = ws_config
+                    .open_on_output
+                    .as_deref()
+                    .and_then(|name| monitors.iter().position(|mon| mon.output.name() == name))
+                    .unwrap_or(*primary_idx);
+                let mon = &mut monitors[mon_idx];
+
+                let ws = Workspace::new_with_config(Some(mon.output.clone()), ws_config, options);
+
+                // Keep named workspaces in the config order, before all other workspaces. There's
+                // always an unnamed workspace at the end, so this never inserts past it.
+                let idx = mon
+                    .workspaces
+                    .iter()
+                    .take_while(|ws| ws.name.is_some())
+                    .count();
+                mon.workspaces.insert(idx, ws);
+
+                if idx <= mon.active_workspace_idx {
+                    mon.set_active_workspace_idx(mon.active_workspace_idx + 1);
+                }
+
+                // The workspace indices have shifted, so an ongoing switch is no longer valid.
+                mon.workspace_switch = None;
+                mon.clean_up_workspaces();
+            }
+            MonitorSet::NoOutputs { workspaces } => {
+                if workspaces
+                    .iter()
+                    .any(|ws| ws.name.as_ref() == Some(&ws_config.name))
+                {
+                    return;
+                }
+
+                let ws = Workspace::new_with_config(None, ws_config, options);
+                let idx = workspaces.iter().take_while(|ws| ws.name.is_some()).count();
+                workspaces.insert(idx, ws);
+            }
+        }
+    }
+
+    /// Turns the named workspace into a regular one, which is cleaned up once empty.
+    pub fn unname_workspace(&mut self, workspace_name: &str) {
+        match &mut self.monitor_set {
+            MonitorSet::Normal { monitors, .. } => {
+                for mon in monitors {
+                    if let Some(ws) = mon
+                        .workspaces
+                        .iter_mut()
+                        .find(|ws| ws.name.as_deref() == Some(workspace_name))
+                    {
+                        ws.name = None;
+
+                        if mon.workspace_switch.is_none() {
+                            mon.clean_up_workspaces();
+                        }
+
+                        return;
+                    }
+                }
+            }
+            MonitorSet::NoOutputs { workspaces } => {
+                if let Some(idx) = workspaces
+                    .iter()
+                    .position(|ws| ws.name.as_deref() == Some(workspace_name))
+                {
+                    workspaces[idx].name = None;
+
+                    if !workspaces[idx].has_windows() {
+                        workspaces.remove(idx);
+                    }
+                }
+            }
+        }
     }
 
     fn active_monitor(&mut self) -> Option<&mut Monitor<W>> {
@@ -832,6 +1135,21 @@ impl<W: LayoutElement> Layout<W> {
         monitors.iter().find(|monitor| &monitor.output == output)
     }
 
+    /// Returns the workspace activations since the last call, as outputs and workspace indices.
+    pub fn take_workspace_activations(&mut self) -> Vec<(Output, usize)> {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return vec![];
+        };
+
+        let mut activations = vec![];
+        for mon in monitors {
+            for idx in mon.take_workspace_activations() {
+                activations.push((mon.output.clone(), idx));
+            }
+        }
+        activations
+    }
+
     pub fn outputs(&self) -> impl Iterator<Item = &Output> + '_ {
         let monitors = if let MonitorSet::Normal { monitors, .. } = &self.monitor_set {
             &monitors[..]
@@ -1031,6 +1349,20 @@ impl<W: LayoutElement> Layout<W> {
         monitor.switch_workspace(idx);
     }
 
+    pub fn switch_workspace_auto_back_and_forth(&mut self, idx: usize) {
+        let Some(monitor) = self.active_monitor() else {
+            return;
+        };
+        monitor.switch_workspace_auto_back_and_forth(idx);
+    }
+
+    pub fn switch_workspace_previous(&mut self) {
+        let Some(monitor) = self.active_monitor() else {
+            return;
+        };
+        monitor.switch_workspace_previous();
+    }
+
     pub fn consume_into_column(&mut self) {
         let Some(monitor) = self.active_monitor() else {
             return;
@@ -1096,8 +1428,8 @@ impl<W: LayoutElement> Layout<W> {
             MonitorSet::NoOutputs { workspaces } => {
                 for workspace in workspaces {
                     assert!(
-                        workspace.has_windows(),
-                        "with no outputs there cannot be empty workspaces"
+                        workspace.has_windows_or_name(),
+                        "with no outputs there cannot be empty unnamed workspaces"
                     );
 
                     assert_eq!(
@@ -1115,6 +1447,16 @@ impl<W: LayoutElement> Layout<W> {
         assert!(primary_idx < monitors.len());
         assert!(active_monitor_idx < monitors.len());
 
+        let mut seen_workspace_names = std::collections::HashSet::new();
+        for ws in monitors.iter().flat_map(|mon| &mon.workspaces) {
+            if let Some(name) = &ws.name {
+                assert!(
+                    seen_workspace_names.insert(name),
+                    "workspace names must be unique"
+                );
+            }
+        }
+
         for (idx, monitor) in monitors.iter().enumerate() {
             assert!(
                 !monitor.workspaces.is_empty(),
@@ -1163,18 +1505,22 @@ impl<W: LayoutElement> Layout<W> {
             }
 
             assert!(
-                monitor.workspaces.last().unwrap().columns.is_empty(),
+                !monitor.workspaces.last().unwrap().has_windows(),
                 "monitor must have an empty workspace in the end"
             );
+            assert!(
+                monitor.workspaces.last().unwrap().name.is_none(),
+                "monitor must have an unnamed workspace in the end"
+            );
 
             // If there's no workspace switch in progress, there can't be any non-last non-active
-            // empty workspaces.
+            // empty unnamed workspaces.
             if monitor.workspace_switch.is_none() {
                 for (idx, ws) in monitor.workspaces.iter().enumerate().rev().skip(1) {
                     if idx != monitor.active_workspace_idx {
                         assert!(
-                            !ws.columns.is_empty(),
-                            "non-active workspace can't be empty except the last one"
+                            ws.has_windows_or_name(),
+                            "non-active workspace can't be empty and unnamed except the last one"
                         );
                     }
                 }
@@ -1218,6 +1564,19 @@ impl<W: LayoutElement> Layout<W> {
     pub fn update_config(&mut self, config: &Config) {
         let options = Rc::new(Options::from_config(config));
 
+        // Workspaces removed from the config become regular, unnamed ones.
+        let mut removed_names = vec![];
+        self.with_workspaces(|ws, _, _| {
+            if let Some(name) = &ws.name {
+                if !config.workspaces.iter().any(|c| &c.name == name) {
+                    removed_names.push(name.clone());
+                }
+            }
+        });
+        for name in removed_names {
+            self.unname_workspace(&name);
+        }
+
         match &mut self.monitor_set {
             MonitorSet::Normal { monitors, .. } => {
                 for mon in monitors {
@@ -1232,6 +1591,10 @@ impl<W: LayoutElement> Layout<W> {
         }
 
         self.options = options;
+
+        for ws_config in &config.workspaces {
+            self.ensure_named_workspace(ws_config);
+        }
     }
 
     pub fn toggle_width(&mut self) {
@@ -1248,6 +1611,13 @@ impl<W: LayoutElement> Layout<W> {
         monitor.toggle_full_width();
     }
 
+    pub fn toggle_column_tabbed_display(&mut self) {
+        let Some(monitor) = self.active_monitor() else {
+            return;
+        };
+        monitor.toggle_column_tabbed_display();
+    }
+
     pub fn set_column_width(&mut self, change: SizeChange) {
         let Some(monitor) = self.active_monitor() else {
             return;
@@ -1262,6 +1632,27 @@ impl<W: LayoutElement> Layout<W> {
         monitor.set_window_height(change);
     }
 
+    pub fn toggle_window_floating(&mut self) {
+        let Some(monitor) = self.active_monitor() else {
+            return;
+        };
+        monitor.toggle_window_floating();
+    }
+
+    pub fn switch_focus_between_floating_and_tiling(&mut self) {
+        let Some(monitor) = self.active_monitor() else {
+            return;
+        };
+        monitor.switch_focus_between_floating_and_tiling();
+    }
+
+    pub fn move_floating_window(&mut self, x: i32, y: i32) {
+        let Some(monitor) = self.active_monitor() else {
+            return;
+        };
+        monitor.move_floating_window(x, y);
+    }
+
     pub fn focus_output(&mut self, output: &Output) {
         if let MonitorSet::Normal {
             monitors,
@@ -1278,7 +1669,10 @@ impl<W: LayoutElement> Layout<W> {
         }
     }
 
-    pub fn move_to_output(&mut self, output: &Output) {
+    /// Moves the active window to the given output.
+    ///
+    /// `workspace_idx` defaults to the active workspace on that output.
+    pub fn move_to_output(&mut self, output: &Output, workspace_idx: Option<usize>) {
         if let MonitorSet::Normal {
             monitors,
             active_monitor_idx,
@@ -1292,7 +1686,15 @@ impl<W: LayoutElement> Layout<W> {
 
             let current = &mut monitors[*active_monitor_idx];
             let ws = current.active_workspace();
-            if !ws.has_windows() {
+            if let Some(window) = ws.remove_active_floating_window() {
+                let target = &monitors[new_idx];
+                let workspace_idx = workspace_idx
+                    .map(|idx| min(idx, target.workspaces.len() - 1))
+                    .unwrap_or(target.active_workspace_idx);
+                self.add_floating_window_by_idx(new_idx, workspace_idx, window, true);
+                return;
+            }
+            if ws.columns.is_empty() {
                 return;
             }
             let column = &ws.columns[ws.active_column_idx];
@@ -1300,12 +1702,18 @@ impl<W: LayoutElement> Layout<W> {
             let is_full_width = column.is_full_width;
             let window = ws.remove_window_by_idx(ws.active_column_idx, column.active_tile_idx);
 
-            let workspace_idx = monitors[new_idx].active_workspace_idx;
+            let target = &monitors[new_idx];
+            let workspace_idx = workspace_idx
+                .map(|idx| min(idx, target.workspaces.len() - 1))
+                .unwrap_or(target.active_workspace_idx);
             self.add_window_by_idx(new_idx, workspace_idx, window, true, width, is_full_width);
         }
     }
 
-    pub fn move_column_to_output(&mut self, output: &Output) {
+    /// Moves the active column to the given output.
+    ///
+    /// `workspace_idx` defaults to the active workspace on that output.
+    pub fn move_column_to_output(&mut self, output: &Output, workspace_idx: Option<usize>) {
         if let MonitorSet::Normal {
             monitors,
             active_monitor_idx,
@@ -1319,47 +1727,264 @@ impl<W: LayoutElement> Layout<W> {
 
             let current = &mut monitors[*active_monitor_idx];
             let ws = current.active_workspace();
-            if !ws.has_windows() {
+            if let Some(window) = ws.remove_active_floating_window() {
+                let target = &monitors[new_idx];
+                let workspace_idx = workspace_idx
+                    .map(|idx| min(idx, target.workspaces.len() - 1))
+                    .unwrap_or(target.active_workspace_idx);
+                self.add_floating_window_by_idx(new_idx, workspace_idx, window, true);
+                return;
+            }
+            if ws.columns.is_empty() {
                 return;
             }
             let column = ws.remove_column_by_idx(ws.active_column_idx);
 
-            let workspace_idx = monitors[new_idx].active_workspace_idx;
+            let target = &monitors[new_idx];
+            let workspace_idx = workspace_idx
+                .map(|idx| min(idx, target.workspaces.len() - 1))
+                .unwrap_or(target.active_workspace_idx);
             self.add_column_by_idx(new_idx, workspace_idx, column, true);
         }
     }
 
     pub fn move_window_to_output(&mut self, window: W, output: &Output) {
-        if !matches!(&self.monitor_set, MonitorSet::Normal { .. }) {
-            return;
-        }
+        self.move_window_to_output_at(&window, output, None, None);
+    }
 
-        self.remove_window(&window);
+    /// Moves the window to a workspace of the given output.
+    ///
+    /// `workspace_idx` defaults to the active workspace on that output. `position` defaults to a
+    /// new column to the right of the active one. It is ignored for floating windows, which stay
+    /// floating.
+    fn move_window_to_output_at(
+        &mut self,
+        window: &W,
+        output: &Output,
+        workspace_idx: Option<usize>,
+        position: Option<InsertPosition>,
+    ) {
+        let MonitorSet::Normal { monitors, .. } = &self.monitor_set else {
+            return;
+        };
 
-        if let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set {
-            let mut width = None;
-            let mut is_full_width = false;
-            for mon in &*monitors {
-                for ws in &mon.workspaces {
-                    for col in &ws.columns {
-                        if col.contains(&window) {
-                            width = Some(col.width);
-                            is_full_width = col.is_full_width;
-                            break;
-                        }
-                    }
-                }
+        let mut source = None;
+        for ws in monitors.iter().flat_map(|mon| &mon.workspaces) {
+            if ws.is_floating(window) {
+                source = Some(None);
+                break;
             }
-            let Some(width) = width else { return };
 
-            let new_idx = monitors
-                .iter()
-                .position(|mon| &mon.output == output)
-                .unwrap();
+            if let Some(col) = ws.columns.iter().find(|col| col.contains(window)) {
+                source = Some(Some((col.width, col.is_full_width)));
+                break;
+            }
+        }
+        let Some(source) = source else { return };
 
-            let workspace_idx = monitors[new_idx].active_workspace_idx;
-            // FIXME: activate only if it was already active and focused.
-            self.add_window_by_idx(new_idx, workspace_idx, window, true, width, is_full_width);
+        let new_idx = monitors
+            .iter()
+            .position(|mon| &mon.output == output)
+            .unwrap();
+        let workspace_count = monitors[new_idx].workspaces.len();
+        let source_ws_idx = monitors[new_idx]
+            .workspaces
+            .iter()
+            .position(|ws| ws.has_window(window));
+
+        let Some(window) = self.remove_window(window) else {
+            return;
+        };
+
+        let MonitorSet::Normal {
+            monitors,
+            active_monitor_idx,
+            ..
+        } = &mut self.monitor_set
+        else {
+            unreachable!()
+        };
+
+        let mon = &mut monitors[new_idx];
+        let workspace_idx = match workspace_idx {
+            Some(mut idx) => {
+                // Removing the window could have cleaned up its workspace on this output.
+                if source_ws_idx.is_some_and(|source| source < idx)
+                    && mon.workspaces.len() < workspace_count
+                {
+                    idx -= 1;
+                }
+                min(idx, mon.workspaces.len() - 1)
+            }
+            None => mon.active_workspace_idx,
+        };
+
+        // FIXME: activate only if it was already active and focused.
+        match (source, position) {
+            (None, _) => mon.add_floating_window(workspace_idx, window, true),
+            (Some((width, is_full_width)), None) => {
+                mon.add_window(workspace_idx, window, true, width, is_full_width)
+            }
+            (Some((width, is_full_width)), Some(position)) => {
+                mon.add_window_at(workspace_idx, window, position, true, width, is_full_width)
+            }
+        }
+        *active_monitor_idx = new_idx;
+    }
+
+    /// Updates an interactive move of the window with the pointer.
+    ///
+    /// `delta` is the pointer movement since the last update, and `output` and
+    /// `pos_within_output` are the current pointer position. Returns `false` if the window is
+    /// gone and the move should stop.
+    pub fn interactive_move_update(
+        &mut self,
+        window: &W,
+        delta: Point<i32, Logical>,
+        output: &Output,
+        pos_within_output: Point<f64, Logical>,
+    ) -> bool {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return false;
+        };
+
+        let mut source = None;
+        for (mon_idx, mon) in monitors.iter_mut().enumerate() {
+            for (ws_idx, ws) in mon.workspaces.iter_mut().enumerate() {
+                ws.set_insert_hint(None);
+
+                if ws.has_window(window) {
+                    source = Some((mon_idx, ws_idx));
+                }
+            }
+        }
+        let Some((source_mon_idx, source_ws_idx)) = source else {
+            return false;
+        };
+
+        let Some(target_mon_idx) = monitors.iter().position(|mon| &mon.output == output) else {
+            return true;
+        };
+
+        let source_ws = &mut monitors[source_mon_idx].workspaces[source_ws_idx];
+        if source_ws.is_floating(window) {
+            // Floating windows follow the pointer within their output, and otherwise keep
+            // floating wherever they are dropped.
+            if source_mon_idx == target_mon_idx {
+                source_ws.move_floating_window_by(window, delta);
+            }
+            return true;
+        }
+
+        let mon = &mut monitors[target_mon_idx];
+        let Some((ws_idx, pos_within_ws)) = mon.workspace_under(pos_within_output) else {
+            return true;
+        };
+        let ws = &mut mon.workspaces[ws_idx];
+        let position = ws.insert_position(pos_within_ws);
+        ws.set_insert_hint(Some(position));
+
+        true
+    }
+
+    /// Finishes an interactive move of the window, dropping it at the pointer position.
+    ///
+    /// `target` is the output under the pointer and the pointer position within it, if any.
+    pub fn interactive_move_end(
+        &mut self,
+        window: &W,
+        target: Option<(&Output, Point<f64, Logical>)>,
+    ) {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return;
+        };
+
+        let mut source = None;
+        for (mon_idx, mon) in monitors.iter_mut().enumerate() {
+            for (ws_idx, ws) in mon.workspaces.iter_mut().enumerate() {
+                ws.set_insert_hint(None);
+
+                if ws.has_window(window) {
+                    source = Some((mon_idx, ws_idx));
+                }
+            }
+        }
+        let Some((source_mon_idx, source_ws_idx)) = source else {
+            return;
+        };
+
+        let Some((output, pos_within_output)) = target else {
+            return;
+        };
+        let Some(target_mon_idx) = monitors.iter().position(|mon| &mon.output == output) else {
+            return;
+        };
+
+        let is_floating = monitors[source_mon_idx].workspaces[source_ws_idx].is_floating(window);
+        if is_floating {
+            // The window has been following the pointer already.
+            if source_mon_idx != target_mon_idx {
+                self.move_window_to_output_at(window, output, None, None);
+            }
+            return;
+        }
+
+        let mon = &mut monitors[target_mon_idx];
+        let Some((target_ws_idx, pos_within_ws)) = mon.workspace_under(pos_within_output) else {
+            return;
+        };
+        let ws = &mut mon.workspaces[target_ws_idx];
+        let position = ws.insert_position(pos_within_ws);
+
+        if source_mon_idx == target_mon_idx && source_ws_idx == target_ws_idx {
+            ws.move_window_to(window, position);
+        } else {
+            self.move_window_to_output_at(window, output, Some(target_ws_idx), Some(position));
+        }
+    }
+
+    /// Starts an interactive resize of the window by the given edges.
+    ///
+    /// Returns `false` if the window cannot be resized interactively.
+    pub fn interactive_resize_begin(&mut self, window: W, edges: ResizeEdge) -> bool {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return false;
+        };
+
+        for ws in monitors.iter_mut().flat_map(|mon| &mut mon.workspaces) {
+            if ws.has_window(&window) {
+                return ws.interactive_resize_begin(window, edges);
+            }
+        }
+
+        false
+    }
+
+    /// Updates the interactive resize of the window.
+    ///
+    /// `delta` is the pointer movement since the start of the resize. Returns `false` if the
+    /// resize is no longer ongoing, for example, because the window is gone.
+    pub fn interactive_resize_update(&mut self, window: &W, delta: Point<f64, Logical>) -> bool {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return false;
+        };
+
+        for ws in monitors.iter_mut().flat_map(|mon| &mut mon.workspaces) {
+            if ws.has_window(window) {
+                return ws.interactive_resize_update(window, delta);
+            }
+        }
+
+        false
+    }
+
+    pub fn interactive_resize_end(&mut self, window: &W) {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return;
+        };
+
+        for ws in monitors.iter_mut().flat_map(|mon| &mut mon.workspaces) {
+            ws.interactive_resize_end(window);
         }
     }
 
@@ -1380,7 +2005,7 @@ impl<W: LayoutElement> Layout<W> {
             current.workspaces.push(ws);
         }
         let mut ws = current.workspaces.remove(current.active_workspace_idx);
-        current.active_workspace_idx = current.active_workspace_idx.saturating_sub(1);
+        current.set_active_workspace_idx(current.active_workspace_idx.saturating_sub(1));
         current.workspace_switch = None;
         current.clean_up_workspaces();
 
@@ -1397,7 +2022,7 @@ impl<W: LayoutElement> Layout<W> {
         // the last empty workspace, then insert before.
         let target_ws_idx = min(target.active_workspace_idx + 1, target.workspaces.len() - 1);
         target.workspaces.insert(target_ws_idx, ws);
-        target.active_workspace_idx = target_ws_idx;
+        target.set_active_workspace_idx(target_ws_idx);
         target.workspace_switch = None;
         target.clean_up_workspaces();
 
@@ -1427,6 +2052,29 @@ impl<W: LayoutElement> Layout<W> {
         }
     }
 
+    pub fn set_maximized(&mut self, window: &W, is_maximized: bool) {
+        match &mut self.monitor_set {
+            MonitorSet::Normal { monitors, .. } => {
+                for mon in monitors {
+                    for ws in &mut mon.workspaces {
+                        if ws.has_window(window) {
+                            ws.set_maximized(window, is_maximized);
+                            return;
+                        }
+                    }
+                }
+            }
+            MonitorSet::NoOutputs { workspaces, .. } => {
+                for ws in workspaces {
+                    if ws.has_window(window) {
+                        ws.set_maximized(window, is_maximized);
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
     pub fn toggle_fullscreen(&mut self, window: &W) {
         match &mut self.monitor_set {
             MonitorSet::Normal { monitors, .. } => {
@@ -1469,18 +2117,23 @@ impl<W: LayoutElement> Layout<W> {
             let current_idx = monitor
                 .workspace_switch
                 .as_ref()
-                .map(|s| s.current_idx())
+                .map(|s| monitor.clamped_switch_idx(s))
                 .unwrap_or(center_idx as f64);
 
             let gesture = WorkspaceSwitchGesture {
                 center_idx,
                 current_idx,
+                tracker: SwipeTracker::new(),
             };
             monitor.workspace_switch = Some(WorkspaceSwitch::Gesture(gesture));
         }
     }
 
-    pub fn workspace_switch_gesture_update(&mut self, delta_y: f64) -> Option<Option<Output>> {
+    pub fn workspace_switch_gesture_update(
+        &mut self,
+        delta_y: f64,
+        timestamp: Duration,
+    ) -> Option<Option<Output>> {
         let monitors = match &mut self.monitor_set {
             MonitorSet::Normal { monitors, .. } => monitors,
             MonitorSet::NoOutputs { .. } => return None,
@@ -1490,6 +2143,7 @@ impl<W: LayoutElement> Layout<W> {
             if let Some(WorkspaceSwitch::Gesture(gesture)) = &mut monitor.workspace_switch {
                 // Normalize like GNOME Shell's workspace switching.
                 let delta_y = delta_y / 400.;
+                gesture.tracker.push(delta_y, timestamp);
 
                 let min = gesture.center_idx.saturating_sub(1) as f64;
                 let max = (gesture.center_idx + 1).min(monitor.workspaces.len() - 1) as f64;
@@ -1520,16 +2174,25 @@ impl<W: LayoutElement> Layout<W> {
                     return Some(monitor.output.clone());
                 }
 
-                // FIXME: keep track of gesture velocity and use it to compute the final point and
-                // to animate to it.
+                // Let a quick flick carry over to the next workspace.
                 let current_idx = gesture.current_idx;
-                let idx = current_idx.round() as usize;
+                let velocity = gesture.tracker.velocity();
+                let projected_idx = current_idx + gesture.tracker.projected_offset();
+
+                let min = gesture.center_idx.saturating_sub(1) as f64;
+                let max = (gesture.center_idx + 1).min(monitor.workspaces.len() - 1) as f64;
+                let idx = projected_idx.round().clamp(min, max) as usize;
 
-                monitor.active_workspace_idx = idx;
+                if idx != gesture.center_idx {
+                    let id = monitor.workspaces[gesture.center_idx].id();
+                    monitor.previous_workspace_id = Some(id);
+                }
+                monitor.set_active_workspace_idx(idx);
                 monitor.workspace_switch = Some(WorkspaceSwitch::Animation(Animation::new(
                     current_idx,
                     idx as f64,
-                    Duration::from_millis(250),
+                    velocity,
+                    monitor.options.animations.workspace_switch,
                 )));
 
                 return Some(monitor.output.clone());
@@ -1539,6 +2202,62 @@ impl<W: LayoutElement> Layout<W> {
         None
     }
 
+    pub fn is_overview_open(&self) -> bool {
+        let MonitorSet::Normal { monitors, .. } = &self.monitor_set else {
+            return false;
+        };
+
+        monitors.iter().any(|mon| mon.is_overview_open())
+    }
+
+    pub fn toggle_overview(&mut self) {
+        let open = !self.is_overview_open();
+
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return;
+        };
+
+        for mon in monitors {
+            mon.set_overview_open(open);
+        }
+    }
+
+    /// Pans the overview on the output by the given amount of logical pixels.
+    pub fn overview_pan(&mut self, output: &Output, delta_y: f64) {
+        let MonitorSet::Normal { monitors, .. } = &mut self.monitor_set else {
+            return;
+        };
+
+        if let Some(mon) = monitors.iter_mut().find(|mon| &mon.output == output) {
+            mon.overview_pan(delta_y);
+        }
+    }
+
+    /// Focuses the workspace under the position in the overview and closes the overview.
+    pub fn overview_pick_workspace(
+        &mut self,
+        output: &Output,
+        pos_within_output: Point<f64, Logical>,
+    ) {
+        let MonitorSet::Normal {
+            monitors,
+            active_monitor_idx,
+            ..
+        } = &mut self.monitor_set
+        else {
+            return;
+        };
+
+        if let Some(idx) = monitors.iter().position(|mon| &mon.output == output) {
+            monitors[idx].overview_pick_workspace(pos_within_output);
+            *active_monitor_idx = idx;
+        }
+
+        for mon in monitors {
+            mon.set_overview_open(false);
+        }
+    }
+
     pub fn move_workspace_down(&mut self) {
         let Some(monitor) = self.active_monitor() else {
             return;
@@ -1554,6 +2273,105 @@ impl<W: LayoutElement> Layout<W> {
     }
 }
 
+impl<W: LayoutElement + Clone> Layout<W> {
+    /// Hides the active window in the scratchpad with the given name.
+    ///
+    /// If the scratchpad already held a hidden window, that window is shown on the active
+    /// workspace in its place.
+    pub fn move_window_to_scratchpad(&mut self, name: &str) {
+        let Some(window) = self.focus().cloned() else {
+            return;
+        };
+
+        // A window summoned from a scratchpad leaves it.
+        self.scratchpads.retain(|s| s.window != window);
+
+        let Some(window) = self.remove_window(&window) else {
+            return;
+        };
+
+        if let Some(idx) = self.scratchpads.iter().position(|s| s.name == name) {
+            let previous = self.scratchpads.remove(idx);
+            if previous.is_hidden {
+                if let Some(monitor) = self.active_monitor() {
+                    monitor.add_floating_window(
+                        monitor.active_workspace_idx,
+                        previous.window,
+                        false,
+                    );
+                }
+            }
+        }
+
+        self.scratchpads.push(Scratchpad {
+            name: name.to_owned(),
+            window,
+            is_hidden: true,
+        });
+    }
+
+    /// Shows the scratchpad with the given name on the active workspace, or hides it again.
+    pub fn toggle_scratchpad(&mut self, name: &str) {
+        let Some(idx) = self.scratchpads.iter().position(|s| s.name == name) else {
+            return;
+        };
+        if self.active_monitor().is_none() {
+            return;
+        }
+
+        let scratchpad = &self.scratchpads[idx];
+        let window = scratchpad.window.clone();
+
+        if scratchpad.is_hidden {
+            let monitor = self.active_monitor().unwrap();
+            monitor.add_floating_window(monitor.active_workspace_idx, window, true);
+            self.scratchpads[idx].is_hidden = false;
+            return;
+        }
+
+        let is_on_active_workspace = self
+            .active_workspace()
+            .map_or(false, |ws| ws.has_window(&window));
+
+        let Some(window) = self.remove_window(&window) else {
+            // The window is gone from the layout, so it was unmapped while shown.
+            self.scratchpads.remove(idx);
+            return;
+        };
+
+        if is_on_active_workspace {
+            let scratchpad = &mut self.scratchpads[idx];
+            scratchpad.window = window;
+            scratchpad.is_hidden = true;
+        } else {
+            // Shown on some other workspace, bring it over.
+            let monitor = self.active_monitor().unwrap();
+            monitor.add_floating_window(monitor.active_workspace_idx, window, true);
+        }
+    }
+
+    pub fn hidden_scratchpad_window(&self, wl_surface: &WlSurface) -> Option<&W> {
+        self.scratchpads
+            .iter()
+            .find(|s| s.is_hidden && s.window.is_wl_surface(wl_surface))
+            .map(|s| &s.window)
+    }
+
+    /// Forgets the scratchpad that the window was shown from, if any.
+    pub fn remove_shown_scratchpad_window(&mut self, window: &W) {
+        self.scratchpads
+            .retain(|s| s.is_hidden || s.window != *window);
+    }
+
+    pub fn remove_hidden_scratchpad_window(&mut self, wl_surface: &WlSurface) -> Option<W> {
+        let idx = self
+            .scratchpads
+            .iter()
+            .position(|s| s.is_hidden && s.window.is_wl_surface(wl_surface))?;
+        Some(self.scratchpads.remove(idx).window)
+    }
+}
+
 impl Layout<Window> {
     pub fn refresh(&self) {
         let _span = tracy_client::span!("MonitorSet::refresh");
@@ -1594,13 +2412,18 @@ mod tests {
     use proptest_derive::Arbitrary;
     use smithay::output::{Mode, PhysicalProperties, Subpixel};
 
+    use super::tab_bar::TAB_BAR_HEIGHT;
+    use super::workspace::ColumnDisplay;
     use super::*;
+    use crate::utils::get_monotonic_time;
 
     impl<W: LayoutElement> Default for Layout<W> {
         fn default() -> Self {
             Self {
                 monitor_set: MonitorSet::NoOutputs { workspaces: vec![] },
                 options: Rc::new(Options::default()),
+                scratchpads: Vec::new(),
+                restore: None,
             }
         }
     }
@@ -1614,6 +2437,7 @@ mod tests {
         min_size: Size<i32, Logical>,
         max_size: Size<i32, Logical>,
         pending_fullscreen: Cell<bool>,
+        pending_maximized: Cell<bool>,
     }
 
     #[derive(Debug, Clone)]
@@ -1634,6 +2458,7 @@ mod tests {
                 min_size,
                 max_size,
                 pending_fullscreen: Cell::new(false),
+                pending_maximized: Cell::new(false),
             }))
         }
 
@@ -1697,6 +2522,10 @@ mod tests {
             self.0.pending_fullscreen.set(true);
         }
 
+        fn request_maximized(&self, maximized: bool) {
+            self.0.pending_maximized.set(maximized);
+        }
+
         fn min_size(&self) -> Size<i32, Logical> {
             self.0.min_size
         }
@@ -1726,6 +2555,14 @@ mod tests {
         fn is_pending_fullscreen(&self) -> bool {
             self.0.pending_fullscreen.get()
         }
+
+        fn is_pending_maximized(&self) -> bool {
+            self.0.pending_maximized.get()
+        }
+
+        fn title(&self) -> Option<String> {
+            None
+        }
     }
 
     fn arbitrary_bbox() -> impl Strategy<Value = Rectangle<i32, Logical>> {
@@ -1745,6 +2582,10 @@ mod tests {
         ]
     }
 
+    fn arbitrary_resize_edge() -> impl Strategy<Value = ResizeEdge> {
+        (0..16u32).prop_map(ResizeEdge::from_bits_truncate)
+    }
+
     fn arbitrary_min_max() -> impl Strategy<Value = (i32, i32)> {
         prop_oneof![
             Just((0, 0)),
@@ -1776,8 +2617,27 @@ mod tests {
             #[proptest(strategy = "arbitrary_min_max_size()")]
             min_max_size: (Size<i32, Logical>, Size<i32, Logical>),
         },
+        AddWindowTo {
+            #[proptest(strategy = "1..=5usize")]
+            id: usize,
+            #[proptest(strategy = "arbitrary_bbox()")]
+            bbox: Rectangle<i32, Logical>,
+            #[proptest(strategy = "arbitrary_min_max_size()")]
+            min_max_size: (Size<i32, Logical>, Size<i32, Logical>),
+            #[proptest(strategy = "proptest::option::of(1..=5usize)")]
+            output_id: Option<usize>,
+            #[proptest(strategy = "proptest::option::of(0..=4usize)")]
+            workspace_idx: Option<usize>,
+            activate: Option<bool>,
+            is_floating: bool,
+        },
         CloseWindow(#[proptest(strategy = "1..=5usize")] usize),
         FullscreenWindow(#[proptest(strategy = "1..=5usize")] usize),
+        SetMaximized {
+            #[proptest(strategy = "1..=5usize")]
+            id: usize,
+            maximized: bool,
+        },
         FocusColumnLeft,
         FocusColumnRight,
         FocusColumnFirst,
@@ -1802,6 +2662,10 @@ mod tests {
         FocusWorkspaceDown,
         FocusWorkspaceUp,
         FocusWorkspace(#[proptest(strategy = "0..=4usize")] usize),
+        FocusWorkspaceAutoBackAndForth(#[proptest(strategy = "0..=4usize")] usize),
+        FocusWorkspacePrevious,
+        MoveWindowToScratchpad(#[proptest(strategy = "0..=1usize")] usize),
+        ToggleScratchpad(#[proptest(strategy = "0..=1usize")] usize),
         MoveWindowToWorkspaceDown,
         MoveWindowToWorkspaceUp,
         MoveWindowToWorkspace(#[proptest(strategy = "0..=4usize")] usize),
@@ -1810,14 +2674,92 @@ mod tests {
         MoveColumnToWorkspace(#[proptest(strategy = "0..=4usize")] usize),
         MoveWorkspaceDown,
         MoveWorkspaceUp,
-        MoveWindowToOutput(#[proptest(strategy = "1..=5u8")] u8),
-        MoveColumnToOutput(#[proptest(strategy = "1..=5u8")] u8),
+        MoveWindowToOutput {
+            #[proptest(strategy = "1..=5u8")]
+            output_id: u8,
+            #[proptest(strategy = "proptest::option::of(0..=4usize)")]
+            workspace_idx: Option<usize>,
+        },
+        MoveColumnToOutput {
+            #[proptest(strategy = "1..=5u8")]
+            output_id: u8,
+            #[proptest(strategy = "proptest::option::of(0..=4usize)")]
+            workspace_idx: Option<usize>,
+        },
+        AddNamedWorkspace {
+            #[proptest(strategy = "1..=5usize")]
+            ws_name: usize,
+            #[proptest(strategy = "proptest::option::of(1..=5usize)")]
+            output_name: Option<usize>,
+        },
+        UnnameWorkspace {
+            #[proptest(strategy = "1..=5usize")]
+            ws_name: usize,
+        },
+        FocusWorkspaceByName {
+            #[proptest(strategy = "1..=5usize")]
+            ws_name: usize,
+        },
         SwitchPresetColumnWidth,
         MaximizeColumn,
+        ToggleColumnTabbedDisplay,
         SetColumnWidth(#[proptest(strategy = "arbitrary_size_change()")] SizeChange),
         SetWindowHeight(#[proptest(strategy = "arbitrary_size_change()")] SizeChange),
+        ToggleWindowFloating,
+        SwitchFocusFloatingTiling,
+        MoveFloatingWindow {
+            #[proptest(strategy = "-2000..=2000i32")]
+            x: i32,
+            #[proptest(strategy = "-2000..=2000i32")]
+            y: i32,
+        },
+        InteractiveMove {
+            #[proptest(strategy = "1..=5usize")]
+            window: usize,
+            #[proptest(strategy = "1..=5u8")]
+            output_id: u8,
+            #[proptest(strategy = "-200..=200i32")]
+            dx: i32,
+            #[proptest(strategy = "-200..=200i32")]
+            dy: i32,
+            #[proptest(strategy = "-100..=2000i32")]
+            px: i32,
+            #[proptest(strategy = "-100..=2000i32")]
+            py: i32,
+            drop: bool,
+        },
+        InteractiveResizeBegin {
+            #[proptest(strategy = "1..=5usize")]
+            window: usize,
+            #[proptest(strategy = "arbitrary_resize_edge()")]
+            edges: ResizeEdge,
+        },
+        InteractiveResizeUpdate {
+            #[proptest(strategy = "1..=5usize")]
+            window: usize,
+            #[proptest(strategy = "-20000f64..20000f64")]
+            dx: f64,
+            #[proptest(strategy = "-20000f64..20000f64")]
+            dy: f64,
+        },
+        InteractiveResizeEnd(#[proptest(strategy = "1..=5usize")] usize),
         Communicate(#[proptest(strategy = "1..=5usize")] usize),
         MoveWorkspaceToOutput(#[proptest(strategy = "1..=5u8")] u8),
+        ToggleOverview,
+        OverviewPan {
+            #[proptest(strategy = "1..=5u8")]
+            output_id: u8,
+            #[proptest(strategy = "-2000f64..2000f64")]
+            dy: f64,
+        },
+        OverviewPickWorkspace {
+            #[proptest(strategy = "1..=5u8")]
+            output_id: u8,
+            #[proptest(strategy = "-100..=2000i32")]
+            px: i32,
+            #[proptest(strategy = "-100..=2000i32")]
+            py: i32,
+        },
     }
 
     impl Op {
@@ -1870,6 +2812,14 @@ mod tests {
                     bbox,
                     min_max_size,
                 } => {
+                    if layout
+                        .scratchpads
+                        .iter()
+                        .any(|s| s.is_hidden && s.window.0.id == id)
+                    {
+                        return;
+                    }
+
                     match &mut layout.monitor_set {
                         MonitorSet::Normal { monitors, .. } => {
                             for mon in monitors {
@@ -1896,16 +2846,63 @@ mod tests {
                     let win = TestWindow::new(id, bbox, min_max_size.0, min_max_size.1);
                     layout.add_window(win, None, false);
                 }
-                Op::CloseWindow(id) => {
+                Op::AddWindowTo {
+                    id,
+                    bbox,
+                    min_max_size,
+                    output_id,
+                    workspace_idx,
+                    activate,
+                    is_floating,
+                } => {
+                    if layout
+                        .scratchpads
+                        .iter()
+                        .any(|s| s.is_hidden && s.window.0.id == id)
+                    {
+                        return;
+                    }
+
+                    let mut exists = false;
+                    layout.with_workspaces(|ws, _, _| {
+                        exists |= ws.windows().any(|win| win.0.id == id);
+                    });
+                    if exists {
+                        return;
+                    }
+
+                    let output = output_id.and_then(|id| {
+                        let name = format!("output{id}");
+                        layout.outputs().find(|o| o.name() == name).cloned()
+                    });
+
+                    let win = TestWindow::new(id, bbox, min_max_size.0, min_max_size.1);
+                    layout.add_window_to(
+                        win,
+                        output.as_ref(),
+                        workspace_idx,
+                        activate,
+                        None,
+                        false,
+                        is_floating,
+                    );
+                }
+                Op::CloseWindow(id) => {
                     let dummy =
                         TestWindow::new(id, Rectangle::default(), Size::default(), Size::default());
                     layout.remove_window(&dummy);
+                    layout.remove_shown_scratchpad_window(&dummy);
                 }
                 Op::FullscreenWindow(id) => {
                     let dummy =
                         TestWindow::new(id, Rectangle::default(), Size::default(), Size::default());
                     layout.toggle_fullscreen(&dummy);
                 }
+                Op::SetMaximized { id, maximized } => {
+                    let dummy =
+                        TestWindow::new(id, Rectangle::default(), Size::default(), Size::default());
+                    layout.set_maximized(&dummy, maximized);
+                }
                 Op::FocusColumnLeft => layout.focus_left(),
                 Op::FocusColumnRight => layout.focus_right(),
                 Op::FocusColumnFirst => layout.focus_column_first(),
@@ -1930,34 +2927,129 @@ mod tests {
                 Op::FocusWorkspaceDown => layout.switch_workspace_down(),
                 Op::FocusWorkspaceUp => layout.switch_workspace_up(),
                 Op::FocusWorkspace(idx) => layout.switch_workspace(idx),
+                Op::FocusWorkspaceAutoBackAndForth(idx) => {
+                    layout.switch_workspace_auto_back_and_forth(idx)
+                }
+                Op::FocusWorkspacePrevious => layout.switch_workspace_previous(),
+                Op::MoveWindowToScratchpad(idx) => {
+                    layout.move_window_to_scratchpad(&idx.to_string())
+                }
+                Op::ToggleScratchpad(idx) => layout.toggle_scratchpad(&idx.to_string()),
                 Op::MoveWindowToWorkspaceDown => layout.move_to_workspace_down(),
                 Op::MoveWindowToWorkspaceUp => layout.move_to_workspace_up(),
                 Op::MoveWindowToWorkspace(idx) => layout.move_to_workspace(idx),
                 Op::MoveColumnToWorkspaceDown => layout.move_column_to_workspace_down(),
                 Op::MoveColumnToWorkspaceUp => layout.move_column_to_workspace_up(),
                 Op::MoveColumnToWorkspace(idx) => layout.move_column_to_workspace(idx),
-                Op::MoveWindowToOutput(id) => {
+                Op::MoveWindowToOutput {
+                    output_id: id,
+                    workspace_idx,
+                } => {
                     let name = format!("output{id}");
                     let Some(output) = layout.outputs().find(|o| o.name() == name).cloned() else {
                         return;
                     };
 
-                    layout.move_to_output(&output);
+                    layout.move_to_output(&output, workspace_idx);
                 }
-                Op::MoveColumnToOutput(id) => {
+                Op::MoveColumnToOutput {
+                    output_id: id,
+                    workspace_idx,
+                } => {
                     let name = format!("output{id}");
                     let Some(output) = layout.outputs().find(|o| o.name() == name).cloned() else {
                         return;
                     };
 
-                    layout.move_column_to_output(&output);
+                    layout.move_column_to_output(&output, workspace_idx);
+                }
+                Op::AddNamedWorkspace {
+                    ws_name,
+                    output_name,
+                } => {
+                    layout.ensure_named_workspace(&niri_config::Workspace {
+                        name: format!("ws{ws_name}"),
+                        open_on_output: output_name.map(|name| format!("output{name}")),
+                    });
+                }
+                Op::UnnameWorkspace { ws_name } => {
+                    layout.unname_workspace(&format!("ws{ws_name}"));
+                }
+                Op::FocusWorkspaceByName { ws_name } => {
+                    let Some((output, idx)) =
+                        layout.find_workspace_by_name(&format!("ws{ws_name}"))
+                    else {
+                        return;
+                    };
+                    let output = output.clone();
+
+                    layout.focus_output(&output);
+                    layout.switch_workspace(idx);
                 }
                 Op::MoveWorkspaceDown => layout.move_workspace_down(),
                 Op::MoveWorkspaceUp => layout.move_workspace_up(),
                 Op::SwitchPresetColumnWidth => layout.toggle_width(),
                 Op::MaximizeColumn => layout.toggle_full_width(),
+                Op::ToggleColumnTabbedDisplay => layout.toggle_column_tabbed_display(),
                 Op::SetColumnWidth(change) => layout.set_column_width(change),
                 Op::SetWindowHeight(change) => layout.set_window_height(change),
+                Op::ToggleWindowFloating => layout.toggle_window_floating(),
+                Op::SwitchFocusFloatingTiling => layout.switch_focus_between_floating_and_tiling(),
+                Op::MoveFloatingWindow { x, y } => layout.move_floating_window(x, y),
+                Op::InteractiveMove {
+                    window,
+                    output_id,
+                    dx,
+                    dy,
+                    px,
+                    py,
+                    drop,
+                } => {
+                    let name = format!("output{output_id}");
+                    let Some(output) = layout.outputs().find(|o| o.name() == name).cloned() else {
+                        return;
+                    };
+
+                    let dummy = TestWindow::new(
+                        window,
+                        Rectangle::default(),
+                        Size::default(),
+                        Size::default(),
+                    );
+                    let pos = Point::from((px as f64, py as f64));
+                    layout.interactive_move_update(&dummy, Point::from((dx, dy)), &output, pos);
+
+                    if drop {
+                        layout.interactive_move_end(&dummy, Some((&output, pos)));
+                    }
+                }
+                Op::InteractiveResizeBegin { window, edges } => {
+                    let win = TestWindow::new(
+                        window,
+                        Rectangle::default(),
+                        Size::default(),
+                        Size::default(),
+                    );
+                    layout.interactive_resize_begin(win, edges);
+                }
+                Op::InteractiveResizeUpdate { window, dx, dy } => {
+                    let win = TestWindow::new(
+                        window,
+                        Rectangle::default(),
+                        Size::default(),
+                        Size::default(),
+                    );
+                    layout.interactive_resize_update(&win, Point::from((dx, dy)));
+                }
+                Op::InteractiveResizeEnd(window) => {
+                    let win = TestWindow::new(
+                        window,
+                        Rectangle::default(),
+                        Size::default(),
+                        Size::default(),
+                    );
+                    layout.interactive_resize_end(&win);
+                }
                 Op::Communicate(id) => {
                     let mut window = None;
                     match &mut layout.monitor_set {
@@ -2001,6 +3093,23 @@ mod tests {
 
                     layout.move_workspace_to_output(&output);
                 }
+                Op::ToggleOverview => layout.toggle_overview(),
+                Op::OverviewPan { output_id, dy } => {
+                    let name = format!("output{output_id}");
+                    let Some(output) = layout.outputs().find(|o| o.name() == name).cloned() else {
+                        return;
+                    };
+
+                    layout.overview_pan(&output, dy);
+                }
+                Op::OverviewPickWorkspace { output_id, px, py } => {
+                    let name = format!("output{output_id}");
+                    let Some(output) = layout.outputs().find(|o| o.name() == name).cloned() else {
+                        return;
+                    };
+
+                    layout.overview_pick_workspace(&output, Point::from((px as f64, py as f64)));
+                }
             }
         }
     }
@@ -2060,6 +3169,14 @@ mod tests {
             Op::FullscreenWindow(1),
             Op::FullscreenWindow(2),
             Op::FullscreenWindow(3),
+            Op::SetMaximized {
+                id: 1,
+                maximized: true,
+            },
+            Op::SetMaximized {
+                id: 2,
+                maximized: false,
+            },
             Op::FocusColumnLeft,
             Op::FocusColumnRight,
             Op::FocusWindowUp,
@@ -2071,11 +3188,19 @@ mod tests {
             Op::ConsumeWindowIntoColumn,
             Op::ExpelWindowFromColumn,
             Op::CenterColumn,
+            Op::ToggleColumnTabbedDisplay,
             Op::FocusWorkspaceDown,
             Op::FocusWorkspaceUp,
             Op::FocusWorkspace(1),
             Op::FocusWorkspace(2),
             Op::FocusWorkspace(3),
+            Op::FocusWorkspaceAutoBackAndForth(1),
+            Op::FocusWorkspaceAutoBackAndForth(2),
+            Op::FocusWorkspacePrevious,
+            Op::MoveWindowToScratchpad(0),
+            Op::MoveWindowToScratchpad(1),
+            Op::ToggleScratchpad(0),
+            Op::ToggleScratchpad(1),
             Op::MoveWindowToWorkspaceDown,
             Op::MoveWindowToWorkspaceUp,
             Op::MoveWindowToWorkspace(1),
@@ -2093,6 +3218,43 @@ mod tests {
             Op::ConsumeOrExpelWindowLeft,
             Op::ConsumeOrExpelWindowRight,
             Op::MoveWorkspaceToOutput(1),
+            Op::ToggleOverview,
+            Op::OverviewPan {
+                output_id: 1,
+                dy: 500.,
+            },
+            Op::OverviewPickWorkspace {
+                output_id: 1,
+                px: 0,
+                py: 0,
+            },
+            Op::AddNamedWorkspace {
+                ws_name: 1,
+                output_name: Some(1),
+            },
+            Op::UnnameWorkspace { ws_name: 1 },
+            Op::FocusWorkspaceByName { ws_name: 1 },
+            Op::ToggleWindowFloating,
+            Op::SwitchFocusFloatingTiling,
+            Op::InteractiveMove {
+                window: 1,
+                output_id: 1,
+                dx: 0,
+                dy: 0,
+                px: 0,
+                py: 0,
+                drop: true,
+            },
+            Op::InteractiveResizeBegin {
+                window: 1,
+                edges: ResizeEdge::BOTTOM_LEFT,
+            },
+            Op::InteractiveResizeUpdate {
+                window: 1,
+                dx: -100.,
+                dy: 100.,
+            },
+            Op::InteractiveResizeEnd(1),
         ];
 
         for third in every_op {
@@ -2153,7 +3315,10 @@ mod tests {
                 bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
                 min_max_size: Default::default(),
             },
-            Op::MoveWindowToOutput(2),
+            Op::MoveWindowToOutput {
+                output_id: 2,
+                workspace_idx: None,
+            },
             Op::FocusOutput(1),
             Op::Communicate(1),
             Op::Communicate(2),
@@ -2549,18 +3714,930 @@ mod tests {
     }
 
     #[test]
-    fn fullscreen() {
+    fn add_window_to_unfocused_output() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddOutput(2),
+            Op::FocusOutput(1),
+            Op::AddWindowTo {
+                id: 0,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+                output_id: Some(2),
+                workspace_idx: Some(0),
+                activate: Some(false),
+                is_floating: false,
+            },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+        }
+
+        let MonitorSet::Normal {
+            monitors,
+            active_monitor_idx,
+            ..
+        } = layout.monitor_set
+        else {
+            unreachable!()
+        };
+
+        assert_eq!(active_monitor_idx, 0);
+        assert!(!monitors[0].workspaces[0].has_windows());
+        assert_eq!(monitors[1].workspaces.len(), 2);
+        assert!(monitors[1].workspaces[0].has_windows());
+    }
+
+    #[test]
+    fn named_workspaces_are_not_cleaned_up() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddNamedWorkspace {
+                ws_name: 1,
+                output_name: None,
+            },
+            Op::AddWindow {
+                id: 0,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::FocusWorkspace(0),
+            Op::FocusWorkspace(2),
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let MonitorSet::Normal { monitors, .. } = layout.monitor_set else {
+            unreachable!()
+        };
+
+        assert_eq!(monitors[0].workspaces.len(), 3);
+        assert_eq!(monitors[0].workspaces[0].name.as_deref(), Some("ws1"));
+        assert!(!monitors[0].workspaces[0].has_windows());
+        assert!(monitors[0].workspaces[1].has_windows());
+    }
+
+    #[test]
+    fn named_workspace_follows_configured_output() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddNamedWorkspace {
+                ws_name: 1,
+                output_name: Some(2),
+            },
+            Op::FocusWorkspaceByName { ws_name: 1 },
+            Op::AddWindow {
+                id: 0,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::CloseWindow(0),
+            Op::AddOutput(2),
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let (output, idx) = layout.find_workspace_by_name("ws1").unwrap();
+        assert_eq!(output.name(), "output2");
+        assert_eq!(idx, 0);
+
+        Op::RemoveOutput(2).apply(&mut layout);
+        layout.verify_invariants();
+
+        let (output, _) = layout.find_workspace_by_name("ws1").unwrap();
+        assert_eq!(output.name(), "output1");
+    }
+
+    #[test]
+    fn unnamed_workspace_gets_cleaned_up() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddNamedWorkspace {
+                ws_name: 1,
+                output_name: None,
+            },
+            Op::UnnameWorkspace { ws_name: 1 },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let MonitorSet::Normal { monitors, .. } = layout.monitor_set else {
+            unreachable!()
+        };
+
+        assert_eq!(monitors[0].workspaces.len(), 1);
+    }
+
+    #[test]
+    fn toggle_window_floating() {
         let ops = [
             Op::AddOutput(1),
             Op::AddWindow {
                 id: 1,
                 bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
-                min_max_size: (Size::from((0, 0)), Size::from((i32::MAX, i32::MAX))),
+                min_max_size: Default::default(),
             },
-            Op::FullscreenWindow(1),
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::ToggleWindowFloating,
         ];
 
-        check_ops(&ops);
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let ws = layout.active_workspace().unwrap();
+        assert!(ws.floating_is_active());
+        assert_eq!(ws.columns.len(), 1);
+        assert_eq!(ws.active_window().unwrap().0.id, 2);
+
+        Op::SwitchFocusFloatingTiling.apply(&mut layout);
+        let ws = layout.active_workspace().unwrap();
+        assert!(!ws.floating_is_active());
+        assert_eq!(ws.active_window().unwrap().0.id, 1);
+
+        Op::SwitchFocusFloatingTiling.apply(&mut layout);
+        Op::ToggleWindowFloating.apply(&mut layout);
+        layout.verify_invariants();
+        let ws = layout.active_workspace().unwrap();
+        assert!(!ws.floating_is_active());
+        assert_eq!(ws.columns.len(), 2);
+        assert_eq!(ws.active_window().unwrap().0.id, 2);
+    }
+
+    #[test]
+    fn floating_window_moves_to_workspace() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindowTo {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+                output_id: None,
+                workspace_idx: None,
+                activate: None,
+                is_floating: true,
+            },
+            Op::MoveWindowToWorkspaceDown,
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let MonitorSet::Normal { monitors, .. } = layout.monitor_set else {
+            unreachable!()
+        };
+
+        assert_eq!(monitors[0].active_workspace_idx, 1);
+        let ws = &monitors[0].workspaces[1];
+        assert!(ws.floating_is_active());
+        assert_eq!(ws.floating_windows().count(), 1);
+        assert!(ws.columns.is_empty());
+    }
+
+    fn column_window_ids(ws: &Workspace<TestWindow>) -> Vec<Vec<usize>> {
+        ws.columns
+            .iter()
+            .map(|col| col.tiles.iter().map(|tile| tile.window().0.id).collect())
+            .collect()
+    }
+
+    /// Two windows in their own columns. Both columns are 100 wide with 16 gaps, so on the
+    /// output they span X 16..116 and 132..232.
+    fn interactive_move_layout() -> Layout<TestWindow> {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+        layout.advance_animations(get_monotonic_time() + Duration::from_secs(10));
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(column_window_ids(ws), [vec![1], vec![2]]);
+        layout
+    }
+
+    #[test]
+    fn interactive_move_into_column() {
+        let mut layout = interactive_move_layout();
+
+        // The middle of the first column, below its window.
+        Op::InteractiveMove {
+            window: 2,
+            output_id: 1,
+            dx: 0,
+            dy: 0,
+            px: 66,
+            py: 600,
+            drop: true,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(column_window_ids(ws), [vec![1, 2]]);
+        assert_eq!(ws.active_window().unwrap().0.id, 2);
+    }
+
+    #[test]
+    fn interactive_move_into_new_column() {
+        let mut layout = interactive_move_layout();
+
+        // The left edge of the first column.
+        Op::InteractiveMove {
+            window: 2,
+            output_id: 1,
+            dx: 0,
+            dy: 0,
+            px: 20,
+            py: 100,
+            drop: true,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(column_window_ids(ws), [vec![2], vec![1]]);
+        assert_eq!(ws.active_window().unwrap().0.id, 2);
+    }
+
+    #[test]
+    fn interactive_move_without_drop_keeps_layout() {
+        let mut layout = interactive_move_layout();
+
+        Op::InteractiveMove {
+            window: 2,
+            output_id: 1,
+            dx: 0,
+            dy: 0,
+            px: 66,
+            py: 600,
+            drop: false,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(column_window_ids(ws), [vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn interactive_move_to_other_workspace() {
+        let mut layout = interactive_move_layout();
+
+        Op::ToggleOverview.apply(&mut layout);
+        layout.advance_animations(get_monotonic_time() + Duration::from_secs(20));
+
+        // In the overview, workspaces are scaled in half and centered, so the second workspace
+        // spans Y 572..932.
+        Op::InteractiveMove {
+            window: 2,
+            output_id: 1,
+            dx: 0,
+            dy: 0,
+            px: 640,
+            py: 700,
+            drop: true,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+            unreachable!()
+        };
+        assert_eq!(column_window_ids(&monitors[0].workspaces[0]), [vec![1]]);
+        assert_eq!(column_window_ids(&monitors[0].workspaces[1]), [vec![2]]);
+    }
+
+    #[test]
+    fn interactive_move_to_other_output() {
+        let mut layout = interactive_move_layout();
+        Op::AddOutput(2).apply(&mut layout);
+
+        Op::InteractiveMove {
+            window: 2,
+            output_id: 2,
+            dx: 0,
+            dy: 0,
+            px: 640,
+            py: 360,
+            drop: true,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+            unreachable!()
+        };
+        assert_eq!(monitors[1].output.name(), "output2");
+        assert_eq!(column_window_ids(&monitors[0].workspaces[0]), [vec![1]]);
+        assert_eq!(column_window_ids(&monitors[1].workspaces[0]), [vec![2]]);
+    }
+
+    #[test]
+    fn interactive_resize_sets_fixed_width() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::InteractiveResizeBegin {
+                window: 1,
+                edges: ResizeEdge::RIGHT,
+            },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let ws = layout.active_workspace().unwrap();
+        let original = ws.columns[0].tiles[0].tile_size().w;
+
+        Op::InteractiveResizeUpdate {
+            window: 1,
+            dx: 50.,
+            dy: 1000.,
+        }
+        .apply(&mut layout);
+        Op::InteractiveResizeEnd(1).apply(&mut layout);
+        layout.verify_invariants();
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(ws.columns[0].width, ColumnWidth::Fixed(original + 50));
+    }
+
+    #[test]
+    fn maximize_extracts_window_and_restores_width() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::FocusColumnLeft,
+            Op::ConsumeWindowIntoColumn,
+            Op::SetMaximized {
+                id: 1,
+                maximized: true,
+            },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(ws.columns.len(), 2);
+        assert!(!ws.columns[0].is_maximized);
+        assert!(ws.columns[1].is_maximized);
+        assert_eq!(ws.columns[1].tiles[0].window().0.id, 1);
+        let width = ws.columns[1].width;
+
+        Op::SetMaximized {
+            id: 1,
+            maximized: false,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        let ws = layout.active_workspace().unwrap();
+        assert!(!ws.columns[1].is_maximized);
+        assert_eq!(ws.columns[1].width, width);
+    }
+
+    #[test]
+    fn tabbed_column_shows_tabs_for_all_windows() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::FocusColumnLeft,
+            Op::ConsumeWindowIntoColumn,
+            Op::ToggleColumnTabbedDisplay,
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(ws.columns.len(), 1);
+        let col = &ws.columns[0];
+        assert_eq!(col.display, ColumnDisplay::Tabbed);
+
+        // Both tiles are placed right below the tab bar.
+        let tab_bar_y = layout.options.gaps;
+        assert_eq!(col.window_y(0), tab_bar_y + TAB_BAR_HEIGHT);
+        assert_eq!(col.window_y(1), tab_bar_y + TAB_BAR_HEIGHT);
+
+        // Every window has a tab in the tab bar that activates it.
+        let mut tab_ids = vec![];
+        for x in 0..1280 {
+            let pos = Point::from((f64::from(x), f64::from(tab_bar_y + 1)));
+            if let Some((window, None)) = ws.window_under(pos) {
+                if !tab_ids.contains(&window.0.id) {
+                    tab_ids.push(window.0.id);
+                }
+            }
+        }
+        assert_eq!(tab_ids, [1, 2]);
+
+        Op::ToggleColumnTabbedDisplay.apply(&mut layout);
+        layout.verify_invariants();
+
+        let ws = layout.active_workspace().unwrap();
+        assert_eq!(ws.columns[0].display, ColumnDisplay::Normal);
+    }
+
+    #[test]
+    fn scroll_amount_to_activate_offscreen_column() {
+        let mut ops = vec![Op::AddOutput(1)];
+        for id in 1..=3 {
+            ops.push(Op::AddWindow {
+                id,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            });
+        }
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let ws = layout.active_workspace().unwrap();
+        let window = |idx: usize| ws.columns[idx].tiles[0].window();
+
+        // The active column needs no scrolling.
+        assert_eq!(layout.scroll_amount_to_activate(window(2)), 0.);
+
+        // The first column is out of view, so activating it scrolls more than activating the
+        // visible neighbor.
+        let far = layout.scroll_amount_to_activate(window(0));
+        let near = layout.scroll_amount_to_activate(window(1));
+        assert!(far > 0.);
+        assert!(near < far);
+    }
+
+    #[test]
+    fn active_tile_visual_rectangle_uses_final_view_position() {
+        let mut ops = vec![Op::AddOutput(1)];
+        for id in 1..=3 {
+            ops.push(Op::AddWindow {
+                id,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            });
+        }
+        ops.push(Op::FocusColumnFirst);
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        // The view is still scrolling, but the rectangle is where the tile will end up.
+        let ws = layout.active_workspace().unwrap();
+        let rect = ws.active_tile_visual_rectangle().unwrap();
+        let output_area = Rectangle::from_loc_and_size((0, 0), (1280, 720));
+        assert!(output_area.contains_rect(rect));
+    }
+
+    #[test]
+    fn focus_workspace_back_and_forth() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::FocusWorkspaceDown,
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let active_idx = |layout: &Layout<TestWindow>| {
+            let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+                unreachable!()
+            };
+            monitors[0].active_workspace_idx
+        };
+        assert_eq!(active_idx(&layout), 1);
+
+        Op::FocusWorkspacePrevious.apply(&mut layout);
+        layout.verify_invariants();
+        assert_eq!(active_idx(&layout), 0);
+
+        // Focusing the already active workspace goes back to the previous one.
+        Op::FocusWorkspaceAutoBackAndForth(0).apply(&mut layout);
+        layout.verify_invariants();
+        assert_eq!(active_idx(&layout), 1);
+
+        Op::FocusWorkspaceAutoBackAndForth(0).apply(&mut layout);
+        layout.verify_invariants();
+        assert_eq!(active_idx(&layout), 0);
+    }
+
+    #[test]
+    fn scratchpad_hides_and_shows_window() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::MoveWindowToScratchpad(0),
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+        assert!(layout.focus().is_none());
+
+        // The window shows up on whichever workspace is active.
+        Op::FocusWorkspaceDown.apply(&mut layout);
+        Op::ToggleScratchpad(0).apply(&mut layout);
+        layout.verify_invariants();
+        assert_eq!(layout.focus().map(|win| win.0.id), Some(1));
+        assert!(layout
+            .active_workspace()
+            .unwrap()
+            .has_window(layout.focus().unwrap()));
+
+        Op::ToggleScratchpad(0).apply(&mut layout);
+        layout.verify_invariants();
+        assert!(layout.focus().is_none());
+
+        // Unknown scratchpads do nothing.
+        Op::ToggleScratchpad(1).apply(&mut layout);
+        layout.verify_invariants();
+        assert!(layout.focus().is_none());
+
+        // Hidden windows are still reported, just without an output.
+        let mut windows = Vec::new();
+        layout.with_windows(|win, output| windows.push((win.0.id, output.is_some())));
+        assert_eq!(windows, [(1, false)]);
+    }
+
+    #[test]
+    fn scratchpad_forgets_window_closed_while_shown() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::MoveWindowToScratchpad(0),
+            Op::ToggleScratchpad(0),
+            Op::CloseWindow(1),
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        assert!(layout.scratchpads.is_empty());
+    }
+
+    #[test]
+    fn workspace_activations_are_recorded_in_order() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::FocusWorkspace(1),
+            Op::FocusWorkspace(0),
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let indices: Vec<_> = layout
+            .take_workspace_activations()
+            .into_iter()
+            .map(|(_, idx)| idx)
+            .collect();
+        assert_eq!(indices, [0, 1, 0]);
+        assert!(layout.take_workspace_activations().is_empty());
+    }
+
+    #[test]
+    fn restore_layout_from_snapshot() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::AddWindow {
+                id: 3,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::ConsumeWindowIntoColumn,
+            Op::SetWindowHeight(SizeChange::SetFixed(300)),
+            Op::SetColumnWidth(SizeChange::SetProportion(0.25)),
+            Op::FocusWorkspaceDown,
+            Op::AddWindow {
+                id: 4,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::AddWindow {
+                id: 5,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::ToggleWindowFloating,
+            Op::MoveFloatingWindow { x: 30, y: -20 },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+
+        let identify = |win: &TestWindow| snapshot::WindowIdentity {
+            app_id: Some(win.0.id.to_string()),
+            ..Default::default()
+        };
+        let snapshot = layout.snapshot(identify);
+        assert_eq!(snapshot.workspaces.len(), 2);
+        assert_eq!(snapshot.workspaces[1].floating.len(), 1);
+
+        let mut restored = Layout::default();
+        Op::AddOutput(1).apply(&mut restored);
+        restored.start_restore(snapshot.clone());
+
+        // Windows come back into place regardless of the order they show up in, including the
+        // floating ones.
+        for id in [5, 4, 3, 1, 2] {
+            let bbox = Rectangle::from_loc_and_size((0, 0), (100, 200));
+            let win = TestWindow::new(id, bbox, Size::default(), Size::default());
+            assert!(restored.restore_window(&win, &identify(&win), false));
+            restored.verify_invariants();
+        }
+        assert_eq!(restored.snapshot(identify), snapshot);
+
+        // Windows that weren't in the snapshot are left for the caller to add.
+        let bbox = Rectangle::from_loc_and_size((0, 0), (100, 200));
+        let win = TestWindow::new(6, bbox, Size::default(), Size::default());
+        assert!(!restored.restore_window(&win, &identify(&win), false));
+    }
+
+    #[test]
+    fn overview_drag_window_to_other_workspace() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::FocusWorkspaceDown,
+            Op::AddWindow {
+                id: 2,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+            Op::ToggleOverview,
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+            layout.verify_invariants();
+        }
+        assert!(layout.is_overview_open());
+
+        // Finish the overview animation. Workspaces are now half the output size, with the
+        // active one (idx 1) in the middle and the first one partially visible above it.
+        layout.advance_animations(get_monotonic_time() + Duration::from_secs(10));
+
+        let output = layout.outputs().next().unwrap().clone();
+        let window = TestWindow::new(2, Rectangle::default(), Size::default(), Size::default());
+        let pos = Point::from((640., 100.));
+        assert!(layout.interactive_move_update(&window, Point::from((0, -300)), &output, pos));
+        layout.interactive_move_end(&window, Some((&output, pos)));
+        layout.verify_invariants();
+
+        let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+            unreachable!()
+        };
+        assert!(monitors[0].workspaces[0].has_window(&window));
+        assert_eq!(monitors[0].active_workspace_idx, 0);
+
+        // Clicking on an empty area of the last workspace picks it and closes the overview.
+        layout.advance_animations(get_monotonic_time() + Duration::from_secs(20));
+        Op::OverviewPickWorkspace {
+            output_id: 1,
+            px: 640,
+            py: 700,
+        }
+        .apply(&mut layout);
+        layout.verify_invariants();
+
+        assert!(!layout.is_overview_open());
+        let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+            unreachable!()
+        };
+        assert_eq!(
+            monitors[0].active_workspace_idx,
+            monitors[0].workspaces.len() - 1
+        );
+    }
+
+    #[test]
+    fn floating_fullscreen_window_picks_own_size() {
+        let mut layout = Layout::default();
+        Op::AddOutput(1).apply(&mut layout);
+
+        let bbox = Rectangle::from_loc_and_size((0, 0), (100, 200));
+        let window = TestWindow::new(1, bbox, Size::default(), Size::default());
+        layout.add_window(window.clone(), None, false);
+        layout.toggle_fullscreen(&window);
+        assert!(window.is_pending_fullscreen());
+
+        layout.toggle_window_floating();
+        layout.verify_invariants();
+
+        assert!(!window.is_pending_fullscreen());
+        assert_eq!(window.0.requested_size.get(), Some(Size::from((0, 0))));
+    }
+
+    #[test]
+    fn fullscreen() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: (Size::from((0, 0)), Size::from((i32::MAX, i32::MAX))),
+            },
+            Op::FullscreenWindow(1),
+        ];
+
+        check_ops(&ops);
+    }
+
+    #[test]
+    fn workspace_switch_gesture_uses_velocity() {
+        let ops = [
+            Op::AddOutput(1),
+            Op::AddWindow {
+                id: 1,
+                bbox: Rectangle::from_loc_and_size((0, 0), (100, 200)),
+                min_max_size: Default::default(),
+            },
+        ];
+
+        let mut layout = Layout::default();
+        for op in ops {
+            op.apply(&mut layout);
+        }
+        let output = layout.outputs().next().unwrap().clone();
+
+        let active_workspace_idx = |layout: &Layout<TestWindow>| {
+            let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+                unreachable!()
+            };
+            monitors[0].active_workspace_idx
+        };
+
+        // A slow swipe of a fifth of a workspace snaps back.
+        layout.workspace_switch_gesture_begin(&output);
+        for i in 1..=4 {
+            layout.workspace_switch_gesture_update(20., Duration::from_millis(i * 200));
+        }
+        layout.workspace_switch_gesture_end(false);
+        layout.verify_invariants();
+        assert_eq!(active_workspace_idx(&layout), 0);
+
+        // The same swipe done quickly carries over to the next workspace.
+        layout.workspace_switch_gesture_begin(&output);
+        for i in 1..=4 {
+            layout.workspace_switch_gesture_update(20., Duration::from_millis(1000 + i * 5));
+        }
+        layout.workspace_switch_gesture_end(false);
+        layout.verify_invariants();
+        assert_eq!(active_workspace_idx(&layout), 1);
+    }
+
+    #[test]
+    fn workspace_switch_gesture_begins_within_workspaces() {
+        let mut layout = Layout::default();
+        Op::AddOutput(1).apply(&mut layout);
+        let output = layout.outputs().next().unwrap().clone();
+
+        // Pretend that a spring overshot past the first workspace.
+        let MonitorSet::Normal { monitors, .. } = &mut layout.monitor_set else {
+            unreachable!()
+        };
+        monitors[0].workspace_switch = Some(WorkspaceSwitch::Gesture(WorkspaceSwitchGesture {
+            center_idx: 0,
+            current_idx: -0.3,
+            tracker: SwipeTracker::new(),
+        }));
+
+        layout.workspace_switch_gesture_begin(&output);
+
+        let MonitorSet::Normal { monitors, .. } = &layout.monitor_set else {
+            unreachable!()
+        };
+        let Some(WorkspaceSwitch::Gesture(gesture)) = &monitors[0].workspace_switch else {
+            unreachable!()
+        };
+        assert_eq!(gesture.current_idx, 0.);
     }
 
     fn arbitrary_spacing() -> impl Strategy<Value = u16> {
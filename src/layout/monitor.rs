@@ -1,22 +1,32 @@
 use std::cmp::min;
+use std::mem;
 use std::rc::Rc;
 use std::time::Duration;
 
 use niri_config::SizeChange;
 use smithay::backend::renderer::element::utils::{
-    CropRenderElement, Relocate, RelocateRenderElement,
+    CropRenderElement, Relocate, RelocateRenderElement, RescaleRenderElement,
 };
 use smithay::output::Output;
-use smithay::utils::{Logical, Point, Rectangle, Scale};
+use smithay::utils::{Logical, Physical, Point, Rectangle, Scale, Size};
 
 use super::workspace::{
-    compute_working_area, Column, ColumnWidth, OutputId, Workspace, WorkspaceRenderElement,
+    compute_working_area, Column, ColumnWidth, InsertPosition, OutputId, Workspace, WorkspaceId,
+    WorkspaceRenderElement,
 };
 use super::{LayoutElement, Options};
 use crate::animation::Animation;
+use crate::input::swipe_tracker::SwipeTracker;
+use crate::niri_render_elements;
 use crate::render_helpers::NiriRenderer;
 use crate::utils::output_size;
 
+/// Scale of the workspaces in the overview.
+const OVERVIEW_ZOOM: f64 = 0.5;
+
+/// Gap between workspaces in the overview, in logical pixels.
+const OVERVIEW_GAP: f64 = 32.;
+
 #[derive(Debug)]
 pub struct Monitor<W: LayoutElement> {
     /// Output for this monitor.
@@ -24,9 +34,21 @@ pub struct Monitor<W: LayoutElement> {
     // Must always contain at least one.
     pub workspaces: Vec<Workspace<W>>,
     /// Index of the currently active workspace.
+    ///
+    /// Change it with [`Monitor::set_active_workspace_idx()`] so that the change is reported.
     pub active_workspace_idx: usize,
+    /// Active workspace indices not yet taken by [`Monitor::take_workspace_activations()`].
+    workspace_activations: Vec<usize>,
     /// In-progress switch between workspaces.
     pub workspace_switch: Option<WorkspaceSwitch>,
+    /// ID of the previously active workspace, for switching back and forth.
+    pub previous_workspace_id: Option<WorkspaceId>,
+    /// Whether the overview is open on this monitor.
+    overview_open: bool,
+    /// In-progress opening or closing of the overview, from 0 (closed) to 1 (open).
+    overview_anim: Option<Animation>,
+    /// Vertical pan of the overview, in workspaces, relative to the active workspace.
+    overview_pan: f64,
     /// Configurable properties of the layout.
     pub options: Rc<Options>,
 }
@@ -43,10 +65,18 @@ pub struct WorkspaceSwitchGesture {
     pub center_idx: usize,
     /// Current, fractional workspace index.
     pub current_idx: f64,
+    /// Recent gesture motion, for animating with the gesture velocity on release.
+    pub tracker: SwipeTracker,
 }
 
-pub type MonitorRenderElement<R> =
-    RelocateRenderElement<CropRenderElement<WorkspaceRenderElement<R>>>;
+niri_render_elements! {
+    MonitorRenderElement => {
+        Workspace = RelocateRenderElement<CropRenderElement<WorkspaceRenderElement<R>>>,
+        Overview = RelocateRenderElement<
+            CropRenderElement<RescaleRenderElement<WorkspaceRenderElement<R>>>
+        >,
+    }
+}
 
 impl WorkspaceSwitch {
     pub fn current_idx(&self) -> f64 {
@@ -71,7 +101,12 @@ impl<W: LayoutElement> Monitor<W> {
             output,
             workspaces,
             active_workspace_idx: 0,
+            workspace_activations: vec![0],
             workspace_switch: None,
+            previous_workspace_id: None,
+            overview_open: false,
+            overview_anim: None,
+            overview_pan: 0.,
             options,
         }
     }
@@ -80,6 +115,19 @@ impl<W: LayoutElement> Monitor<W> {
         &mut self.workspaces[self.active_workspace_idx]
     }
 
+    /// Sets the active workspace index without animating, recording the change.
+    ///
+    /// Also use this when the active workspace stays the same but its index shifts.
+    pub fn set_active_workspace_idx(&mut self, idx: usize) {
+        self.active_workspace_idx = idx;
+        self.workspace_activations.push(idx);
+    }
+
+    /// Returns the active workspace indices set since the last call, in order.
+    pub fn take_workspace_activations(&mut self) -> Vec<usize> {
+        mem::take(&mut self.workspace_activations)
+    }
+
     fn activate_workspace(&mut self, idx: usize) {
         if self.active_workspace_idx == idx {
             return;
@@ -88,15 +136,24 @@ impl<W: LayoutElement> Monitor<W> {
         let current_idx = self
             .workspace_switch
             .as_ref()
-            .map(|s| s.current_idx())
+            .map(|s| self.clamped_switch_idx(s))
             .unwrap_or(self.active_workspace_idx as f64);
 
-        self.active_workspace_idx = idx;
+        self.previous_workspace_id = Some(self.workspaces[self.active_workspace_idx].id());
+        self.set_active_workspace_idx(idx);
+        self.overview_pan = 0.;
+
+        // Keep the momentum of an ongoing switch animation.
+        let velocity = match &self.workspace_switch {
+            Some(WorkspaceSwitch::Animation(anim)) => anim.velocity(),
+            _ => 0.,
+        };
 
         self.workspace_switch = Some(WorkspaceSwitch::Animation(Animation::new(
             current_idx,
             idx as f64,
-            Duration::from_millis(250),
+            velocity,
+            self.options.animations.workspace_switch,
         )));
     }
 
@@ -112,18 +169,31 @@ impl<W: LayoutElement> Monitor<W> {
 
         workspace.add_window(window, activate, width, is_full_width);
 
-        // After adding a new window, workspace becomes this output's own.
-        workspace.original_output = OutputId::new(&self.output);
+        self.finish_adding_to_workspace(workspace_idx, activate);
+    }
 
-        if workspace_idx == self.workspaces.len() - 1 {
-            // Insert a new empty workspace.
-            let ws = Workspace::new(self.output.clone(), self.options.clone());
-            self.workspaces.push(ws);
-        }
+    pub fn add_floating_window(&mut self, workspace_idx: usize, window: W, activate: bool) {
+        let workspace = &mut self.workspaces[workspace_idx];
 
-        if activate {
-            self.activate_workspace(workspace_idx);
-        }
+        workspace.add_floating_window(window, activate);
+
+        self.finish_adding_to_workspace(workspace_idx, activate);
+    }
+
+    pub fn add_window_at(
+        &mut self,
+        workspace_idx: usize,
+        window: W,
+        position: InsertPosition,
+        activate: bool,
+        width: ColumnWidth,
+        is_full_width: bool,
+    ) {
+        let workspace = &mut self.workspaces[workspace_idx];
+
+        workspace.add_window_at(window, position, activate, width, is_full_width);
+
+        self.finish_adding_to_workspace(workspace_idx, activate);
     }
 
     pub fn add_column(&mut self, workspace_idx: usize, column: Column<W>, activate: bool) {
@@ -131,8 +201,17 @@ impl<W: LayoutElement> Monitor<W> {
 
         workspace.add_column(column, activate);
 
-        // After adding a new window, workspace becomes this output's own.
-        workspace.original_output = OutputId::new(&self.output);
+        self.finish_adding_to_workspace(workspace_idx, activate);
+    }
+
+    fn finish_adding_to_workspace(&mut self, workspace_idx: usize, activate: bool) {
+        let workspace = &mut self.workspaces[workspace_idx];
+
+        // After adding a new window, workspace becomes this output's own. Named workspaces stick
+        // to their original output instead.
+        if workspace.name.is_none() {
+            workspace.original_output = OutputId::new(&self.output);
+        }
 
         if workspace_idx == self.workspaces.len() - 1 {
             // Insert a new empty workspace.
@@ -153,10 +232,10 @@ impl<W: LayoutElement> Monitor<W> {
                 continue;
             }
 
-            if !self.workspaces[idx].has_windows() {
+            if !self.workspaces[idx].has_windows_or_name() {
                 self.workspaces.remove(idx);
                 if self.active_workspace_idx > idx {
-                    self.active_workspace_idx -= 1;
+                    self.set_active_workspace_idx(self.active_workspace_idx - 1);
                 }
             }
         }
@@ -188,6 +267,10 @@ impl<W: LayoutElement> Monitor<W> {
 
     pub fn move_down_or_to_workspace_down(&mut self) {
         let workspace = self.active_workspace();
+        if workspace.floating_is_active() {
+            self.move_to_workspace_down();
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -203,6 +286,10 @@ impl<W: LayoutElement> Monitor<W> {
 
     pub fn move_up_or_to_workspace_up(&mut self) {
         let workspace = self.active_workspace();
+        if workspace.floating_is_active() {
+            self.move_to_workspace_up();
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -287,6 +374,10 @@ impl<W: LayoutElement> Monitor<W> {
         }
 
         let workspace = &mut self.workspaces[source_workspace_idx];
+        if let Some(window) = workspace.remove_active_floating_window() {
+            self.add_floating_window(new_idx, window, true);
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -309,6 +400,10 @@ impl<W: LayoutElement> Monitor<W> {
         }
 
         let workspace = &mut self.workspaces[source_workspace_idx];
+        if let Some(window) = workspace.remove_active_floating_window() {
+            self.add_floating_window(new_idx, window, true);
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -331,6 +426,15 @@ impl<W: LayoutElement> Monitor<W> {
         }
 
         let workspace = &mut self.workspaces[source_workspace_idx];
+        if let Some(window) = workspace.remove_active_floating_window() {
+            self.add_floating_window(new_idx, window, true);
+
+            // Don't animate this action.
+            self.workspace_switch = None;
+
+            self.clean_up_workspaces();
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -358,6 +462,10 @@ impl<W: LayoutElement> Monitor<W> {
         }
 
         let workspace = &mut self.workspaces[source_workspace_idx];
+        if let Some(window) = workspace.remove_active_floating_window() {
+            self.add_floating_window(new_idx, window, true);
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -375,6 +483,10 @@ impl<W: LayoutElement> Monitor<W> {
         }
 
         let workspace = &mut self.workspaces[source_workspace_idx];
+        if let Some(window) = workspace.remove_active_floating_window() {
+            self.add_floating_window(new_idx, window, true);
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -392,6 +504,15 @@ impl<W: LayoutElement> Monitor<W> {
         }
 
         let workspace = &mut self.workspaces[source_workspace_idx];
+        if let Some(window) = workspace.remove_active_floating_window() {
+            self.add_floating_window(new_idx, window, true);
+
+            // Don't animate this action.
+            self.workspace_switch = None;
+
+            self.clean_up_workspaces();
+            return;
+        }
         if workspace.columns.is_empty() {
             return;
         }
@@ -424,6 +545,31 @@ impl<W: LayoutElement> Monitor<W> {
         self.clean_up_workspaces();
     }
 
+    /// Switches to the workspace, or back to the previous one if it is already active.
+    pub fn switch_workspace_auto_back_and_forth(&mut self, idx: usize) {
+        let idx = min(idx, self.workspaces.len() - 1);
+
+        if idx == self.active_workspace_idx {
+            if let Some(prev_idx) = self.previous_workspace_idx() {
+                self.switch_workspace(prev_idx);
+                return;
+            }
+        }
+
+        self.switch_workspace(idx);
+    }
+
+    pub fn switch_workspace_previous(&mut self) {
+        if let Some(idx) = self.previous_workspace_idx() {
+            self.switch_workspace(idx);
+        }
+    }
+
+    fn previous_workspace_idx(&self) -> Option<usize> {
+        let id = self.previous_workspace_id?;
+        self.workspaces.iter().position(|ws| ws.id() == id)
+    }
+
     pub fn consume_into_column(&mut self) {
         self.active_workspace().consume_into_column();
     }
@@ -437,13 +583,14 @@ impl<W: LayoutElement> Monitor<W> {
     }
 
     pub fn focus(&self) -> Option<&W> {
-        let workspace = &self.workspaces[self.active_workspace_idx];
-        if !workspace.has_windows() {
-            return None;
-        }
+        self.workspaces[self.active_workspace_idx].active_window()
+    }
 
-        let column = &workspace.columns[workspace.active_column_idx];
-        Some(column.tiles[column.active_tile_idx].window())
+    /// Returns the workspace switch index, kept within the workspaces in case a spring
+    /// animation overshoots.
+    pub(super) fn clamped_switch_idx(&self, switch: &WorkspaceSwitch) -> f64 {
+        let max = self.workspaces.len().saturating_sub(1) as f64;
+        switch.current_idx().clamp(0., max)
     }
 
     pub fn advance_animations(&mut self, current_time: Duration, is_active: bool) {
@@ -455,6 +602,13 @@ impl<W: LayoutElement> Monitor<W> {
             }
         }
 
+        if let Some(anim) = &mut self.overview_anim {
+            anim.set_current_time(current_time);
+            if anim.is_done() {
+                self.overview_anim = None;
+            }
+        }
+
         for ws in &mut self.workspaces {
             ws.advance_animations(current_time, is_active);
         }
@@ -464,11 +618,13 @@ impl<W: LayoutElement> Monitor<W> {
         self.workspace_switch
             .as_ref()
             .is_some_and(|s| s.is_animation())
+            || self.overview_anim.is_some()
             || self.workspaces.iter().any(|ws| ws.are_animations_ongoing())
     }
 
     pub fn are_transitions_ongoing(&self) -> bool {
         self.workspace_switch.is_some()
+            || self.overview_anim.is_some()
             || self.workspaces.iter().any(|ws| ws.are_animations_ongoing())
     }
 
@@ -497,6 +653,10 @@ impl<W: LayoutElement> Monitor<W> {
         self.active_workspace().toggle_full_width();
     }
 
+    pub fn toggle_column_tabbed_display(&mut self) {
+        self.active_workspace().toggle_column_tabbed_display();
+    }
+
     pub fn set_column_width(&mut self, change: SizeChange) {
         self.active_workspace().set_column_width(change);
     }
@@ -505,6 +665,19 @@ impl<W: LayoutElement> Monitor<W> {
         self.active_workspace().set_window_height(change);
     }
 
+    pub fn toggle_window_floating(&mut self) {
+        self.active_workspace().toggle_window_floating();
+    }
+
+    pub fn switch_focus_between_floating_and_tiling(&mut self) {
+        self.active_workspace()
+            .switch_focus_between_floating_and_tiling();
+    }
+
+    pub fn move_floating_window(&mut self, x: i32, y: i32) {
+        self.active_workspace().move_floating_window(x, y);
+    }
+
     pub fn move_workspace_down(&mut self) {
         let new_idx = min(self.active_workspace_idx + 1, self.workspaces.len() - 1);
         if new_idx == self.active_workspace_idx {
@@ -545,15 +718,139 @@ impl<W: LayoutElement> Monitor<W> {
         self.clean_up_workspaces();
     }
 
+    pub fn is_overview_open(&self) -> bool {
+        self.overview_open
+    }
+
+    /// Returns whether the overview is open or is animating open or closed.
+    pub fn is_overview_visible(&self) -> bool {
+        self.overview_open || self.overview_anim.is_some()
+    }
+
+    pub fn set_overview_open(&mut self, open: bool) {
+        if self.overview_open == open {
+            return;
+        }
+
+        let from = self.overview_progress();
+        let to = if open { 1. } else { 0. };
+
+        self.overview_open = open;
+        self.overview_pan = 0.;
+        self.overview_anim = Some(Animation::new(
+            from,
+            to,
+            0.,
+            self.options.animations.overview,
+        ));
+    }
+
+    /// Pans the overview vertically by the given amount of logical pixels.
+    pub fn overview_pan(&mut self, delta_y: f64) {
+        if !self.overview_open {
+            return;
+        }
+
+        let size = output_size(&self.output);
+        let step = size.h as f64 * OVERVIEW_ZOOM + OVERVIEW_GAP;
+        let active_idx = self.active_workspace_idx as f64;
+        let max_idx = (self.workspaces.len() - 1) as f64;
+
+        self.overview_pan =
+            (self.overview_pan + delta_y / step).clamp(-active_idx, max_idx - active_idx);
+    }
+
+    fn overview_progress(&self) -> f64 {
+        match &self.overview_anim {
+            Some(anim) => anim.value(),
+            None if self.overview_open => 1.,
+            None => 0.,
+        }
+    }
+
+    fn overview_zoom(&self) -> f64 {
+        1. - (1. - OVERVIEW_ZOOM) * self.overview_progress()
+    }
+
+    /// Returns the geometry of the workspace in the overview, within the output.
+    ///
+    /// Workspaces are stacked vertically around the active one. Fully closed, this matches the
+    /// regular workspace switch layout.
+    fn overview_workspace_geometry(&self, idx: usize) -> Rectangle<f64, Logical> {
+        let size = output_size(&self.output).to_f64();
+        let progress = self.overview_progress();
+        let zoom = self.overview_zoom();
+
+        let center_idx = self
+            .workspace_switch
+            .as_ref()
+            .map(|s| self.clamped_switch_idx(s))
+            .unwrap_or(self.active_workspace_idx as f64)
+            + self.overview_pan;
+
+        let ws_size = Size::from((size.w * zoom, size.h * zoom));
+        let gap = OVERVIEW_GAP * progress;
+
+        let x = (size.w - ws_size.w) / 2.;
+        let y = (size.h - ws_size.h) / 2. + (idx as f64 - center_idx) * (ws_size.h + gap);
+        Rectangle::from_loc_and_size((x, y), ws_size)
+    }
+
+    /// Returns the workspace under the position and the position within that workspace.
+    ///
+    /// Outside the overview, this is always the active workspace. In the overview, every
+    /// workspace spans the full output width, so that its off-screen columns can be reached.
+    pub fn workspace_under(
+        &self,
+        pos_within_output: Point<f64, Logical>,
+    ) -> Option<(usize, Point<f64, Logical>)> {
+        if !self.is_overview_visible() {
+            return Some((self.active_workspace_idx, pos_within_output));
+        }
+
+        let zoom = self.overview_zoom();
+        (0..self.workspaces.len()).find_map(|idx| {
+            let geo = self.overview_workspace_geometry(idx);
+            if pos_within_output.y < geo.loc.y || geo.loc.y + geo.size.h <= pos_within_output.y {
+                return None;
+            }
+
+            let pos_within_ws = Point::from((
+                (pos_within_output.x - geo.loc.x) / zoom,
+                (pos_within_output.y - geo.loc.y) / zoom,
+            ));
+            Some((idx, pos_within_ws))
+        })
+    }
+
+    /// Activates the workspace under the position in the overview and closes the overview.
+    pub fn overview_pick_workspace(&mut self, pos_within_output: Point<f64, Logical>) {
+        if !self.overview_open {
+            return;
+        }
+
+        if let Some((idx, _)) = self.workspace_under(pos_within_output) {
+            self.activate_workspace(idx);
+        }
+        self.set_overview_open(false);
+    }
+
     pub fn window_under(
         &self,
         pos_within_output: Point<f64, Logical>,
     ) -> Option<(&W, Option<Point<i32, Logical>>)> {
+        if self.is_overview_visible() {
+            // Windows in the overview are only picked and dragged, not interacted with.
+            let (idx, pos_within_ws) = self.workspace_under(pos_within_output)?;
+            let (win, _) = self.workspaces[idx].window_under(pos_within_ws)?;
+            return Some((win, None));
+        }
+
         match &self.workspace_switch {
             Some(switch) => {
                 let size = output_size(&self.output);
 
-                let render_idx = switch.current_idx();
+                let render_idx = self.clamped_switch_idx(switch);
                 let before_idx = render_idx.floor() as usize;
                 let after_idx = render_idx.ceil() as usize;
 
@@ -577,6 +874,11 @@ impl<W: LayoutElement> Monitor<W> {
     }
 
     pub fn render_above_top_layer(&self) -> bool {
+        // The overview always goes on top.
+        if self.is_overview_visible() {
+            return true;
+        }
+
         // Render above the top layer only if the view is stationary.
         if self.workspace_switch.is_some() {
             return false;
@@ -597,9 +899,13 @@ impl<W: LayoutElement> Monitor<W> {
         let output_mode = self.output.current_mode().unwrap();
         let size = output_transform.transform_size(output_mode.size);
 
+        if self.is_overview_visible() {
+            return self.render_overview(renderer);
+        }
+
         match &self.workspace_switch {
             Some(switch) => {
-                let render_idx = switch.current_idx();
+                let render_idx = self.clamped_switch_idx(switch);
                 let before_idx = render_idx.floor() as usize;
                 let after_idx = render_idx.ceil() as usize;
 
@@ -618,6 +924,7 @@ impl<W: LayoutElement> Monitor<W> {
                         (0, -offset),
                         Relocate::Relative,
                     ))
+                    .map(MonitorRenderElement::Workspace)
                 });
                 let after = after.into_iter().filter_map(|elem| {
                     Some(RelocateRenderElement::from_element(
@@ -629,6 +936,7 @@ impl<W: LayoutElement> Monitor<W> {
                         (0, -offset + size.h),
                         Relocate::Relative,
                     ))
+                    .map(MonitorRenderElement::Workspace)
                 });
                 before.chain(after).collect()
             }
@@ -654,8 +962,40 @@ impl<W: LayoutElement> Monitor<W> {
                             Relocate::Relative,
                         ))
                     })
+                    .map(MonitorRenderElement::Workspace)
                     .collect()
             }
         }
     }
+
+    fn render_overview<R: NiriRenderer>(&self, renderer: &mut R) -> Vec<MonitorRenderElement<R>> {
+        let output_scale = Scale::from(self.output.current_scale().fractional_scale());
+        let size = output_size(&self.output).to_f64();
+        let zoom = self.overview_zoom();
+
+        let mut rv = vec![];
+
+        for (idx, ws) in self.workspaces.iter().enumerate() {
+            let geo = self.overview_workspace_geometry(idx);
+            if geo.loc.y + geo.size.h <= 0. || size.h <= geo.loc.y {
+                continue;
+            }
+
+            let loc: Point<i32, Physical> = geo.loc.to_physical_precise_round(output_scale);
+            let ws_size: Size<i32, Physical> = geo.size.to_physical_precise_round(output_scale);
+
+            // Columns stay visible across the whole output width, but not across workspaces.
+            let crop = Rectangle::from_extemities((-i32::MAX / 2, 0), (i32::MAX / 2, ws_size.h));
+
+            let elements = ws.render_elements(renderer);
+            rv.extend(elements.into_iter().filter_map(|elem| {
+                let elem = RescaleRenderElement::from_element(elem, Point::from((0, 0)), zoom);
+                let elem = CropRenderElement::from_element(elem, output_scale, crop)?;
+                let elem = RelocateRenderElement::from_element(elem, loc, Relocate::Relative);
+                Some(MonitorRenderElement::Overview(elem))
+            }));
+        }
+
+        rv
+    }
 }
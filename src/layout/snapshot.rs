@@ -0,0 +1,489 @@
+//! Saving the layout and restoring windows into it after a restart.
+//!
+//! A snapshot records the tiled windows of every workspace along with their column widths and
+//! window heights, and the floating windows along with their positions. When niri starts with a
+//! saved snapshot, every newly mapped window is matched against the windows in the snapshot, and if
+//! it matches, it goes back into its old column or floating position and workspace, recreating
+//! them as needed.
+//!
+//! Restored windows are placed relative to the windows restored before them, so the order of the
+//! columns and workspaces comes out the same regardless of which window happens to map first.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use smithay::utils::Point;
+
+use super::monitor::Monitor;
+use super::workspace::{ColumnWidth, InsertPosition, OutputId, WindowHeight, Workspace};
+use super::{Layout, LayoutElement, MonitorSet};
+
+/// Saved state of the layout.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayoutSnapshot {
+    /// Workspaces that had windows.
+    pub workspaces: Vec<WorkspaceSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    /// Name of the output the workspace was on, if any was connected.
+    pub output: Option<String>,
+    /// Name of the original output of the workspace.
+    pub original_output: Option<String>,
+    /// Name of the workspace, if it was declared in the config.
+    pub name: Option<String>,
+    pub columns: Vec<ColumnSnapshot>,
+    /// Floating windows, topmost first.
+    #[serde(default)]
+    pub floating: Vec<FloatingWindowSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnSnapshot {
+    pub width: ColumnWidth,
+    pub is_full_width: bool,
+    pub windows: Vec<WindowSnapshot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    #[serde(flatten)]
+    pub identity: WindowIdentity,
+    pub height: WindowHeight,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FloatingWindowSnapshot {
+    #[serde(flatten)]
+    pub identity: WindowIdentity,
+    /// Position of the window's tile within the workspace view.
+    pub x: i32,
+    pub y: i32,
+}
+
+/// What a window is recognized by when matching it against a snapshot.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowIdentity {
+    pub app_id: Option<String>,
+    pub title: Option<String>,
+    /// PID of the window's client.
+    ///
+    /// Saved for reference only. Clients that reconnect after a restart are new processes, so
+    /// matching does not use it.
+    pub pid: Option<i32>,
+}
+
+/// Snapshot that windows are being restored from.
+#[derive(Debug)]
+pub(super) struct Restore<W> {
+    snapshot: LayoutSnapshot,
+    /// Positions of the snapshot windows that haven't been matched yet.
+    pending: Vec<Position>,
+    /// Windows restored so far, along with their positions in the snapshot.
+    restored: Vec<(W, Position)>,
+}
+
+/// Position of a window in a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position {
+    workspace: usize,
+    window: WindowPosition,
+}
+
+/// Position of a window within its workspace in a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowPosition {
+    Tiled { column: usize, tile: usize },
+    Floating(usize),
+}
+
+/// Location of a window in the live layout.
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    monitor: usize,
+    workspace: usize,
+    /// Column and tile indices, if the window is tiled.
+    tiled: Option<(usize, usize)>,
+}
+
+impl LayoutSnapshot {
+    /// Loads the snapshot from the file, returning `None` if there's no file.
+    pub fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context("error reading the file"),
+        };
+
+        let snapshot = serde_json::from_slice(&contents).context("error parsing the snapshot")?;
+        Ok(Some(snapshot))
+    }
+
+    /// Saves the snapshot to the file, creating its directory as needed.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("error creating the directory")?;
+        }
+
+        let contents = serde_json::to_vec(self).context("error serializing the snapshot")?;
+
+        // Write to a temporary file first so that a crash mid-write keeps the old snapshot intact.
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, contents).context("error writing the temporary file")?;
+        fs::rename(&temp_path, path).context("error renaming the temporary file")?;
+
+        Ok(())
+    }
+
+    fn identity(&self, pos: Position) -> &WindowIdentity {
+        let ws = &self.workspaces[pos.workspace];
+        match pos.window {
+            WindowPosition::Tiled { column, tile } => &ws.columns[column].windows[tile].identity,
+            WindowPosition::Floating(idx) => &ws.floating[idx].identity,
+        }
+    }
+}
+
+impl<W: LayoutElement> Restore<W> {
+    fn new(snapshot: LayoutSnapshot) -> Self {
+        let mut pending = Vec::new();
+        for (workspace, ws) in snapshot.workspaces.iter().enumerate() {
+            for (column, col) in ws.columns.iter().enumerate() {
+                for tile in 0..col.windows.len() {
+                    pending.push(Position {
+                        workspace,
+                        window: WindowPosition::Tiled { column, tile },
+                    });
+                }
+            }
+
+            for idx in 0..ws.floating.len() {
+                pending.push(Position {
+                    workspace,
+                    window: WindowPosition::Floating(idx),
+                });
+            }
+        }
+
+        Self {
+            snapshot,
+            pending,
+            restored: Vec::new(),
+        }
+    }
+
+    /// Finds and removes the best pending match for the window.
+    ///
+    /// The app ID must match. Among those, a matching title is preferred, and the earliest window
+    /// in the snapshot wins ties.
+    fn take_match(&mut self, identity: &WindowIdentity) -> Option<Position> {
+        if identity.app_id.is_none() {
+            return None;
+        }
+
+        let (idx, _) = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, pos)| {
+                let saved = self.snapshot.identity(*pos);
+                if saved.app_id != identity.app_id {
+                    return None;
+                }
+
+                Some((idx, saved.title == identity.title))
+            })
+            .min_by_key(|(_, same_title)| !same_title)?;
+
+        Some(self.pending.remove(idx))
+    }
+
+    /// Returns where the restored windows currently are in the layout, skipping the ones that
+    /// were closed or moved away.
+    fn locations<'a>(
+        &'a self,
+        monitors: &'a [Monitor<W>],
+    ) -> impl Iterator<Item = (Position, Location)> + 'a {
+        self.restored
+            .iter()
+            .filter_map(|(window, pos)| Some((*pos, find_window(monitors, window)?)))
+    }
+}
+
+impl<W: LayoutElement> Layout<W> {
+    /// Returns the snapshot of the current layout.
+    pub fn snapshot(&self, identify: impl Fn(&W) -> WindowIdentity) -> LayoutSnapshot {
+        let mut workspaces = Vec::new();
+
+        self.with_workspaces(|ws, output, _| {
+            if ws.columns.is_empty() && ws.floating_windows().next().is_none() {
+                return;
+            }
+
+            let columns = ws
+                .columns
+                .iter()
+                .map(|col| {
+                    // Preset indices can go stale when the config changes.
+                    let width = match col.width {
+                        ColumnWidth::Preset(idx) => self.options.preset_widths[idx],
+                        width => width,
+                    };
+
+                    let windows = col
+                        .tiles
+                        .iter()
+                        .zip(col.heights())
+                        .map(|(tile, height)| WindowSnapshot {
+                            identity: identify(tile.window()),
+                            height: *height,
+                        })
+                        .collect();
+
+                    ColumnSnapshot {
+                        width,
+                        is_full_width: col.is_full_width,
+                        windows,
+                    }
+                })
+                .collect();
+
+            let floating = ws
+                .floating_window_positions()
+                .map(|(window, pos)| FloatingWindowSnapshot {
+                    identity: identify(window),
+                    x: pos.x,
+                    y: pos.y,
+                })
+                .collect();
+
+            workspaces.push(WorkspaceSnapshot {
+                output: output.map(|output| output.name()),
+                original_output: ws.original_output.name().map(String::from),
+                name: ws.name.clone(),
+                columns,
+                floating,
+            });
+        });
+
+        LayoutSnapshot { workspaces }
+    }
+
+    /// Starts matching new windows against the snapshot to put them back in their places.
+    pub fn start_restore(&mut self, snapshot: LayoutSnapshot) {
+        self.restore = Some(Restore::new(snapshot));
+    }
+
+    /// Stops matching new windows against the snapshot.
+    pub fn finish_restore(&mut self) {
+        self.restore = None;
+    }
+
+    /// Adds the window at its place in the snapshot being restored.
+    ///
+    /// Returns `false` without adding the window if it did not match any window in the snapshot.
+    pub fn restore_window(&mut self, window: &W, identity: &WindowIdentity, activate: bool) -> bool
+    where
+        W: Clone,
+    {
+        let Some(restore) = &mut self.restore else {
+            return false;
+        };
+        let MonitorSet::Normal {
+            monitors,
+            primary_idx,
+            active_monitor_idx,
+        } = &mut self.monitor_set
+        else {
+            return false;
+        };
+
+        let Some(pos) = restore.take_match(identity) else {
+            return false;
+        };
+
+        let ws_snapshot = &restore.snapshot.workspaces[pos.workspace];
+
+        // Find or create the workspace.
+        let existing = restore
+            .locations(monitors)
+            .find(|(p, _)| p.workspace == pos.workspace)
+            .map(|(_, loc)| (loc.monitor, loc.workspace))
+            .or_else(|| {
+                let name = ws_snapshot.name.as_deref()?;
+                monitors.iter().enumerate().find_map(|(mon_idx, mon)| {
+                    mon.workspaces
+                        .iter()
+                        .position(|ws| ws.name.as_deref() == Some(name))
+                        .map(|ws_idx| (mon_idx, ws_idx))
+                })
+            });
+
+        let (mon_idx, ws_idx, is_new_workspace) = match existing {
+            Some((mon_idx, ws_idx)) => (mon_idx, ws_idx, false),
+            None => {
+                let by_name = |name: &Option<String>| {
+                    let name = name.as_deref()?;
+                    monitors.iter().position(|mon| mon.output.name() == name)
+                };
+                let mon_idx = by_name(&ws_snapshot.output)
+                    .or_else(|| by_name(&ws_snapshot.original_output))
+                    .unwrap_or(*primary_idx);
+
+                let placed = restore
+                    .locations(monitors)
+                    .filter(|(_, loc)| loc.monitor == mon_idx)
+                    .map(|(p, loc)| (p.workspace, loc.workspace));
+                let mon = &monitors[mon_idx];
+                let ws_idx = insert_idx(placed, pos.workspace, mon.workspaces.len() - 1);
+
+                let mon = &mut monitors[mon_idx];
+                let ws = Workspace::new(mon.output.clone(), self.options.clone());
+                mon.workspaces.insert(ws_idx, ws);
+                if ws_idx <= mon.active_workspace_idx {
+                    mon.set_active_workspace_idx(mon.active_workspace_idx + 1);
+                }
+
+                (mon_idx, ws_idx, true)
+            }
+        };
+
+        match pos.window {
+            WindowPosition::Tiled { column, tile } => {
+                let col_snapshot = &ws_snapshot.columns[column];
+
+                // Find or create the column.
+                let same_ws = restore
+                    .locations(monitors)
+                    .filter_map(|(p, loc)| {
+                        if p.workspace != pos.workspace
+                            || loc.monitor != mon_idx
+                            || loc.workspace != ws_idx
+                        {
+                            return None;
+                        }
+
+                        let WindowPosition::Tiled { column, tile } = p.window else {
+                            return None;
+                        };
+                        Some(((column, tile), loc.tiled?))
+                    })
+                    .collect::<Vec<_>>();
+
+                let position = match same_ws.iter().find(|((col, _), _)| *col == column) {
+                    Some(&(_, (live_column, _))) => {
+                        let placed = same_ws
+                            .iter()
+                            .filter(|((col, _), (live_col, _))| {
+                                *col == column && *live_col == live_column
+                            })
+                            .map(|((_, tile), (_, live_tile))| (*tile, *live_tile));
+                        let tile_count = monitors[mon_idx].workspaces[ws_idx].columns[live_column]
+                            .tiles
+                            .len();
+                        InsertPosition::InColumn(live_column, insert_idx(placed, tile, tile_count))
+                    }
+                    None => {
+                        let placed = same_ws
+                            .iter()
+                            .map(|((col, _), (live_col, _))| (*col, *live_col));
+                        let column_count = monitors[mon_idx].workspaces[ws_idx].columns.len();
+                        InsertPosition::NewColumn(insert_idx(placed, column, column_count))
+                    }
+                };
+
+                let mon = &mut monitors[mon_idx];
+                mon.add_window_at(
+                    ws_idx,
+                    window.clone(),
+                    position,
+                    activate,
+                    col_snapshot.width,
+                    col_snapshot.is_full_width,
+                );
+
+                let ws = &mut mon.workspaces[ws_idx];
+                ws.restore_window_height(window, col_snapshot.windows[tile].height);
+            }
+            WindowPosition::Floating(idx) => {
+                let saved = &ws_snapshot.floating[idx];
+
+                let mon = &mut monitors[mon_idx];
+                mon.add_floating_window(ws_idx, window.clone(), activate);
+
+                let ws = &mut mon.workspaces[ws_idx];
+                ws.set_floating_window_position(window, Point::from((saved.x, saved.y)));
+            }
+        }
+
+        let ws = &mut monitors[mon_idx].workspaces[ws_idx];
+
+        // Workspaces whose output is still missing should go back to it once it connects.
+        if is_new_workspace && ws.name.is_none() {
+            if let Some(name) = &ws_snapshot.original_output {
+                ws.original_output = OutputId::from_name(name);
+            }
+        }
+
+        if activate {
+            *active_monitor_idx = mon_idx;
+        }
+
+        restore.restored.push((window.clone(), pos));
+        true
+    }
+}
+
+fn find_window<W: LayoutElement>(monitors: &[Monitor<W>], window: &W) -> Option<Location> {
+    for (mon_idx, mon) in monitors.iter().enumerate() {
+        for (ws_idx, ws) in mon.workspaces.iter().enumerate() {
+            if ws.is_floating(window) {
+                return Some(Location {
+                    monitor: mon_idx,
+                    workspace: ws_idx,
+                    tiled: None,
+                });
+            }
+
+            for (col_idx, col) in ws.columns.iter().enumerate() {
+                if let Some(tile_idx) = col.position(window) {
+                    return Some(Location {
+                        monitor: mon_idx,
+                        workspace: ws_idx,
+                        tiled: Some((col_idx, tile_idx)),
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Returns the live index to insert an item at to keep the snapshot order.
+///
+/// `placed` yields the snapshot and live indices of the items placed so far. The new item goes
+/// right after the last one preceding it in the snapshot, or right before the first one following
+/// it, or at `default` if there are none.
+fn insert_idx(
+    placed: impl Iterator<Item = (usize, usize)>,
+    target: usize,
+    default: usize,
+) -> usize {
+    let mut after = None;
+    let mut before = None;
+
+    for (snapshot_idx, live_idx) in placed {
+        if snapshot_idx < target {
+            after = after.max(Some(live_idx + 1));
+        } else {
+            before = Some(before.map_or(live_idx, |idx: usize| idx.min(live_idx)));
+        }
+    }
+
+    after.or(before).unwrap_or(default)
+}
@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::fmt;
+
+use niri_config::Color;
+use pangocairo::cairo::{self, ImageSurface};
+use pangocairo::pango::{EllipsizeMode, FontDescription};
+use smithay::backend::renderer::element::memory::{
+    MemoryRenderBuffer, MemoryRenderBufferRenderElement,
+};
+use smithay::backend::renderer::element::Kind;
+use smithay::reexports::gbm::Format as Fourcc;
+use smithay::utils::{Logical, Physical, Point, Transform};
+
+use crate::render_helpers::NiriRenderer;
+
+/// Height of the tab bar of a tabbed column, in logical pixels.
+pub const TAB_BAR_HEIGHT: i32 = 24;
+
+const PADDING: i32 = 6;
+const FONT: &str = "sans 12px";
+
+/// Strip of tabs shown above the active tile of a tabbed column.
+#[derive(Default)]
+pub struct TabBar {
+    rendered: RefCell<Option<RenderedTabBar>>,
+}
+
+struct RenderedTabBar {
+    params: TabBarParams,
+    buffer: Option<MemoryRenderBuffer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TabBarParams {
+    titles: Vec<String>,
+    active_idx: usize,
+    width: i32,
+    scale: i32,
+    active_color: Color,
+}
+
+pub type TabBarRenderElement<R> = MemoryRenderBufferRenderElement<R>;
+
+impl fmt::Debug for TabBar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TabBar").finish_non_exhaustive()
+    }
+}
+
+impl TabBar {
+    /// Renders the tab bar, re-rendering the buffer only when something has changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        titles: Vec<String>,
+        active_idx: usize,
+        width: i32,
+        active_color: Color,
+        location: Point<i32, Physical>,
+        scale: i32,
+    ) -> Option<TabBarRenderElement<R>> {
+        let params = TabBarParams {
+            titles,
+            active_idx,
+            width,
+            scale,
+            active_color,
+        };
+
+        let mut rendered = self.rendered.borrow_mut();
+        if rendered.as_ref().map_or(true, |r| r.params != params) {
+            let buffer = render(&params)
+                .map_err(|err| warn!("error rendering tab bar: {err:?}"))
+                .ok();
+            *rendered = Some(RenderedTabBar { params, buffer });
+        }
+        let buffer = rendered.as_ref().unwrap().buffer.as_ref()?;
+
+        MemoryRenderBufferRenderElement::from_buffer(
+            renderer,
+            location.to_f64(),
+            buffer,
+            None,
+            None,
+            None,
+            Kind::Unspecified,
+        )
+        .ok()
+    }
+}
+
+/// Returns the index of the tab under the point, relative to the top-left of the tab bar.
+pub fn tab_under(tab_count: usize, width: i32, point: Point<f64, Logical>) -> Option<usize> {
+    if tab_count == 0
+        || point.x < 0.
+        || point.y < 0.
+        || width as f64 <= point.x
+        || TAB_BAR_HEIGHT as f64 <= point.y
+    {
+        return None;
+    }
+
+    let idx = (point.x * tab_count as f64 / width as f64) as usize;
+    Some(idx.min(tab_count - 1))
+}
+
+fn render(params: &TabBarParams) -> anyhow::Result<MemoryRenderBuffer> {
+    let _span = tracy_client::span!("tab_bar::render");
+
+    let scale = params.scale;
+    let padding = PADDING * scale;
+    let width = params.width.max(1) * scale;
+    let height = TAB_BAR_HEIGHT * scale;
+
+    let mut font = FontDescription::from_string(FONT);
+    font.set_absolute_size((font.size() * scale).into());
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let cr = cairo::Context::new(&surface)?;
+    cr.set_source_rgb(0.1, 0.1, 0.1);
+    cr.paint()?;
+
+    let layout = pangocairo::create_layout(&cr);
+    layout.set_font_description(Some(&font));
+    layout.set_ellipsize(EllipsizeMode::End);
+
+    let count = params.titles.len() as i32;
+    for (idx, title) in params.titles.iter().enumerate() {
+        let is_active = idx == params.active_idx;
+        let follows_active = idx == params.active_idx + 1;
+        let idx = idx as i32;
+        let x = width * idx / count;
+        let tab_width = width * (idx + 1) / count - x;
+
+        if is_active {
+            let c = params.active_color;
+            cr.set_source_rgba(
+                c.r as f64 / 255.,
+                c.g as f64 / 255.,
+                c.b as f64 / 255.,
+                c.a as f64 / 255.,
+            );
+            cr.rectangle(x.into(), 0., tab_width.into(), height.into());
+            cr.fill()?;
+        } else if idx > 0 && !follows_active {
+            // Separator between inactive tabs.
+            cr.set_source_rgb(0.3, 0.3, 0.3);
+            cr.rectangle(
+                x.into(),
+                padding.into(),
+                scale.into(),
+                (height - padding * 2).into(),
+            );
+            cr.fill()?;
+        }
+
+        layout.set_width((tab_width - padding * 2).max(0) * pangocairo::pango::SCALE);
+        layout.set_text(title);
+        let (_, text_height) = layout.pixel_size();
+
+        cr.set_source_rgb(1., 1., 1.);
+        cr.move_to((x + padding).into(), ((height - text_height) / 2).into());
+        pangocairo::show_layout(&cr, &layout);
+    }
+    drop(cr);
+
+    let data = surface.take_data().unwrap();
+    let buffer = MemoryRenderBuffer::from_slice(
+        &data,
+        Fourcc::Argb8888,
+        (width, height),
+        scale,
+        Transform::Normal,
+        None,
+    );
+
+    Ok(buffer)
+}
@@ -1,31 +1,49 @@
 use std::cmp::{max, min};
 use std::iter::{self, zip};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 use niri_config::{CenterFocusedColumn, PresetWidth, SizeChange, Struts};
+use serde::{Deserialize, Serialize};
+use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
+use smithay::backend::renderer::element::Kind;
 use smithay::desktop::space::SpaceElement;
 use smithay::desktop::{layer_map_for_output, Window};
 use smithay::output::Output;
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
 use smithay::utils::{Logical, Point, Rectangle, Scale, Size};
 
+use super::floating::FloatingSpace;
 use super::focus_ring::{FocusRing, FocusRingRenderElement};
+use super::tab_bar::{tab_under, TabBar, TabBarRenderElement, TAB_BAR_HEIGHT};
 use super::tile::{Tile, TileRenderElement};
 use super::{LayoutElement, Options};
 use crate::animation::Animation;
 use crate::niri_render_elements;
 use crate::render_helpers::NiriRenderer;
-use crate::utils::output_size;
+use crate::utils::{output_size, ResizeEdge};
+
+/// Minimum size of the insert hint across its thin side, in logical pixels.
+const INSERT_HINT_MIN_SIZE: i32 = 8;
 
 #[derive(Debug)]
 pub struct Workspace<W: LayoutElement> {
+    /// Unique ID of this workspace.
+    id: WorkspaceId,
+
     /// The original output of this workspace.
     ///
     /// Most of the time this will be the workspace's current output, however, after an output
     /// disconnection, it may remain pointing to the disconnected output.
     pub original_output: OutputId,
 
+    /// Name of this workspace, if it was declared in the config.
+    ///
+    /// Named workspaces are never cleaned up when empty. They also keep their original output
+    /// when windows are added to them on a different output.
+    pub name: Option<String>,
+
     /// Current output of this workspace.
     output: Option<Output>,
 
@@ -47,9 +65,24 @@ pub struct Workspace<W: LayoutElement> {
     /// Index of the currently active column, if any.
     pub active_column_idx: usize,
 
+    /// Floating windows drawn on top of the columns.
+    floating: FloatingSpace<W>,
+
+    /// Whether the focus is on the floating layer rather than on the columns.
+    ///
+    /// This is always `false` when the floating layer is empty, and always `true` when there are
+    /// no columns but the floating layer is not empty.
+    floating_is_active: bool,
+
     /// Focus ring buffer and parameters.
     focus_ring: FocusRing,
 
+    /// Where a window being interactively moved would be inserted, if it is over this workspace.
+    insert_hint: Option<InsertHint>,
+
+    /// Ongoing interactive resize of a window on this workspace.
+    interactive_resize: Option<InteractiveResize<W>>,
+
     /// Offset of the view computed from the active column.
     ///
     /// Any gaps, including left padding from work area left exclusive zone, is handled
@@ -77,15 +110,48 @@ pub struct Workspace<W: LayoutElement> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OutputId(String);
 
+/// Stable identifier of a workspace, unlike its index which changes as workspaces come and go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkspaceId(u64);
+
+/// Position to insert a window at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertPosition {
+    /// In a new column at this index.
+    NewColumn(usize),
+    /// In an existing column at this index, at this tile index.
+    InColumn(usize, usize),
+}
+
+#[derive(Debug)]
+struct InteractiveResize<W> {
+    window: W,
+    edges: ResizeEdge,
+    /// Tile size when the resize started.
+    original_size: Size<i32, Logical>,
+    /// Tile size as of the last window update.
+    ///
+    /// Used to keep the opposite edge in place when resizing by the left or top edge.
+    last_size: Size<i32, Logical>,
+}
+
+#[derive(Debug)]
+struct InsertHint {
+    position: InsertPosition,
+    buffer: SolidColorBuffer,
+    location: Point<i32, Logical>,
+}
+
 niri_render_elements! {
     WorkspaceRenderElement => {
         Tile = TileRenderElement<R>,
         FocusRing = FocusRingRenderElement,
+        TabBar = TabBarRenderElement<R>,
     }
 }
 
 /// Width of a column.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ColumnWidth {
     /// Proportion of the current view width.
     Proportion(f64),
@@ -110,7 +176,7 @@ pub enum ColumnWidth {
 /// This does not preclude the usual set of binds to set or resize a window proportionally. Just,
 /// they are converted to, and stored as fixed height right away, so that once you resize a window
 /// to fit the desired content, it can never become smaller than that when moving between monitors.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum WindowHeight {
     /// Automatically computed height, evenly distributed across the column.
     Auto,
@@ -118,6 +184,16 @@ pub enum WindowHeight {
     Fixed(i32),
 }
 
+/// How the tiles of a column are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColumnDisplay {
+    /// Tiles are stacked vertically, sharing the column height.
+    #[default]
+    Normal,
+    /// Only the active tile is shown at the full column height, with a tab bar above it.
+    Tabbed,
+}
+
 #[derive(Debug)]
 pub struct Column<W: LayoutElement> {
     /// Tiles in this column.
@@ -144,9 +220,24 @@ pub struct Column<W: LayoutElement> {
     /// Whether this column is full-width.
     pub is_full_width: bool,
 
+    /// Whether this column is maximized at the request of its window.
+    ///
+    /// A maximized column takes the full width like a full-width one, and its windows are told
+    /// that they are maximized. The desired width is restored upon unmaximizing.
+    pub is_maximized: bool,
+
     /// Whether this column contains a single full-screened window.
     pub is_fullscreen: bool,
 
+    /// How the tiles of this column are displayed.
+    pub display: ColumnDisplay,
+
+    /// Tab bar shown when this column is tabbed.
+    tab_bar: TabBar,
+
+    /// Animation of the horizontal render offset after this column was moved.
+    move_anim: Option<Animation>,
+
     /// Latest known view size for this column's workspace.
     view_size: Size<i32, Logical>,
 
@@ -157,10 +248,31 @@ pub struct Column<W: LayoutElement> {
     options: Rc<Options>,
 }
 
+impl WorkspaceId {
+    fn next() -> Self {
+        static NEXT_WORKSPACE_ID: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT_WORKSPACE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 impl OutputId {
     pub fn new(output: &Output) -> Self {
         Self(output.name())
     }
+
+    /// Creates an ID for an output that may not be connected right now.
+    pub fn from_name(name: &str) -> Self {
+        Self(name.to_owned())
+    }
+
+    /// Returns the output name, or `None` for workspaces created with no outputs connected.
+    pub fn name(&self) -> Option<&str> {
+        if self.0.is_empty() {
+            None
+        } else {
+            Some(&self.0)
+        }
+    }
 }
 
 impl ColumnWidth {
@@ -187,14 +299,21 @@ impl From<PresetWidth> for ColumnWidth {
 impl<W: LayoutElement> Workspace<W> {
     pub fn new(output: Output, options: Rc<Options>) -> Self {
         let working_area = compute_working_area(&output, options.struts);
+        let view_size = output_size(&output);
         Self {
+            id: WorkspaceId::next(),
             original_output: OutputId::new(&output),
-            view_size: output_size(&output),
+            name: None,
+            view_size,
             working_area,
             output: Some(output),
             columns: vec![],
             active_column_idx: 0,
+            floating: FloatingSpace::new(view_size, working_area, options.clone()),
+            floating_is_active: false,
             focus_ring: FocusRing::new(options.focus_ring),
+            insert_hint: None,
+            interactive_resize: None,
             view_offset: 0,
             view_offset_anim: None,
             activate_prev_column_on_removal: false,
@@ -203,14 +322,22 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn new_no_outputs(options: Rc<Options>) -> Self {
+        let view_size = Size::from((1280, 720));
+        let working_area = Rectangle::from_loc_and_size((0, 0), (1280, 720));
         Self {
+            id: WorkspaceId::next(),
             output: None,
             original_output: OutputId(String::new()),
-            view_size: Size::from((1280, 720)),
-            working_area: Rectangle::from_loc_and_size((0, 0), (1280, 720)),
+            name: None,
+            view_size,
+            working_area,
             columns: vec![],
             active_column_idx: 0,
+            floating: FloatingSpace::new(view_size, working_area, options.clone()),
+            floating_is_active: false,
             focus_ring: FocusRing::new(options.focus_ring),
+            insert_hint: None,
+            interactive_resize: None,
             view_offset: 0,
             view_offset_anim: None,
             activate_prev_column_on_removal: false,
@@ -218,6 +345,31 @@ impl<W: LayoutElement> Workspace<W> {
         }
     }
 
+    /// Creates a named workspace declared in the config.
+    ///
+    /// The original output is the one from the config, if set, so that the workspace moves there
+    /// once it is connected.
+    pub fn new_with_config(
+        output: Option<Output>,
+        config: &niri_config::Workspace,
+        options: Rc<Options>,
+    ) -> Self {
+        let original_output = config
+            .open_on_output
+            .clone()
+            .map(OutputId)
+            .or_else(|| output.as_ref().map(OutputId::new))
+            .unwrap_or(OutputId(String::new()));
+
+        let mut ws = match output {
+            Some(output) => Self::new(output, options),
+            None => Self::new_no_outputs(options),
+        };
+        ws.original_output = original_output;
+        ws.name = Some(config.name.clone());
+        ws
+    }
+
     pub fn advance_animations(&mut self, current_time: Duration, is_active: bool) {
         match &mut self.view_offset_anim {
             Some(anim) => {
@@ -233,33 +385,55 @@ impl<W: LayoutElement> Workspace<W> {
         let view_pos = self.view_pos();
 
         for (col_idx, col) in self.columns.iter_mut().enumerate() {
+            col.advance_animations(current_time);
+
             for (tile_idx, tile) in col.tiles.iter_mut().enumerate() {
                 let is_active = is_active
+                    && !self.floating_is_active
                     && col_idx == self.active_column_idx
                     && tile_idx == col.active_tile_idx;
                 tile.advance_animations(current_time, is_active);
             }
         }
 
+        self.floating
+            .advance_animations(current_time, is_active && self.floating_is_active);
+
         // This shall one day become a proper animation.
-        if !self.columns.is_empty() {
+        if self.floating_is_active {
+            if let Some((tile_pos, size, has_ssd)) = self.floating.active_tile_geometry() {
+                self.focus_ring.update(tile_pos, size, has_ssd);
+                self.focus_ring.set_active(is_active);
+            }
+        } else if !self.columns.is_empty() {
             let col = &self.columns[self.active_column_idx];
             let active_tile = &col.tiles[col.active_tile_idx];
             let size = active_tile.tile_size();
             let has_ssd = active_tile.has_ssd();
 
             let tile_pos = Point::from((
-                self.column_x(self.active_column_idx) - view_pos,
+                self.column_x(self.active_column_idx) - view_pos + col.render_offset(),
                 col.tile_y(col.active_tile_idx),
             ));
 
             self.focus_ring.update(tile_pos, size, has_ssd);
             self.focus_ring.set_active(is_active);
         }
+
+        if let Some(position) = self.insert_hint.as_ref().map(|hint| hint.position) {
+            let area = self.insert_hint_area(position);
+            let mut color: [f32; 4] = self.options.focus_ring.active_color.into();
+            // Premultiplied half-transparent version of the focus ring color.
+            color.iter_mut().for_each(|c| *c *= 0.5);
+
+            let hint = self.insert_hint.as_mut().unwrap();
+            hint.buffer.update(area.size, color);
+            hint.location = area.loc;
+        }
     }
 
     pub fn are_animations_ongoing(&self) -> bool {
-        self.view_offset_anim.is_some()
+        self.view_offset_anim.is_some() || self.columns.iter().any(|col| col.move_anim.is_some())
     }
 
     pub fn update_config(&mut self, options: Rc<Options>) {
@@ -270,6 +444,8 @@ impl<W: LayoutElement> Workspace<W> {
             column.update_config(options.clone());
         }
 
+        self.floating.update_config(options.clone());
+
         self.options = options;
     }
 
@@ -278,6 +454,44 @@ impl<W: LayoutElement> Workspace<W> {
             .iter()
             .flat_map(|col| col.tiles.iter())
             .map(Tile::window)
+            .chain(self.floating.windows())
+    }
+
+    pub fn floating_windows(&self) -> impl Iterator<Item = &W> + '_ {
+        self.floating.windows()
+    }
+
+    /// Returns the floating windows with their positions within the view, topmost first.
+    pub fn floating_window_positions(
+        &self,
+    ) -> impl Iterator<Item = (&W, Point<i32, Logical>)> + '_ {
+        self.floating
+            .tiles_with_positions()
+            .map(|(tile, pos)| (tile.window(), pos))
+    }
+
+    /// Returns whether the focus is on the floating layer.
+    pub fn floating_is_active(&self) -> bool {
+        self.floating_is_active
+    }
+
+    /// Returns whether the window is in the floating layer.
+    pub fn is_floating(&self, window: &W) -> bool {
+        self.floating.has_window(window)
+    }
+
+    /// Returns the active window, whether tiled or floating.
+    pub fn active_window(&self) -> Option<&W> {
+        if self.floating_is_active {
+            return self.floating.active_window();
+        }
+
+        if self.columns.is_empty() {
+            return None;
+        }
+
+        let col = &self.columns[self.active_column_idx];
+        Some(col.tiles[col.active_tile_idx].window())
     }
 
     pub fn set_output(&mut self, output: Option<Output>) {
@@ -325,6 +539,9 @@ impl<W: LayoutElement> Workspace<W> {
         for col in &mut self.columns {
             col.set_view_size(self.view_size, self.working_area);
         }
+
+        self.floating
+            .set_view_size(self.view_size, self.working_area);
     }
 
     pub fn view_size(&self) -> Size<i32, Logical> {
@@ -352,8 +569,11 @@ impl<W: LayoutElement> Workspace<W> {
         ))
     }
 
-    pub fn new_window_size(&self) -> Size<i32, Logical> {
-        let width = if let Some(width) = self.options.default_width {
+    /// Computes the size to configure a new window with.
+    ///
+    /// `width` of `None` means that the window picks its own width.
+    pub fn new_window_size(&self, width: Option<ColumnWidth>) -> Size<i32, Logical> {
+        let width = if let Some(width) = width {
             let mut width = width.resolve(&self.options, self.working_area.size.w);
             if !self.options.border.off {
                 width -= self.options.border.width as i32 * 2;
@@ -371,8 +591,8 @@ impl<W: LayoutElement> Workspace<W> {
         Size::from((width, max(height, 1)))
     }
 
-    pub fn configure_new_window(&self, window: &Window) {
-        let size = self.new_window_size();
+    pub fn configure_new_window(&self, window: &Window, width: Option<ColumnWidth>) {
+        let size = self.new_window_size(width);
         let bounds = self.toplevel_bounds();
 
         if let Some(output) = self.output.as_ref() {
@@ -410,6 +630,31 @@ impl<W: LayoutElement> Workspace<W> {
         new_offset - self.working_area.loc.x
     }
 
+    /// Returns how much activating the window would scroll the view, as a fraction of the working
+    /// area width.
+    pub fn scroll_amount_to_activate(&self, window: &W) -> f64 {
+        if self.floating.has_window(window) {
+            return 0.;
+        }
+
+        let column_idx = self
+            .columns
+            .iter()
+            .position(|col| col.contains(window))
+            .unwrap();
+
+        if self.active_column_idx == column_idx {
+            return 0.;
+        }
+
+        let current_x = self.view_pos();
+        let new_view_offset = self.compute_new_view_offset_for_column(current_x, column_idx);
+        let from_view_offset = current_x - self.column_x(column_idx);
+
+        let width = max(self.working_area.size.w, 1);
+        (from_view_offset - new_view_offset).abs() as f64 / width as f64
+    }
+
     fn animate_view_offset(&mut self, current_x: i32, idx: usize, new_view_offset: i32) {
         let new_col_x = self.column_x(idx);
         let from_view_offset = current_x - new_col_x;
@@ -430,10 +675,17 @@ impl<W: LayoutElement> Workspace<W> {
             return;
         }
 
+        // Keep the momentum of an ongoing view movement.
+        let velocity = self
+            .view_offset_anim
+            .as_ref()
+            .map_or(0., Animation::velocity);
+
         self.view_offset_anim = Some(Animation::new(
             self.view_offset as f64,
             new_view_offset as f64,
-            Duration::from_millis(250),
+            velocity,
+            self.options.animations.view_movement,
         ));
     }
 
@@ -516,10 +768,19 @@ impl<W: LayoutElement> Workspace<W> {
         self.activate_prev_column_on_removal = false;
     }
 
+    pub fn id(&self) -> WorkspaceId {
+        self.id
+    }
+
     pub fn has_windows(&self) -> bool {
         self.windows().next().is_some()
     }
 
+    /// Returns whether this workspace should be kept around even when it's not active.
+    pub fn has_windows_or_name(&self) -> bool {
+        self.has_windows() || self.name.is_some()
+    }
+
     pub fn has_window(&self, window: &W) -> bool {
         self.windows().any(|win| win == window)
     }
@@ -546,16 +807,54 @@ impl<W: LayoutElement> Workspace<W> {
         width: ColumnWidth,
         is_full_width: bool,
     ) {
-        self.enter_output_for_window(&window);
-
-        let was_empty = self.columns.is_empty();
-
         let idx = if self.columns.is_empty() {
             0
         } else {
             self.active_column_idx + 1
         };
 
+        self.add_window_at(
+            window,
+            InsertPosition::NewColumn(idx),
+            activate,
+            width,
+            is_full_width,
+        );
+    }
+
+    /// Adds a window at a specific position.
+    ///
+    /// `width` and `is_full_width` only apply when adding a new column.
+    pub fn add_window_at(
+        &mut self,
+        window: W,
+        position: InsertPosition,
+        activate: bool,
+        width: ColumnWidth,
+        is_full_width: bool,
+    ) {
+        self.enter_output_for_window(&window);
+
+        let idx = match position {
+            InsertPosition::NewColumn(idx) => min(idx, self.columns.len()),
+            InsertPosition::InColumn(col_idx, tile_idx) => {
+                let column = &mut self.columns[col_idx];
+                let tile_idx = min(tile_idx, column.tiles.len());
+                column.insert_window(tile_idx, window);
+
+                if activate {
+                    column.active_tile_idx = tile_idx;
+                    self.activate_column(col_idx);
+                    self.floating_is_active = false;
+                }
+
+                return;
+            }
+        };
+
+        let was_empty = self.columns.is_empty();
+        let is_right_of_active = !was_empty && idx == self.active_column_idx + 1;
+
         let column = Column::new(
             window,
             self.view_size,
@@ -566,6 +865,13 @@ impl<W: LayoutElement> Workspace<W> {
         );
         let width = column.width();
         self.columns.insert(idx, column);
+        self.animate_columns_shift(idx + 1, -(width + self.options.gaps));
+
+        if !was_empty && idx <= self.active_column_idx {
+            // The column went to the left of the active one; keep the view in place.
+            self.active_column_idx += 1;
+            self.view_offset -= width + self.options.gaps;
+        }
 
         if activate {
             // If this is the first window on an empty workspace, skip the animation from whatever
@@ -583,7 +889,8 @@ impl<W: LayoutElement> Workspace<W> {
             }
 
             self.activate_column(idx);
-            self.activate_prev_column_on_removal = true;
+            self.activate_prev_column_on_removal = was_empty || is_right_of_active;
+            self.floating_is_active = false;
         }
     }
 
@@ -600,9 +907,12 @@ impl<W: LayoutElement> Workspace<W> {
             self.active_column_idx + 1
         };
 
+        // The move animation offset is relative to the previous workspace.
+        column.move_anim = None;
         column.set_view_size(self.view_size, self.working_area);
         let width = column.width();
         self.columns.insert(idx, column);
+        self.animate_columns_shift(idx + 1, -(width + self.options.gaps));
 
         if activate {
             // If this is the first window on an empty workspace, skip the animation from whatever
@@ -621,11 +931,22 @@ impl<W: LayoutElement> Workspace<W> {
 
             self.activate_column(idx);
             self.activate_prev_column_on_removal = true;
+            self.floating_is_active = false;
+        }
+    }
+
+    pub fn add_floating_window(&mut self, window: W, activate: bool) {
+        self.enter_output_for_window(&window);
+        self.floating.add_window(window, activate);
+
+        if activate || self.columns.is_empty() {
+            self.floating_is_active = true;
         }
     }
 
     pub fn remove_window_by_idx(&mut self, column_idx: usize, window_idx: usize) -> W {
         let column = &mut self.columns[column_idx];
+        let column_width = column.width();
         let window = column.tiles.remove(window_idx).into_window();
         column.heights.remove(window_idx);
 
@@ -644,7 +965,9 @@ impl<W: LayoutElement> Workspace<W> {
             // position, which can include the column we're removing here. This leads to unwanted
             // view jumps.
             self.columns.remove(column_idx);
+            self.animate_columns_shift(column_idx, column_width + self.options.gaps);
             if self.columns.is_empty() {
+                self.floating_is_active = !self.floating.is_empty();
                 return window;
             }
 
@@ -670,6 +993,7 @@ impl<W: LayoutElement> Workspace<W> {
 
     pub fn remove_column_by_idx(&mut self, column_idx: usize) -> Column<W> {
         let column = self.columns.remove(column_idx);
+        self.animate_columns_shift(column_idx, column.width() + self.options.gaps);
 
         if let Some(output) = &self.output {
             for tile in &column.tiles {
@@ -687,6 +1011,7 @@ impl<W: LayoutElement> Workspace<W> {
         // position, which can include the column we're removing here. This leads to unwanted
         // view jumps.
         if self.columns.is_empty() {
+            self.floating_is_active = !self.floating.is_empty();
             return column;
         }
 
@@ -704,7 +1029,13 @@ impl<W: LayoutElement> Workspace<W> {
         column
     }
 
-    pub fn remove_window(&mut self, window: &W) {
+    pub fn remove_window(&mut self, window: &W) -> W {
+        if self.floating.has_window(window) {
+            let window = self.floating.remove_window(window);
+            self.leave_floating_window(&window);
+            return window;
+        }
+
         let column_idx = self
             .columns
             .iter()
@@ -713,10 +1044,56 @@ impl<W: LayoutElement> Workspace<W> {
         let column = &self.columns[column_idx];
 
         let window_idx = column.position(window).unwrap();
-        self.remove_window_by_idx(column_idx, window_idx);
+        self.remove_window_by_idx(column_idx, window_idx)
+    }
+
+    /// Removes the active floating window, if the floating layer is focused.
+    pub fn remove_active_floating_window(&mut self) -> Option<W> {
+        if !self.floating_is_active {
+            return None;
+        }
+
+        let window = self.floating.remove_active_window()?;
+        self.leave_floating_window(&window);
+        Some(window)
+    }
+
+    fn leave_floating_window(&mut self, window: &W) {
+        if let Some(output) = &self.output {
+            window.output_leave(output);
+        }
+
+        if self.floating.is_empty() {
+            self.floating_is_active = false;
+        }
     }
 
     pub fn update_window(&mut self, window: &W) {
+        let resize = self
+            .interactive_resize
+            .as_mut()
+            .filter(|resize| &resize.window == window);
+
+        if self.floating.has_window(window) {
+            self.floating.update_window(window);
+
+            if let Some(resize) = resize {
+                // Keep the opposite edges in place.
+                let size = self.floating.tile(window).unwrap().tile_size();
+                let mut delta = Point::from((0, 0));
+                if resize.edges.contains(ResizeEdge::LEFT) {
+                    delta.x = resize.last_size.w - size.w;
+                }
+                if resize.edges.contains(ResizeEdge::TOP) {
+                    delta.y = resize.last_size.h - size.h;
+                }
+                resize.last_size = size;
+                self.floating.move_window_by(window, delta);
+            }
+
+            return;
+        }
+
         let (idx, column) = self
             .columns
             .iter_mut()
@@ -726,6 +1103,15 @@ impl<W: LayoutElement> Workspace<W> {
         column.update_window(window);
         column.update_tile_sizes();
 
+        if let Some(resize) = resize {
+            // Keep the right edge of the column in place when resizing by the left edge.
+            let width = column.width();
+            if resize.edges.contains(ResizeEdge::LEFT) && idx == self.active_column_idx {
+                self.view_offset += width - resize.last_size.w;
+            }
+            resize.last_size.w = width;
+        }
+
         if idx == self.active_column_idx {
             // We might need to move the view to ensure the resized window is still visible.
             let current_x = self.view_pos();
@@ -741,6 +1127,12 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn activate_window(&mut self, window: &W) {
+        if self.floating.has_window(window) {
+            self.floating.activate_window(window);
+            self.floating_is_active = true;
+            return;
+        }
+
         let column_idx = self
             .columns
             .iter()
@@ -750,6 +1142,261 @@ impl<W: LayoutElement> Workspace<W> {
 
         column.activate_window(window);
         self.activate_column(column_idx);
+        self.floating_is_active = false;
+    }
+
+    /// Returns the Y position of the window's visual geometry within the view.
+    pub fn window_y(&self, window: &W) -> Option<i32> {
+        if let Some(y) = self.floating.window_y(window) {
+            return Some(y);
+        }
+
+        self.columns
+            .iter()
+            .find_map(|col| col.position(window).map(|tile_idx| col.window_y(tile_idx)))
+    }
+
+    /// Returns where a window dropped at this position would be inserted.
+    pub fn insert_position(&self, pos: Point<f64, Logical>) -> InsertPosition {
+        if self.columns.is_empty() {
+            return InsertPosition::NewColumn(0);
+        }
+
+        // Convert to the column coordinates.
+        let x = pos.x + self.view_pos() as f64;
+
+        let mut col_x = 0;
+        for (col_idx, col) in self.columns.iter().enumerate() {
+            let width = col.width();
+
+            // Edges of the column make a new column, and the middle inserts into the column.
+            if x < (col_x + width / 4) as f64 {
+                return InsertPosition::NewColumn(col_idx);
+            }
+
+            if x < (col_x + width * 3 / 4) as f64 {
+                let tile_idx = zip(&col.tiles, col.tile_ys())
+                    .position(|(tile, y)| pos.y < (y + tile.tile_size().h / 2) as f64)
+                    .unwrap_or(col.tiles.len());
+                return InsertPosition::InColumn(col_idx, tile_idx);
+            }
+
+            col_x += width + self.options.gaps;
+        }
+
+        InsertPosition::NewColumn(self.columns.len())
+    }
+
+    pub fn set_insert_hint(&mut self, position: Option<InsertPosition>) {
+        let Some(position) = position else {
+            self.insert_hint = None;
+            return;
+        };
+
+        match &mut self.insert_hint {
+            Some(hint) => hint.position = position,
+            None => {
+                self.insert_hint = Some(InsertHint {
+                    position,
+                    buffer: SolidColorBuffer::default(),
+                    location: Point::default(),
+                })
+            }
+        }
+    }
+
+    /// Computes the area of the insert hint, in view coordinates.
+    fn insert_hint_area(&self, position: InsertPosition) -> Rectangle<i32, Logical> {
+        let gaps = self.options.gaps;
+        let size = max(gaps, INSERT_HINT_MIN_SIZE);
+        let view_pos = self.view_pos();
+
+        // The columns could have changed since the hint was set.
+        let position = match position {
+            InsertPosition::InColumn(col_idx, tile_idx) if col_idx < self.columns.len() => {
+                let tile_idx = min(tile_idx, self.columns[col_idx].tiles.len());
+                InsertPosition::InColumn(col_idx, tile_idx)
+            }
+            InsertPosition::InColumn(..) => InsertPosition::NewColumn(self.columns.len()),
+            InsertPosition::NewColumn(idx) => {
+                InsertPosition::NewColumn(min(idx, self.columns.len()))
+            }
+        };
+
+        match position {
+            InsertPosition::NewColumn(idx) => {
+                let x = if self.columns.is_empty() {
+                    self.working_area.loc.x + gaps / 2
+                } else {
+                    self.column_x(idx) - gaps / 2 - view_pos
+                };
+
+                Rectangle::from_loc_and_size(
+                    (x - size / 2, self.working_area.loc.y + gaps),
+                    (size, max(self.working_area.size.h - gaps * 2, 1)),
+                )
+            }
+            InsertPosition::InColumn(col_idx, tile_idx) => {
+                let col = &self.columns[col_idx];
+                let x = self.column_x(col_idx) - view_pos;
+
+                let y = if tile_idx < col.tiles.len() {
+                    col.tile_y(tile_idx) - gaps / 2
+                } else {
+                    let last = col.tiles.len() - 1;
+                    col.tile_y(last) + col.tiles[last].tile_size().h + gaps / 2
+                };
+
+                Rectangle::from_loc_and_size((x, y - size / 2), (col.width(), size))
+            }
+        }
+    }
+
+    /// Moves a tiled window to a different position within this workspace and activates it.
+    pub fn move_window_to(&mut self, window: &W, position: InsertPosition) {
+        let (col_idx, tile_idx) = self
+            .columns
+            .iter()
+            .enumerate()
+            .find_map(|(col_idx, col)| col.position(window).map(|tile_idx| (col_idx, tile_idx)))
+            .unwrap();
+
+        let column = &self.columns[col_idx];
+        let is_alone = column.tiles.len() == 1;
+        let width = column.width;
+        let is_full_width = column.is_full_width;
+
+        // Account for the removal of the window, and do nothing if it stays in place.
+        let position = match position {
+            InsertPosition::NewColumn(idx) => {
+                if is_alone && (idx == col_idx || idx == col_idx + 1) {
+                    self.activate_window(window);
+                    return;
+                }
+
+                if is_alone && idx > col_idx {
+                    InsertPosition::NewColumn(idx - 1)
+                } else {
+                    position
+                }
+            }
+            InsertPosition::InColumn(idx, new_tile_idx) => {
+                if idx == col_idx {
+                    if new_tile_idx == tile_idx || new_tile_idx == tile_idx + 1 {
+                        self.activate_window(window);
+                        return;
+                    }
+
+                    let new_tile_idx = if new_tile_idx > tile_idx {
+                        new_tile_idx - 1
+                    } else {
+                        new_tile_idx
+                    };
+                    InsertPosition::InColumn(idx, new_tile_idx)
+                } else if is_alone && idx > col_idx {
+                    InsertPosition::InColumn(idx - 1, new_tile_idx)
+                } else {
+                    position
+                }
+            }
+        };
+
+        let window = self.remove_window_by_idx(col_idx, tile_idx);
+        self.add_window_at(window, position, true, width, is_full_width);
+    }
+
+    /// Moves a floating window by the given amount of logical pixels.
+    pub fn move_floating_window_by(&mut self, window: &W, delta: Point<i32, Logical>) {
+        self.floating.move_window_by(window, delta);
+    }
+
+    /// Moves a floating window to the given position within the view.
+    pub fn set_floating_window_position(&mut self, window: &W, pos: Point<i32, Logical>) {
+        self.floating.set_window_position(window, pos);
+    }
+
+    /// Starts an interactive resize of the window by the given edges.
+    ///
+    /// Returns `false` if the window cannot be resized, for example, because it is fullscreen.
+    pub fn interactive_resize_begin(&mut self, window: W, edges: ResizeEdge) -> bool {
+        let size = if let Some(tile) = self.floating.tile(&window) {
+            tile.tile_size()
+        } else {
+            let col = self
+                .columns
+                .iter()
+                .find(|col| col.contains(&window))
+                .unwrap();
+            if col.is_fullscreen {
+                return false;
+            }
+
+            let tile = &col.tiles[col.position(&window).unwrap()];
+            Size::from((col.width(), tile.tile_size().h))
+        };
+
+        self.activate_window(&window);
+
+        self.interactive_resize = Some(InteractiveResize {
+            window,
+            edges,
+            original_size: size,
+            last_size: size,
+        });
+
+        true
+    }
+
+    /// Updates the interactive resize of the window by the pointer movement since its start.
+    ///
+    /// Returns `false` if the window isn't being resized on this workspace.
+    pub fn interactive_resize_update(&mut self, window: &W, delta: Point<f64, Logical>) -> bool {
+        let Some(resize) = &self.interactive_resize else {
+            return false;
+        };
+        if &resize.window != window {
+            return false;
+        }
+
+        let delta: Point<i32, Logical> = delta.to_i32_round();
+        let edges = resize.edges;
+        let mut size = resize.original_size;
+        if edges.contains(ResizeEdge::LEFT) {
+            size.w -= delta.x;
+        } else if edges.contains(ResizeEdge::RIGHT) {
+            size.w += delta.x;
+        }
+        if edges.contains(ResizeEdge::TOP) {
+            size.h -= delta.y;
+        } else if edges.contains(ResizeEdge::BOTTOM) {
+            size.h += delta.y;
+        }
+
+        if self.floating.has_window(window) {
+            self.floating.request_tile_size(window, size);
+            return true;
+        }
+
+        let Some(col) = self.columns.iter_mut().find(|col| col.contains(window)) else {
+            return false;
+        };
+        let tile_idx = col.position(window).unwrap();
+
+        let width = edges.intersects(ResizeEdge::LEFT_RIGHT).then_some(size.w);
+        let height = edges.intersects(ResizeEdge::TOP_BOTTOM).then_some(size.h);
+        col.set_interactive_size(tile_idx, width, height);
+
+        true
+    }
+
+    pub fn interactive_resize_end(&mut self, window: &W) {
+        if self
+            .interactive_resize
+            .as_ref()
+            .is_some_and(|resize| &resize.window == window)
+        {
+            self.interactive_resize = None;
+        }
     }
 
     #[cfg(test)]
@@ -764,13 +1411,47 @@ impl<W: LayoutElement> Workspace<W> {
                 column.verify_invariants();
             }
         }
+
+        self.floating.verify_invariants();
+
+        if self.floating_is_active {
+            assert!(
+                !self.floating.is_empty(),
+                "floating layer cannot be active when empty"
+            );
+        } else if self.columns.is_empty() {
+            assert!(
+                self.floating.is_empty(),
+                "floating layer must be active when there are no columns"
+            );
+        }
+    }
+
+    /// Moves the focus from the floating layer back to the columns.
+    ///
+    /// Returns `true` if the focus was moved.
+    fn leave_floating_focus(&mut self) -> bool {
+        if !self.floating_is_active || self.columns.is_empty() {
+            return false;
+        }
+
+        self.floating_is_active = false;
+        true
     }
 
     pub fn focus_left(&mut self) {
+        if self.leave_floating_focus() {
+            return;
+        }
+
         self.activate_column(self.active_column_idx.saturating_sub(1));
     }
 
     pub fn focus_right(&mut self) {
+        if self.leave_floating_focus() {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -779,10 +1460,18 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn focus_column_first(&mut self) {
+        if self.leave_floating_focus() {
+            return;
+        }
+
         self.activate_column(0);
     }
 
     pub fn focus_column_last(&mut self) {
+        if self.leave_floating_focus() {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -791,6 +1480,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn focus_down(&mut self) {
+        if self.leave_floating_focus() {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -799,6 +1492,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn focus_up(&mut self) {
+        if self.leave_floating_focus() {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -813,6 +1510,13 @@ impl<W: LayoutElement> Workspace<W> {
 
         let current_x = self.view_pos();
 
+        // Remember where every column was to animate them into their new positions.
+        let mut old_xs: Vec<_> = (0..self.columns.len())
+            .map(|idx| self.column_x(idx))
+            .collect();
+        let old_x = old_xs.remove(self.active_column_idx);
+        old_xs.insert(new_idx, old_x);
+
         let column = self.columns.remove(self.active_column_idx);
         self.columns.insert(new_idx, column);
 
@@ -820,15 +1524,39 @@ impl<W: LayoutElement> Workspace<W> {
         self.view_offset =
             self.compute_new_view_offset_for_column(current_x, self.active_column_idx);
 
-        self.activate_column(new_idx);
+        self.activate_column(new_idx);
+
+        for (idx, old_x) in old_xs.into_iter().enumerate() {
+            let offset = old_x - self.column_x(idx);
+            if offset != 0 {
+                self.columns[idx].animate_move_from(offset);
+            }
+        }
+    }
+
+    /// Animates the columns starting at `from_idx` moving in from the given offset.
+    ///
+    /// Used when a column is inserted or removed and the ones after it shift over.
+    fn animate_columns_shift(&mut self, from_idx: usize, from_x_offset: i32) {
+        for column in self.columns.iter_mut().skip(from_idx) {
+            column.animate_move_from(from_x_offset);
+        }
     }
 
     pub fn move_left(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         let new_idx = self.active_column_idx.saturating_sub(1);
         self.move_column_to(new_idx);
     }
 
     pub fn move_right(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -838,10 +1566,18 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn move_column_to_first(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         self.move_column_to(0);
     }
 
     pub fn move_column_to_last(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -851,6 +1587,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn move_down(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -859,6 +1599,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn move_up(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -867,6 +1611,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn consume_or_expel_window_left(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -900,6 +1648,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn consume_or_expel_window_right(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -931,6 +1683,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn consume_into_column(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.len() < 2 {
             return;
         }
@@ -948,6 +1704,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn expel_from_column(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -966,6 +1726,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn center_column(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         let center_x = self.view_pos();
         self.animate_view_offset_to_column_centered(center_x, self.active_column_idx);
     }
@@ -981,7 +1745,7 @@ impl<W: LayoutElement> Workspace<W> {
         let col = &self.columns[self.active_column_idx];
         let tile = &col.tiles[col.active_tile_idx];
         let tile_pos = Point::from((
-            self.column_x(self.active_column_idx) - view_pos,
+            self.column_x(self.active_column_idx) - view_pos + col.render_offset(),
             col.tile_y(col.active_tile_idx),
         ));
         let first = iter::once((tile, tile_pos));
@@ -1005,7 +1769,12 @@ impl<W: LayoutElement> Workspace<W> {
                             return None;
                         }
 
-                        let tile_pos = Point::from((x, y));
+                        if col.is_tabbed() && tile_idx != col.active_tile_idx {
+                            // Only the active tile of a tabbed column is visible.
+                            return None;
+                        }
+
+                        let tile_pos = Point::from((x + col.render_offset(), y));
                         Some((tile, tile_pos))
                     },
                 )
@@ -1013,14 +1782,61 @@ impl<W: LayoutElement> Workspace<W> {
         first.chain(rest)
     }
 
+    /// Returns the geometry of the active tile relative to the view, once the view stops moving.
+    pub fn active_tile_visual_rectangle(&self) -> Option<Rectangle<i32, Logical>> {
+        if self.floating_is_active {
+            let (tile_pos, size, _) = self.floating.active_tile_geometry()?;
+            return Some(Rectangle::from_loc_and_size(tile_pos, size));
+        }
+
+        let col = self.columns.get(self.active_column_idx)?;
+        let tile = &col.tiles[col.active_tile_idx];
+
+        let view_offset = self
+            .view_offset_anim
+            .as_ref()
+            .map_or(self.view_offset, |anim| anim.to().round() as i32);
+        let tile_pos = Point::from((-view_offset, col.tile_y(col.active_tile_idx)));
+
+        Some(Rectangle::from_loc_and_size(tile_pos, tile.tile_size()))
+    }
+
+    /// Returns tabbed columns along with their index and X position relative to the view.
+    fn columns_with_tab_bars(&self) -> impl Iterator<Item = (usize, &'_ Column<W>, i32)> {
+        let mut x = -self.view_pos();
+        self.columns
+            .iter()
+            .enumerate()
+            .map(move |(col_idx, col)| {
+                let rv = (col_idx, col, x + col.render_offset());
+                x += col.width() + self.options.gaps;
+                rv
+            })
+            .filter(|(_, col, _)| col.is_tabbed())
+    }
+
     pub fn window_under(
         &self,
         pos: Point<f64, Logical>,
     ) -> Option<(&W, Option<Point<i32, Logical>>)> {
+        // Floating windows are drawn on top.
+        if let Some(rv) = self.floating.window_under(pos) {
+            return Some(rv);
+        }
+
         if self.columns.is_empty() {
             return None;
         }
 
+        // Clicking a tab activates its window.
+        for (_, col, col_x) in self.columns_with_tab_bars() {
+            let area = col.tab_bar_area().unwrap();
+            let pos_within_tab_bar = pos - Point::from((col_x, area.loc.y)).to_f64();
+            if let Some(idx) = tab_under(col.tiles.len(), area.size.w, pos_within_tab_bar) {
+                return Some((col.tiles[idx].window(), None));
+            }
+        }
+
         self.tiles_in_render_order().find_map(|(tile, tile_pos)| {
             let pos_within_tile = pos - tile_pos.to_f64();
 
@@ -1036,6 +1852,10 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn toggle_width(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -1043,7 +1863,23 @@ impl<W: LayoutElement> Workspace<W> {
         self.columns[self.active_column_idx].toggle_width();
     }
 
+    pub fn toggle_column_tabbed_display(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
+        if self.columns.is_empty() {
+            return;
+        }
+
+        self.columns[self.active_column_idx].toggle_tabbed_display();
+    }
+
     pub fn toggle_full_width(&mut self) {
+        if self.floating_is_active {
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -1052,6 +1888,11 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn set_column_width(&mut self, change: SizeChange) {
+        if self.floating_is_active {
+            self.floating.set_window_width(change);
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -1060,6 +1901,11 @@ impl<W: LayoutElement> Workspace<W> {
     }
 
     pub fn set_window_height(&mut self, change: SizeChange) {
+        if self.floating_is_active {
+            self.floating.set_window_height(change);
+            return;
+        }
+
         if self.columns.is_empty() {
             return;
         }
@@ -1067,49 +1913,39 @@ impl<W: LayoutElement> Workspace<W> {
         self.columns[self.active_column_idx].set_window_height(change);
     }
 
-    pub fn set_fullscreen(&mut self, window: &W, is_fullscreen: bool) {
-        let (mut col_idx, tile_idx) = self
-            .columns
-            .iter()
-            .enumerate()
-            .find_map(|(col_idx, col)| col.position(window).map(|tile_idx| (col_idx, tile_idx)))
-            .unwrap();
+    /// Sets the stored height of a tiled window, such as one restored from a layout snapshot.
+    pub fn restore_window_height(&mut self, window: &W, height: WindowHeight) {
+        let Some(col) = self.columns.iter_mut().find(|col| col.contains(window)) else {
+            return;
+        };
 
-        let mut col = &mut self.columns[col_idx];
-
-        if is_fullscreen && col.tiles.len() > 1 {
-            // This wasn't the only window in its column; extract it into a separate column.
-            let target_window_was_focused =
-                self.active_column_idx == col_idx && col.active_tile_idx == tile_idx;
-            let window = col.tiles.remove(tile_idx).into_window();
-            col.heights.remove(tile_idx);
-            col.active_tile_idx = min(col.active_tile_idx, col.tiles.len() - 1);
-            col.update_tile_sizes();
-            let width = col.width;
-            let is_full_width = col.is_full_width;
-
-            col_idx += 1;
-            self.columns.insert(
-                col_idx,
-                Column::new(
-                    window,
-                    self.view_size,
-                    self.working_area,
-                    self.options.clone(),
-                    width,
-                    is_full_width,
-                ),
-            );
-            if self.active_column_idx >= col_idx || target_window_was_focused {
-                self.active_column_idx += 1;
+        let idx = col.position(window).unwrap();
+        col.heights[idx] = match height {
+            WindowHeight::Auto => WindowHeight::Auto,
+            WindowHeight::Fixed(height) => WindowHeight::Fixed(height.max(1)),
+        };
+        col.update_tile_sizes();
+    }
+
+    pub fn set_fullscreen(&mut self, window: &W, is_fullscreen: bool) {
+        if !is_fullscreen {
+            if let Some(col) = self.columns.iter_mut().find(|col| col.contains(window)) {
+                col.set_fullscreen(false);
             }
-            col = &mut self.columns[col_idx];
+            return;
         }
 
-        col.set_fullscreen(is_fullscreen);
+        // Fullscreen windows live alone in their columns.
+        let col_idx = self.ensure_own_column(window);
+        self.columns[col_idx].set_fullscreen(true);
     }
 
     pub fn toggle_fullscreen(&mut self, window: &W) {
+        if self.floating.has_window(window) {
+            self.set_fullscreen(window, true);
+            return;
+        }
+
         let col = self
             .columns
             .iter_mut()
@@ -1119,6 +1955,136 @@ impl<W: LayoutElement> Workspace<W> {
         self.set_fullscreen(window, value);
     }
 
+    pub fn set_maximized(&mut self, window: &W, is_maximized: bool) {
+        if !is_maximized {
+            if let Some(col) = self.columns.iter_mut().find(|col| col.contains(window)) {
+                col.set_maximized(false);
+            }
+            return;
+        }
+
+        let col_idx = self.ensure_own_column(window);
+        self.columns[col_idx].set_maximized(true);
+    }
+
+    /// Makes sure that the window is alone in a column.
+    ///
+    /// Takes the window out of the floating layer, or out of a column with other windows into a
+    /// new column to the right. Returns the index of the window's column.
+    fn ensure_own_column(&mut self, window: &W) -> usize {
+        if self.floating.has_window(window) {
+            let activate = self.floating_is_active && self.floating.active_window() == Some(window);
+            let window = self.floating.remove_window(window);
+            return self.add_window_from_floating(window, activate);
+        }
+
+        let (col_idx, tile_idx) = self
+            .columns
+            .iter()
+            .enumerate()
+            .find_map(|(col_idx, col)| col.position(window).map(|tile_idx| (col_idx, tile_idx)))
+            .unwrap();
+
+        let col = &mut self.columns[col_idx];
+        if col.tiles.len() == 1 {
+            return col_idx;
+        }
+
+        // This isn't the only window in its column; extract it into a separate column.
+        let target_window_was_focused =
+            self.active_column_idx == col_idx && col.active_tile_idx == tile_idx;
+        let window = col.tiles.remove(tile_idx).into_window();
+        col.heights.remove(tile_idx);
+        col.active_tile_idx = min(col.active_tile_idx, col.tiles.len() - 1);
+        col.update_tile_sizes();
+        let width = col.width;
+        let is_full_width = col.is_full_width;
+
+        let col_idx = col_idx + 1;
+        let column = Column::new(
+            window,
+            self.view_size,
+            self.working_area,
+            self.options.clone(),
+            width,
+            is_full_width,
+        );
+        let column_width = column.width();
+        self.columns.insert(col_idx, column);
+        self.animate_columns_shift(col_idx + 1, -(column_width + self.options.gaps));
+        if self.active_column_idx >= col_idx || target_window_was_focused {
+            self.active_column_idx += 1;
+        }
+
+        col_idx
+    }
+
+    /// Adds a window taken out of the floating layer as a new column.
+    ///
+    /// Returns the index of the new column.
+    fn add_window_from_floating(&mut self, window: W, activate: bool) -> usize {
+        let idx = if self.columns.is_empty() {
+            0
+        } else {
+            self.active_column_idx + 1
+        };
+
+        let width = self
+            .options
+            .default_width
+            .unwrap_or_else(|| ColumnWidth::Fixed(window.size().w));
+        let activate = activate || self.columns.is_empty();
+        self.add_window(window, activate, width, false);
+
+        if self.floating.is_empty() {
+            self.floating_is_active = false;
+        }
+
+        idx
+    }
+
+    /// Moves the active window between the columns and the floating layer.
+    pub fn toggle_window_floating(&mut self) {
+        if self.floating_is_active {
+            let window = self.floating.remove_active_window().unwrap();
+            self.add_window_from_floating(window, true);
+            return;
+        }
+
+        if self.columns.is_empty() {
+            return;
+        }
+
+        let col = &self.columns[self.active_column_idx];
+        let window = self.remove_window_by_idx(self.active_column_idx, col.active_tile_idx);
+
+        // Keep the current size. A fullscreen window gets to pick its own size instead, since
+        // its current size is that of the output.
+        let size = if window.is_fullscreen() || window.is_pending_fullscreen() {
+            Size::from((0, 0))
+        } else {
+            window.size()
+        };
+        window.request_size(size);
+        self.add_floating_window(window, true);
+    }
+
+    pub fn switch_focus_between_floating_and_tiling(&mut self) {
+        if self.floating_is_active {
+            self.leave_floating_focus();
+        } else if !self.floating.is_empty() {
+            self.floating_is_active = true;
+        }
+    }
+
+    pub fn move_floating_window(&mut self, x: i32, y: i32) {
+        if !self.floating_is_active {
+            return;
+        }
+
+        self.floating.move_active_by(Point::from((x, y)));
+    }
+
     pub fn render_above_top_layer(&self) -> bool {
         // Render above the top layer if we're on a fullscreen window and the view is stationary.
         if self.columns.is_empty() {
@@ -1136,7 +2102,7 @@ impl<W: LayoutElement> Workspace<W> {
         &self,
         renderer: &mut R,
     ) -> Vec<WorkspaceRenderElement<R>> {
-        if self.columns.is_empty() {
+        if self.columns.is_empty() && self.floating.is_empty() && self.insert_hint.is_none() {
             return vec![];
         }
 
@@ -1149,6 +2115,37 @@ impl<W: LayoutElement> Workspace<W> {
             .unwrap_or(Scale::from(1.));
 
         let mut rv = vec![];
+
+        if let Some(hint) = &self.insert_hint {
+            let elem = SolidColorRenderElement::from_buffer(
+                &hint.buffer,
+                hint.location.to_physical_precise_round(output_scale),
+                output_scale,
+                1.,
+                Kind::Unspecified,
+            );
+            rv.push(elem.into());
+        }
+
+        let mut first = true;
+
+        // Floating tiles go on top, topmost (and active) first.
+        for (tile, tile_pos) in self.floating.tiles_with_positions() {
+            rv.extend(
+                tile.render(renderer, tile_pos, output_scale)
+                    .map(Into::into),
+            );
+
+            if first && self.floating_is_active {
+                rv.extend(self.focus_ring.render(output_scale).map(Into::into));
+            }
+            first = false;
+        }
+
+        if self.columns.is_empty() {
+            return rv;
+        }
+
         let mut first = true;
 
         for (tile, tile_pos) in self.tiles_in_render_order() {
@@ -1159,10 +2156,45 @@ impl<W: LayoutElement> Workspace<W> {
             );
 
             // For the active tile (which comes first), draw the focus ring.
-            if first {
+            if first && !self.floating_is_active {
                 rv.extend(self.focus_ring.render(output_scale).map(Into::into));
-                first = false;
             }
+            first = false;
+        }
+
+        let integer_scale = self
+            .output
+            .as_ref()
+            .map(|o| o.current_scale().integer_scale())
+            .unwrap_or(1);
+        for (col_idx, col, col_x) in self.columns_with_tab_bars() {
+            let area = col.tab_bar_area().unwrap();
+            let titles = col
+                .tiles
+                .iter()
+                .map(|tile| tile.window().title().unwrap_or_default())
+                .collect();
+            let color = if col_idx == self.active_column_idx && !self.floating_is_active {
+                self.options.focus_ring.active_color
+            } else {
+                self.options.focus_ring.inactive_color
+            };
+            let location: Point<i32, Logical> = Point::from((col_x, area.loc.y));
+            let location = location.to_physical_precise_round(output_scale);
+
+            rv.extend(
+                col.tab_bar
+                    .render(
+                        renderer,
+                        titles,
+                        col.active_tile_idx,
+                        area.size.w,
+                        color,
+                        location,
+                        integer_scale,
+                    )
+                    .map(Into::into),
+            );
         }
 
         rv
@@ -1177,6 +2209,7 @@ impl Workspace<Window> {
             for (tile_idx, tile) in col.tiles.iter().enumerate() {
                 let win = tile.window();
                 let active = is_active
+                    && !self.floating_is_active
                     && self.active_column_idx == col_idx
                     && col.active_tile_idx == tile_idx;
                 win.set_activated(active);
@@ -1189,6 +2222,18 @@ impl Workspace<Window> {
                 win.refresh();
             }
         }
+
+        for (idx, win) in self.floating.windows().enumerate() {
+            let active = is_active && self.floating_is_active && idx == 0;
+            win.set_activated(active);
+
+            win.toplevel().with_pending_state(|state| {
+                state.bounds = Some(bounds);
+            });
+
+            win.toplevel().send_pending_configure();
+            win.refresh();
+        }
     }
 }
 
@@ -1207,7 +2252,11 @@ impl<W: LayoutElement> Column<W> {
             active_tile_idx: 0,
             width,
             is_full_width,
+            is_maximized: false,
             is_fullscreen: false,
+            display: ColumnDisplay::Normal,
+            tab_bar: TabBar::default(),
+            move_anim: None,
             view_size,
             working_area,
             options,
@@ -1224,6 +2273,36 @@ impl<W: LayoutElement> Column<W> {
         rv
     }
 
+    fn advance_animations(&mut self, current_time: Duration) {
+        if let Some(anim) = &mut self.move_anim {
+            anim.set_current_time(current_time);
+            if anim.is_done() {
+                self.move_anim = None;
+            }
+        }
+    }
+
+    /// Starts animating the column from the given offset to its current position.
+    fn animate_move_from(&mut self, from_x_offset: i32) {
+        let (current_offset, velocity) = self
+            .move_anim
+            .as_ref()
+            .map_or((0., 0.), |anim| (anim.value(), anim.velocity()));
+        self.move_anim = Some(Animation::new(
+            current_offset + from_x_offset as f64,
+            0.,
+            velocity,
+            self.options.animations.window_movement,
+        ));
+    }
+
+    /// Horizontal offset of the column from its position in the layout, while it's animating.
+    fn render_offset(&self) -> i32 {
+        self.move_anim
+            .as_ref()
+            .map_or(0, |anim| anim.value().round() as i32)
+    }
+
     fn set_view_size(&mut self, size: Size<i32, Logical>, working_area: Rectangle<i32, Logical>) {
         if self.view_size == size && self.working_area == working_area {
             return;
@@ -1269,6 +2348,7 @@ impl<W: LayoutElement> Column<W> {
     fn set_width(&mut self, width: ColumnWidth) {
         self.width = width;
         self.is_full_width = false;
+        self.is_maximized = false;
         self.update_tile_sizes();
     }
 
@@ -1283,16 +2363,29 @@ impl<W: LayoutElement> Column<W> {
             .position(|win| win == window)
     }
 
+    pub fn heights(&self) -> &[WindowHeight] {
+        &self.heights
+    }
+
     fn activate_window(&mut self, window: &W) {
         let idx = self.position(window).unwrap();
         self.active_tile_idx = idx;
     }
 
     fn add_window(&mut self, window: W) {
+        self.insert_window(self.tiles.len(), window);
+    }
+
+    fn insert_window(&mut self, idx: usize, window: W) {
         let tile = Tile::new(window, self.options.clone());
         self.is_fullscreen = false;
-        self.tiles.push(tile);
-        self.heights.push(WindowHeight::Auto);
+        self.tiles.insert(idx, tile);
+        self.heights.insert(idx, WindowHeight::Auto);
+
+        if idx <= self.active_tile_idx && self.tiles.len() > 1 {
+            self.active_tile_idx += 1;
+        }
+
         self.update_tile_sizes();
     }
 
@@ -1306,6 +2399,10 @@ impl<W: LayoutElement> Column<W> {
     }
 
     fn update_tile_sizes(&mut self) {
+        for tile in &self.tiles {
+            tile.window().request_maximized(self.is_maximized);
+        }
+
         if self.is_fullscreen {
             self.tiles[0].request_fullscreen(self.view_size);
             return;
@@ -1341,7 +2438,7 @@ impl<W: LayoutElement> Column<W> {
             .unwrap_or(i32::MAX);
         let max_width = max(max_width, min_width);
 
-        let width = if self.is_full_width {
+        let width = if self.is_full_width || self.is_maximized {
             ColumnWidth::Proportion(1.)
         } else {
             self.width
@@ -1350,6 +2447,24 @@ impl<W: LayoutElement> Column<W> {
         let width = width.resolve(&self.options, self.working_area.size.w);
         let width = max(min(width, max_width), min_width);
 
+        if self.display == ColumnDisplay::Tabbed {
+            // Every tile gets the full column height below the tab bar.
+            let height = self.working_area.size.h - self.options.gaps * 2 - TAB_BAR_HEIGHT;
+            for (tile, (min_size, max_size)) in zip(&mut self.tiles, zip(&min_size, &max_size)) {
+                let mut height = height;
+                if max_size.h > 0 {
+                    height = min(height, max_size.h);
+                }
+                if min_size.h > 0 {
+                    height = max(height, min_size.h);
+                }
+                let height = max(height, 1);
+
+                tile.request_tile_size(Size::from((width, height)));
+            }
+            return;
+        }
+
         // Compute the tile heights. Start by converting window heights to tile heights.
         let mut heights = zip(&self.tiles, &self.heights)
             .map(|(tile, height)| match *height {
@@ -1516,11 +2631,12 @@ impl<W: LayoutElement> Column<W> {
 
         for tile in &self.tiles {
             assert_eq!(self.is_fullscreen, tile.window().is_pending_fullscreen());
+            assert_eq!(self.is_maximized, tile.window().is_pending_maximized());
         }
     }
 
     fn toggle_width(&mut self) {
-        let width = if self.is_full_width {
+        let width = if self.is_full_width || self.is_maximized {
             ColumnWidth::Proportion(1.)
         } else {
             self.width
@@ -1544,12 +2660,23 @@ impl<W: LayoutElement> Column<W> {
     }
 
     fn toggle_full_width(&mut self) {
-        self.is_full_width = !self.is_full_width;
+        if self.is_maximized {
+            // Toggling off a maximized column restores its width.
+            self.is_maximized = false;
+            self.is_full_width = false;
+        } else {
+            self.is_full_width = !self.is_full_width;
+        }
+        self.update_tile_sizes();
+    }
+
+    fn set_maximized(&mut self, is_maximized: bool) {
+        self.is_maximized = is_maximized;
         self.update_tile_sizes();
     }
 
     fn set_column_width(&mut self, change: SizeChange) {
-        let width = if self.is_full_width {
+        let width = if self.is_full_width || self.is_maximized {
             ColumnWidth::Proportion(1.)
         } else {
             self.width
@@ -1647,6 +2774,47 @@ impl<W: LayoutElement> Column<W> {
         self.update_tile_sizes();
     }
 
+    /// Sets the column width and the tile height in logical pixels during an interactive resize.
+    fn set_interactive_size(&mut self, tile_idx: usize, width: Option<i32>, height: Option<i32>) {
+        // FIXME: fix overflows then remove limits.
+        const MAX_PX: i32 = 100000;
+
+        if let Some(width) = width {
+            let min_width = self
+                .tiles
+                .iter()
+                .map(|tile| tile.min_size().w)
+                .max()
+                .unwrap_or(0);
+            let max_width = self
+                .tiles
+                .iter()
+                .map(|tile| tile.max_size().w)
+                .filter(|w| *w > 0)
+                .min()
+                .unwrap_or(MAX_PX);
+
+            let width = width.min(max_width).max(min_width).clamp(1, MAX_PX);
+            self.set_width(ColumnWidth::Fixed(width));
+        }
+
+        if let Some(height) = height {
+            let tile = &self.tiles[tile_idx];
+            let min_height = tile.min_size().h;
+            let max_height = tile.max_size().h;
+
+            let mut height = height;
+            if max_height > 0 {
+                height = height.min(max_height);
+            }
+            let height = height.max(min_height).clamp(1, MAX_PX);
+
+            let window_height = tile.window_height_for_tile_height(height);
+            self.heights[tile_idx] = WindowHeight::Fixed(window_height.max(1));
+            self.update_tile_sizes();
+        }
+    }
+
     fn set_fullscreen(&mut self, is_fullscreen: bool) {
         assert_eq!(self.tiles.len(), 1);
         self.is_fullscreen = is_fullscreen;
@@ -1669,12 +2837,43 @@ impl<W: LayoutElement> Column<W> {
             y = self.working_area.loc.y + self.options.gaps;
         }
 
+        let is_tabbed = self.is_tabbed();
+        if is_tabbed {
+            y += TAB_BAR_HEIGHT;
+        }
+
         self.tiles.iter().map(move |tile| {
             let pos = y;
-            y += tile.tile_size().h + self.options.gaps;
+            if !is_tabbed {
+                y += tile.tile_size().h + self.options.gaps;
+            }
             pos
         })
     }
+
+    /// Returns whether only the active tile of this column is shown, under a tab bar.
+    fn is_tabbed(&self) -> bool {
+        self.display == ColumnDisplay::Tabbed && !self.is_fullscreen
+    }
+
+    fn toggle_tabbed_display(&mut self) {
+        self.display = match self.display {
+            ColumnDisplay::Normal => ColumnDisplay::Tabbed,
+            ColumnDisplay::Tabbed => ColumnDisplay::Normal,
+        };
+        self.update_tile_sizes();
+    }
+
+    /// Returns the tab bar area relative to the column, if the column is tabbed.
+    fn tab_bar_area(&self) -> Option<Rectangle<i32, Logical>> {
+        if !self.is_tabbed() {
+            return None;
+        }
+
+        let loc = Point::from((0, self.working_area.loc.y + self.options.gaps));
+        let size = Size::from((self.width(), TAB_BAR_HEIGHT));
+        Some(Rectangle::from_loc_and_size(loc, size))
+    }
 }
 
 fn compute_new_view_offset(
@@ -20,6 +20,8 @@ pub mod render_helpers;
 pub mod screenshot_ui;
 pub mod utils;
 pub mod watcher;
+pub mod window;
+pub mod window_switcher;
 
 #[cfg(not(feature = "xdp-gnome-screencast"))]
 pub mod dummy_pw_utils;
@@ -33,4 +35,29 @@ pub use dummy_pw_utils as pw_utils;
 pub enum Msg {
     /// List connected outputs.
     Outputs,
+    /// List open windows.
+    Windows,
+    /// List workspaces.
+    Workspaces,
+    /// Perform an action.
+    Action {
+        #[command(subcommand)]
+        action: niri_ipc::Action,
+    },
+    /// Change output configuration temporarily.
+    ///
+    /// The configuration is changed temporarily and not saved into the config file. These
+    /// temporary changes are reverted the next time the config file is reloaded.
+    Output {
+        /// Output name.
+        ///
+        /// Run `niri msg outputs` to see the output names.
+        #[arg()]
+        output: String,
+        /// Configuration to apply.
+        #[command(subcommand)]
+        action: niri_ipc::OutputAction,
+    },
+    /// Continuously print events from niri.
+    EventStream,
 }
@@ -19,6 +19,7 @@ use niri::utils::{cause_panic, spawn, REMOVE_ENV_RUST_BACKTRACE, REMOVE_ENV_RUST
 use niri::watcher::Watcher;
 use niri::{animation, Msg};
 use niri_config::Config;
+use niri_ipc::ConfiguredMode;
 use portable_atomic::Ordering;
 use sd_notify::NotifyState;
 use smithay::reexports::calloop::{self, EventLoop};
@@ -34,6 +35,13 @@ struct Cli {
     /// Path to config file (default: `$XDG_CONFIG_HOME/niri/config.kdl`).
     #[arg(short, long)]
     config: Option<PathBuf>,
+    /// Run without any display hardware, rendering to virtual outputs (useful for testing).
+    #[arg(long)]
+    headless: bool,
+    /// Mode of a virtual output for --headless, can be given multiple times.
+    #[arg(long, value_name = "WxH[@REFRESH]", requires = "headless")]
+    #[arg(default_value = "1920x1080@60")]
+    headless_output: Vec<ConfiguredMode>,
     /// Command to run upon compositor startup.
     #[arg(last = true)]
     command: Vec<OsString>,
@@ -165,26 +173,31 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     let mut config_errored = false;
+    let mut config_files = Vec::new();
     let mut config = path
         .as_deref()
-        .and_then(|path| match Config::load(path) {
-            Ok(config) => Some(config),
-            Err(err) => {
-                warn!("{err:?}");
-                config_errored = true;
-                None
-            }
-        })
+        .and_then(
+            |path| match Config::load_with_files(path, &mut config_files) {
+                Ok(config) => Some(config),
+                Err(err) => {
+                    warn!("{err:?}");
+                    config_errored = true;
+                    None
+                }
+            },
+        )
         .unwrap_or_default();
 
-    animation::ANIMATION_SLOWDOWN.store(config.debug.animation_slowdown, Ordering::Relaxed);
+    animation::set_slowdown(&config);
     let spawn_at_startup = mem::take(&mut config.spawn_at_startup);
 
     // Create the compositor.
     let mut event_loop = EventLoop::try_new().unwrap();
     let display = Display::new().unwrap();
+    let headless_outputs = cli.headless.then_some(cli.headless_output);
     let mut state = State::new(
         config,
+        headless_outputs,
         event_loop.handle(),
         event_loop.get_signal(),
         display,
@@ -226,21 +239,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         warn!("error notifying systemd: {err:?}");
     };
 
-    // Set up config file watcher.
-    let _watcher = if let Some(path) = path.clone() {
+    // Set up config file watcher. It watches the config along with every included file, and the
+    // directories of glob includes to pick up newly matching files.
+    if let Some(path) = path.clone() {
         let (tx, rx) = calloop::channel::sync_channel(1);
-        let watcher = Watcher::new(path.clone(), tx);
+        let watcher = Watcher::new(config_files, tx);
         event_loop
             .handle()
             .insert_source(rx, move |event, _, state| match event {
-                calloop::channel::Event::Msg(()) => state.reload_config(path.clone()),
+                calloop::channel::Event::Msg(()) => {
+                    let files = state.reload_config(path.clone());
+                    watcher.set_paths(files);
+                }
                 calloop::channel::Event::Closed => (),
             })
             .unwrap();
-        Some(watcher)
-    } else {
-        None
-    };
+    }
 
     // Spawn commands from cli and auto-start.
     spawn(cli.command);
@@ -261,6 +275,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .run(None, &mut state, |state| state.refresh_and_flush_clients())
         .unwrap();
 
+    // Keep the changes made since the last periodic save.
+    state.niri.save_layout();
+
     Ok(())
 }
 
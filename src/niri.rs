@@ -3,7 +3,6 @@ use std::collections::{HashMap, HashSet};
 use std::ffi::OsString;
 use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::atomic::Ordering;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{env, mem, thread};
@@ -11,7 +10,7 @@ use std::{env, mem, thread};
 use _server_decoration::server::org_kde_kwin_server_decoration_manager::Mode as KdeDecorationsMode;
 use anyhow::Context;
 use calloop::futures::Scheduler;
-use niri_config::{Config, TrackLayout};
+use niri_config::{Config, TrackLayout, WorkspaceReferenceArg};
 use smithay::backend::allocator::Fourcc;
 use smithay::backend::renderer::element::memory::MemoryRenderBufferRenderElement;
 use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRenderElement};
@@ -52,7 +51,7 @@ use smithay::reexports::wayland_server::backend::{
     ClientData, ClientId, DisconnectReason, GlobalId,
 };
 use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
-use smithay::reexports::wayland_server::{Display, DisplayHandle};
+use smithay::reexports::wayland_server::{Display, DisplayHandle, Resource};
 use smithay::utils::{
     ClockSource, Logical, Monotonic, Physical, Point, Rectangle, Scale, Size, Transform,
     SERIAL_COUNTER,
@@ -77,7 +76,7 @@ use smithay::wayland::session_lock::{LockSurface, SessionLockManagerState, Sessi
 use smithay::wayland::shell::kde::decoration::KdeDecorationState;
 use smithay::wayland::shell::wlr_layer::{Layer, WlrLayerShellState};
 use smithay::wayland::shell::xdg::decoration::XdgDecorationState;
-use smithay::wayland::shell::xdg::XdgShellState;
+use smithay::wayland::shell::xdg::{XdgShellState, XdgToplevelSurfaceData};
 use smithay::wayland::shm::ShmState;
 use smithay::wayland::socket::ListeningSocketSource;
 use smithay::wayland::tablet_manager::{TabletManagerState, TabletSeatTrait};
@@ -85,7 +84,7 @@ use smithay::wayland::text_input::TextInputManagerState;
 use smithay::wayland::virtual_keyboard::VirtualKeyboardManagerState;
 
 use crate::backend::tty::SurfaceDmabufFeedback;
-use crate::backend::{Backend, RenderResult, Tty, Winit};
+use crate::backend::{Backend, Headless, RenderResult, Tty, Winit};
 use crate::config_error_notification::ConfigErrorNotification;
 use crate::cursor::{CursorManager, CursorTextureCache, RenderCursor, XCursor};
 #[cfg(feature = "dbus")]
@@ -98,19 +97,29 @@ use crate::handlers::configure_lock_surface;
 use crate::hotkey_overlay::HotkeyOverlay;
 use crate::input::{apply_libinput_settings, TabletData};
 use crate::ipc::server::IpcServer;
+use crate::layout::snapshot::{LayoutSnapshot, WindowIdentity};
 use crate::layout::{Layout, MonitorRenderElement};
 use crate::protocols::foreign_toplevel::{self, ForeignToplevelManagerState};
 use crate::pw_utils::{Cast, PipeWire};
 use crate::render_helpers::NiriRenderer;
 use crate::screenshot_ui::{ScreenshotUi, ScreenshotUiRenderElement};
 use crate::utils::{
-    center, get_monotonic_time, make_screenshot_path, output_size, write_png_rgba8,
+    center, get_monotonic_time, layout_snapshot_path, make_screenshot_path, output_size, window_id,
+    write_png_rgba8,
 };
-use crate::{animation, niri_render_elements};
+use crate::window::Unmapped;
+use crate::window_switcher::{FocusHistory, WindowSwitcher, WindowSwitcherRenderElement};
+use crate::{animation, ipc, niri_render_elements};
 
 const CLEAR_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 1.];
 const CLEAR_COLOR_LOCKED: [f32; 4] = [0.3, 0.1, 0.1, 1.];
 
+/// How often the layout is saved to disk.
+const LAYOUT_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long after startup windows are put back into the previously saved layout.
+const LAYOUT_RESTORE_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct Niri {
     pub config: Rc<RefCell<Config>>,
 
@@ -125,13 +134,17 @@ pub struct Niri {
     // Each workspace corresponds to a Space. Each workspace generally has one Output mapped to it,
     // however it may have none (when there are no outputs connected) or mutiple (when mirroring).
     pub layout: Layout<Window>,
+    /// Where the layout is saved, or `None` if it isn't, as with the headless backend.
+    pub layout_snapshot_path: Option<PathBuf>,
+    /// Last layout snapshot written to disk.
+    pub saved_layout: Option<LayoutSnapshot>,
 
     // This space does not actually contain any windows, but all outputs are mapped into it
     // according to their global position.
     pub global_space: Space<Window>,
 
     // Windows which don't have a buffer attached yet.
-    pub unmapped_windows: HashMap<WlSurface, Window>,
+    pub unmapped_windows: HashMap<WlSurface, Unmapped>,
 
     pub output_state: HashMap<Output, OutputState>,
     pub output_by_name: HashMap<String, Output>,
@@ -141,6 +154,8 @@ pub struct Niri {
 
     pub devices: HashSet<input::Device>,
     pub tablets: HashMap<input::Device, TabletData>,
+    /// Compositor-side scroll factors, resolved from the config when devices are added.
+    pub scroll_factors: HashMap<input::Device, f64>,
 
     // Smithay state.
     pub compositor_state: CompositorState,
@@ -190,6 +205,8 @@ pub struct Niri {
     pub config_error_notification: ConfigErrorNotification,
     pub hotkey_overlay: HotkeyOverlay,
     pub exit_confirm_dialog: Option<ExitConfirmDialog>,
+    pub focus_history: FocusHistory,
+    pub window_switcher: WindowSwitcher,
 
     #[cfg(feature = "dbus")]
     pub dbus: Option<crate::dbus::DBusServers>,
@@ -290,8 +307,13 @@ pub struct State {
 }
 
 impl State {
+    /// Creates the compositor state.
+    ///
+    /// If `headless_outputs` is set, niri uses the headless backend with virtual outputs of these
+    /// modes.
     pub fn new(
         config: Config,
+        headless_outputs: Option<Vec<niri_ipc::ConfiguredMode>>,
         event_loop: LoopHandle<'static, State>,
         stop_signal: LoopSignal,
         display: Display<State>,
@@ -303,7 +325,11 @@ impl State {
         let has_display =
             env::var_os("WAYLAND_DISPLAY").is_some() || env::var_os("DISPLAY").is_some();
 
-        let mut backend = if has_display {
+        let mut backend = if let Some(modes) = headless_outputs {
+            let headless =
+                Headless::new(&modes).context("error initializing the headless backend")?;
+            Backend::Headless(headless)
+        } else if has_display {
             let winit = Winit::new(config.clone(), event_loop.clone())?;
             Backend::Winit(winit)
         } else {
@@ -327,6 +353,7 @@ impl State {
         self.niri.refresh_pointer_outputs();
         self.niri.popups.cleanup();
         self.refresh_popup_grab();
+        ipc::server::refresh(self);
         self.update_keyboard_focus();
         self.refresh_pointer_focus();
         foreign_toplevel::refresh(self);
@@ -421,6 +448,40 @@ impl State {
         self.move_cursor(center(geo).to_f64());
     }
 
+    /// Moves the cursor into the focused tile if warp-mouse-to-focus is enabled.
+    ///
+    /// Returns `true` if the cursor ended up inside the focused tile.
+    pub fn maybe_warp_cursor_to_focus(&mut self) -> bool {
+        if !self.niri.config.borrow().input.warp_mouse_to_focus
+            || self.niri.layout.is_overview_open()
+        {
+            return false;
+        }
+
+        let Some(output) = self.niri.layout.active_output() else {
+            return false;
+        };
+        let Some(rect) = self
+            .niri
+            .layout
+            .active_workspace()
+            .and_then(|ws| ws.active_tile_visual_rectangle())
+        else {
+            return false;
+        };
+
+        let output_geo = self.niri.global_space.output_geometry(output).unwrap();
+        let rect = Rectangle::from_loc_and_size(rect.loc + output_geo.loc, rect.size);
+
+        let pointer = self.niri.seat.get_pointer().unwrap();
+        if rect.to_f64().contains(pointer.current_location()) {
+            return true;
+        }
+
+        self.move_cursor(center(rect).to_f64());
+        true
+    }
+
     pub fn refresh_popup_grab(&mut self) {
         let keyboard_grabbed = self.niri.seat.input_method().keyboard_grabbed();
 
@@ -555,6 +616,17 @@ impl State {
                 }
             }
 
+            let focused_window = focus.as_ref().and_then(|surface| {
+                let (window, _) = self.niri.layout.find_window_and_output(surface)?;
+                Some(window.clone())
+            });
+            if let Some(window) = &focused_window {
+                self.niri.focus_history.push(window.clone());
+            }
+            let id = focused_window.as_ref().map(window_id);
+            self.niri
+                .send_ipc_event(niri_ipc::Event::WindowFocusChanged { id });
+
             self.niri.keyboard_focus = focus.clone();
             keyboard.set_focus(self, focus, SERIAL_COUNTER.next_serial());
 
@@ -563,23 +635,28 @@ impl State {
         }
     }
 
-    pub fn reload_config(&mut self, path: PathBuf) {
+    /// Reloads the config, returning the paths to watch for further changes.
+    ///
+    /// The paths are returned even if the config failed to load, so that fixing it triggers
+    /// another reload.
+    pub fn reload_config(&mut self, path: PathBuf) -> Vec<PathBuf> {
         let _span = tracy_client::span!("State::reload_config");
 
-        let config = match Config::load(&path) {
+        let mut files = Vec::new();
+        let config = match Config::load_with_files(&path, &mut files) {
             Ok(config) => config,
             Err(err) => {
                 warn!("{:?}", err.context("error loading config"));
                 self.niri.config_error_notification.show();
                 self.niri.queue_redraw_all();
-                return;
+                return files;
             }
         };
 
         self.niri.config_error_notification.hide();
 
         self.niri.layout.update_config(&config);
-        animation::ANIMATION_SLOWDOWN.store(config.debug.animation_slowdown, Ordering::Relaxed);
+        animation::set_slowdown(&config);
 
         let mut reload_xkb = None;
         let mut libinput_config_changed = false;
@@ -612,6 +689,9 @@ impl State {
 
         if config.input.touchpad != old_config.input.touchpad
             || config.input.mouse != old_config.input.mouse
+            || config.input.trackpoint != old_config.input.trackpoint
+            || config.input.trackball != old_config.input.trackball
+            || config.input.devices != old_config.input.devices
         {
             libinput_config_changed = true;
         }
@@ -640,55 +720,123 @@ impl State {
         if libinput_config_changed {
             let config = self.niri.config.borrow();
             for mut device in self.niri.devices.iter().cloned() {
-                apply_libinput_settings(&config.input, &mut device);
+                let scroll_factor = apply_libinput_settings(&config.input, &mut device);
+                self.niri.scroll_factors.insert(device, scroll_factor);
             }
         }
 
         if output_config_changed {
-            let mut resized_outputs = vec![];
-            for output in self.niri.global_space.outputs() {
-                let name = output.name();
-                let config = self.niri.config.borrow_mut();
-                let config = config.outputs.iter().find(|o| o.name == name);
-
-                let scale = config.map(|c| c.scale).unwrap_or(1.);
-                let scale = scale.clamp(1., 10.).ceil() as i32;
-
-                let mut transform = config
-                    .map(|c| c.transform.into())
-                    .unwrap_or(Transform::Normal);
-                // FIXME: fix winit damage on other transforms.
-                if name == "winit" {
-                    transform = Transform::Flipped180;
-                }
+            self.reload_output_config();
+        }
 
-                if output.current_scale().integer_scale() != scale
-                    || output.current_transform() != transform
-                {
-                    output.change_current_state(
-                        None,
-                        Some(transform),
-                        Some(output::Scale::Integer(scale)),
-                        None,
-                    );
-                    resized_outputs.push(output.clone());
-                }
+        // Can't really update xdg-decoration settings since we have to hide the globals for CSD
+        // due to the SDL2 bug... I don't imagine clients are prepared for the xdg-decoration
+        // global suddenly appearing? Either way, right now it's live-reloaded in a sense that new
+        // clients will use the new xdg-decoration setting.
+
+        self.niri.send_ipc_event(niri_ipc::Event::ConfigReloaded);
+
+        self.niri.queue_redraw_all();
+
+        files
+    }
+
+    pub fn reload_output_config(&mut self) {
+        let mut resized_outputs = vec![];
+        for output in self.niri.global_space.outputs() {
+            let name = output.name();
+            let config = self.niri.config.borrow_mut();
+            let config = config.outputs.iter().find(|o| o.name == name);
+
+            let scale = config.map(|c| c.scale).unwrap_or(1.);
+            let scale = scale.clamp(1., 10.).ceil() as i32;
+
+            let mut transform = config
+                .map(|c| c.transform.into())
+                .unwrap_or(Transform::Normal);
+            // FIXME: fix winit damage on other transforms.
+            if name == "winit" {
+                transform = Transform::Flipped180;
             }
-            for output in resized_outputs {
-                self.niri.output_resized(output);
+
+            if output.current_scale().integer_scale() != scale
+                || output.current_transform() != transform
+            {
+                output.change_current_state(
+                    None,
+                    Some(transform),
+                    Some(output::Scale::Integer(scale)),
+                    None,
+                );
+                resized_outputs.push(output.clone());
             }
+        }
+        for output in resized_outputs {
+            self.niri.output_resized(output);
+        }
 
-            self.niri.reposition_outputs(None);
+        self.niri.reposition_outputs(None);
 
-            self.backend.on_output_config_changed(&mut self.niri);
+        self.backend.on_output_config_changed(&mut self.niri);
+    }
+
+    /// Changes an output's configuration without saving it into the config file.
+    pub fn apply_transient_output_config(
+        &mut self,
+        name: &str,
+        action: niri_ipc::OutputAction,
+    ) -> niri_ipc::OutputConfigChanged {
+        // Outputs only support integer scales, so don't silently round the requested one.
+        if let niri_ipc::OutputAction::Scale { scale } = action {
+            if scale.fract() != 0. {
+                return niri_ipc::OutputConfigChanged::FractionalScale;
+            }
         }
 
-        // Can't really update xdg-decoration settings since we have to hide the globals for CSD
-        // due to the SDL2 bug... I don't imagine clients are prepared for the xdg-decoration
-        // global suddenly appearing? Either way, right now it's live-reloaded in a sense that new
-        // clients will use the new xdg-decoration setting.
+        {
+            let mut config = self.niri.config.borrow_mut();
+            let idx = match config.outputs.iter().position(|o| o.name == name) {
+                Some(idx) => idx,
+                None => {
+                    config.outputs.push(niri_config::Output {
+                        name: String::from(name),
+                        ..Default::default()
+                    });
+                    config.outputs.len() - 1
+                }
+            };
+            let config = &mut config.outputs[idx];
+
+            match action {
+                niri_ipc::OutputAction::Off => config.off = true,
+                niri_ipc::OutputAction::On => config.off = false,
+                niri_ipc::OutputAction::Mode { mode } => {
+                    config.mode = match mode {
+                        niri_ipc::ModeToSet::Automatic => None,
+                        niri_ipc::ModeToSet::Specific(mode) => Some(mode.into()),
+                    }
+                }
+                niri_ipc::OutputAction::Scale { scale } => config.scale = scale,
+                niri_ipc::OutputAction::Transform { transform } => {
+                    config.transform = transform.into();
+                }
+                niri_ipc::OutputAction::Position { position } => {
+                    config.position = match position {
+                        niri_ipc::PositionToSet::Automatic => None,
+                        niri_ipc::PositionToSet::Specific(position) => Some(position.into()),
+                    }
+                }
+            }
+        }
 
+        self.reload_output_config();
         self.niri.queue_redraw_all();
+
+        if self.backend.ipc_outputs().borrow().contains_key(name) {
+            niri_ipc::OutputConfigChanged::Applied
+        } else {
+            niri_ipc::OutputConfigChanged::OutputWasMissing
+        }
     }
 
     #[cfg(feature = "xdp-gnome-screencast")]
@@ -798,12 +946,25 @@ impl Niri {
         let display_handle = display.handle();
         let config_ = config.borrow();
 
-        let layout = Layout::new(&config_);
+        // Test and CI instances must not pick up or overwrite the layout of the user's session.
+        let snapshot_path = match backend {
+            Backend::Headless(_) => None,
+            _ => layout_snapshot_path(),
+        };
+
+        let mut layout = Layout::new(&config_);
+        if let Some(path) = &snapshot_path {
+            match LayoutSnapshot::load(path) {
+                Ok(Some(snapshot)) => layout.start_restore(snapshot),
+                Ok(None) => (),
+                Err(err) => warn!("error loading the saved layout from {path:?}: {err:?}"),
+            }
+        }
 
         let compositor_state = CompositorState::new_v6::<State>(&display_handle);
         let xdg_shell_state = XdgShellState::new_with_capabilities::<State>(
             &display_handle,
-            [WmCapabilities::Fullscreen],
+            [WmCapabilities::Fullscreen, WmCapabilities::Maximize],
         );
         let xdg_decoration_state =
             XdgDecorationState::new_with_filter::<State, _>(&display_handle, |client| {
@@ -900,7 +1061,7 @@ impl Niri {
             });
 
         let screenshot_ui = ScreenshotUi::new();
-        let config_error_notification = ConfigErrorNotification::new();
+        let config_error_notification = ConfigErrorNotification::new(config.clone());
 
         let mut hotkey_overlay = HotkeyOverlay::new(config.clone(), backend.mod_key());
         if !config_.hotkey_overlay.skip_at_startup {
@@ -959,6 +1120,27 @@ impl Niri {
             })
             .unwrap();
 
+        if snapshot_path.is_some() {
+            event_loop
+                .insert_source(Timer::from_duration(LAYOUT_SAVE_INTERVAL), |_, _, state| {
+                    state.niri.save_layout();
+                    TimeoutAction::ToDuration(LAYOUT_SAVE_INTERVAL)
+                })
+                .unwrap();
+
+            event_loop
+                .insert_source(
+                    Timer::from_duration(LAYOUT_RESTORE_TIMEOUT),
+                    |_, _, state| {
+                        // By now the windows from the previous session have had their chance to
+                        // reappear.
+                        state.niri.layout.finish_restore();
+                        TimeoutAction::Drop
+                    },
+                )
+                .unwrap();
+        }
+
         drop(config_);
         Self {
             config,
@@ -971,6 +1153,8 @@ impl Niri {
             start_time: Instant::now(),
 
             layout,
+            layout_snapshot_path: snapshot_path,
+            saved_layout: None,
             global_space: Space::default(),
             output_state: HashMap::new(),
             output_by_name: HashMap::new(),
@@ -979,6 +1163,7 @@ impl Niri {
 
             devices: HashSet::new(),
             tablets: HashMap::new(),
+            scroll_factors: HashMap::new(),
 
             compositor_state,
             xdg_shell_state,
@@ -1022,6 +1207,8 @@ impl Niri {
             config_error_notification,
             hotkey_overlay,
             exit_confirm_dialog,
+            focus_history: FocusHistory::default(),
+            window_switcher: WindowSwitcher::default(),
 
             #[cfg(feature = "dbus")]
             dbus: None,
@@ -1221,9 +1408,11 @@ impl Niri {
         };
         let rv = self.output_state.insert(output.clone(), state);
         assert!(rv.is_none(), "output was already tracked");
-        let rv = self.output_by_name.insert(name, output.clone());
+        let rv = self.output_by_name.insert(name.clone(), output.clone());
         assert!(rv.is_none(), "output was already tracked");
 
+        self.send_ipc_event(niri_ipc::Event::OutputAdded { name });
+
         // Must be last since it will call queue_redraw(output) which needs things to be filled-in.
         self.reposition_outputs(Some(&output));
     }
@@ -1240,6 +1429,10 @@ impl Niri {
         let state = self.output_state.remove(output).unwrap();
         self.output_by_name.remove(&output.name()).unwrap();
 
+        self.send_ipc_event(niri_ipc::Event::OutputRemoved {
+            name: output.name(),
+        });
+
         match state.redraw_state {
             RedrawState::Idle => (),
             RedrawState::Queued(idle) => idle.cancel(),
@@ -1568,6 +1761,24 @@ impl Niri {
             .cloned()
     }
 
+    /// Resolves a workspace reference into a workspace index and, if the workspace is on an
+    /// output other than the active one, that output.
+    ///
+    /// Returns `None` if there's no workspace with the referenced name.
+    pub fn find_output_and_workspace_index(
+        &self,
+        workspace_reference: WorkspaceReferenceArg,
+    ) -> Option<(Option<Output>, usize)> {
+        match workspace_reference {
+            WorkspaceReferenceArg::Index(index) => Some((None, index.saturating_sub(1) as usize)),
+            WorkspaceReferenceArg::Name(name) => {
+                let (output, index) = self.layout.find_workspace_by_name(&name)?;
+                let output = (Some(output) != self.layout.active_output()).then(|| output.clone());
+                Some((output, index))
+            }
+        }
+    }
+
     pub fn output_for_tablet(&self) -> Option<&Output> {
         let config = self.config.borrow();
         let map_to_output = config.input.tablet.map_to_output.as_ref();
@@ -1603,6 +1814,13 @@ impl Niri {
         state.lock_surface.as_ref().map(|s| s.wl_surface()).cloned()
     }
 
+    /// Sends an event to IPC event stream clients.
+    pub fn send_ipc_event(&self, event: niri_ipc::Event) {
+        if let Some(server) = &self.ipc_server {
+            server.send_event(event);
+        }
+    }
+
     /// Schedules an immediate redraw on all outputs if one is not already scheduled.
     pub fn queue_redraw_all(&mut self) {
         let outputs: Vec<_> = self.output_state.keys().cloned().collect();
@@ -1644,6 +1862,81 @@ impl Niri {
         };
     }
 
+    /// Queues a redraw of the output showing the window switcher, if it is open.
+    pub fn queue_redraw_window_switcher(&mut self) {
+        if let Some(output) = self.window_switcher.output().cloned() {
+            self.queue_redraw(output);
+        }
+    }
+
+    /// Returns all windows in the layout, most recently focused first.
+    pub fn windows_in_focus_order(&self) -> Vec<Window> {
+        let mut rest = Vec::new();
+        self.layout.with_windows(|win, _| {
+            // Windows hidden in a scratchpad can't be switched to.
+            if self
+                .layout
+                .hidden_scratchpad_window(win.toplevel().wl_surface())
+                .is_none()
+            {
+                rest.push(win.clone());
+            }
+        });
+
+        let mut windows: Vec<Window> = self
+            .focus_history
+            .windows()
+            .filter(|win| rest.contains(win))
+            .cloned()
+            .collect();
+        rest.retain(|win| !windows.contains(win));
+        windows.append(&mut rest);
+        windows
+    }
+
+    /// Returns what the window is recognized by when restoring the saved layout.
+    pub fn window_identity(&self, window: &Window) -> WindowIdentity {
+        let wl_surface = window.toplevel().wl_surface();
+
+        let (app_id, title) = with_states(wl_surface, |states| {
+            let role = states
+                .data_map
+                .get::<XdgToplevelSurfaceData>()
+                .unwrap()
+                .lock()
+                .unwrap();
+            (role.app_id.clone(), role.title.clone())
+        });
+
+        let pid = wl_surface
+            .client()
+            .and_then(|client| client.get_credentials(&self.display_handle).ok())
+            .map(|credentials| credentials.pid);
+
+        WindowIdentity { app_id, title, pid }
+    }
+
+    /// Saves the layout to disk if it changed since the last save.
+    pub fn save_layout(&mut self) {
+        let _span = tracy_client::span!("Niri::save_layout");
+
+        let Some(path) = &self.layout_snapshot_path else {
+            return;
+        };
+
+        let snapshot = self.layout.snapshot(|window| self.window_identity(window));
+        if self.saved_layout.as_ref() == Some(&snapshot) {
+            return;
+        }
+
+        if let Err(err) = snapshot.save(path) {
+            warn!("error saving the layout to {path:?}: {err:?}");
+            return;
+        }
+
+        self.saved_layout = Some(snapshot);
+    }
+
     pub fn pointer_element<R: NiriRenderer>(
         &self,
         renderer: &mut R,
@@ -1943,6 +2236,15 @@ impl Niri {
             elements.push(element.into());
         }
 
+        // Then the window switcher.
+        let active_color = self.config.borrow().layout.focus_ring.active_color;
+        elements.extend(
+            self.window_switcher
+                .render(renderer, output, active_color)
+                .into_iter()
+                .map(OutputRenderElements::from),
+        );
+
         // Get monitor elements.
         let mon = self.layout.monitor_for_output(output).unwrap();
         let monitor_elements = mon.render_elements(renderer);
@@ -2298,16 +2600,8 @@ impl Niri {
         let state = self.output_state.get(output).unwrap();
         let sequence = state.current_estimated_sequence;
 
-        let should_send = |surface: &WlSurface, states: &SurfaceData| {
-            // Do the standard primary scanout output check. For pointer surfaces it deduplicates
-            // the frame callbacks across potentially multiple outputs, and for regular windows and
-            // layer-shell surfaces it avoids sending frame callbacks to invisible surfaces.
-            let current_primary_output = surface_primary_scanout_output(surface, states);
-            if current_primary_output.as_ref() != Some(output) {
-                return None;
-            }
-
-            // Next, check the throttling status.
+        let should_send_throttled = |states: &SurfaceData| {
+            // Check the throttling status.
             let frame_throttling_state = states
                 .data_map
                 .get_or_insert(SurfaceFrameThrottlingState::default);
@@ -2334,12 +2628,33 @@ impl Niri {
             }
         };
 
+        let should_send = |surface: &WlSurface, states: &SurfaceData| {
+            // Do the standard primary scanout output check. For pointer surfaces it deduplicates
+            // the frame callbacks across potentially multiple outputs, and for regular windows and
+            // layer-shell surfaces it avoids sending frame callbacks to invisible surfaces.
+            let current_primary_output = surface_primary_scanout_output(surface, states);
+            if current_primary_output.as_ref() != Some(output) {
+                return None;
+            }
+
+            should_send_throttled(states)
+        };
+
         let frame_callback_time = get_monotonic_time();
 
         for win in self.layout.windows_for_output(output) {
             win.send_frame(output, frame_callback_time, None, should_send);
         }
 
+        // Keep the window switcher thumbnails live, even for windows that are not visible.
+        if self.window_switcher.output() == Some(output) {
+            for win in self.window_switcher.windows() {
+                win.send_frame(output, frame_callback_time, None, |_, states| {
+                    should_send_throttled(states)
+                });
+            }
+        }
+
         for surface in layer_map_for_output(output).layers() {
             surface.send_frame(output, frame_callback_time, None, should_send);
         }
@@ -3005,5 +3320,6 @@ niri_render_elements! {
         ScreenshotUi = ScreenshotUiRenderElement,
         // Used for the CPU-rendered panels.
         RelocatedMemoryBuffer = RelocateRenderElement<MemoryRenderBufferRenderElement<R>>,
+        WindowSwitcher = WindowSwitcherRenderElement<R>,
     }
 }
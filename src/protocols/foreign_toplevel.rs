@@ -37,6 +37,8 @@ pub trait ForeignToplevelHandler {
     fn close(&mut self, wl_surface: WlSurface);
     fn set_fullscreen(&mut self, wl_surface: WlSurface, wl_output: Option<WlOutput>);
     fn unset_fullscreen(&mut self, wl_surface: WlSurface);
+    fn set_maximized(&mut self, wl_surface: WlSurface);
+    fn unset_maximized(&mut self, wl_surface: WlSurface);
 }
 
 struct ToplevelData {
@@ -79,7 +81,10 @@ pub fn refresh(state: &mut State) {
 
     // Handle closed windows.
     protocol_state.toplevels.retain(|surface, data| {
-        if state.niri.layout.find_window_and_output(surface).is_some() {
+        let layout = &state.niri.layout;
+        if layout.find_window_and_output(surface).is_some()
+            || layout.hidden_scratchpad_window(surface).is_some()
+        {
             return true;
         }
 
@@ -391,8 +396,12 @@ where
         let surface = surface.clone();
 
         match request {
-            zwlr_foreign_toplevel_handle_v1::Request::SetMaximized => (),
-            zwlr_foreign_toplevel_handle_v1::Request::UnsetMaximized => (),
+            zwlr_foreign_toplevel_handle_v1::Request::SetMaximized => {
+                state.set_maximized(surface);
+            }
+            zwlr_foreign_toplevel_handle_v1::Request::UnsetMaximized => {
+                state.unset_maximized(surface);
+            }
             zwlr_foreign_toplevel_handle_v1::Request::SetMinimized => (),
             zwlr_foreign_toplevel_handle_v1::Request::UnsetMinimized => (),
             zwlr_foreign_toplevel_handle_v1::Request::Activate { .. } => {
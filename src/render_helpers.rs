@@ -1,39 +1,30 @@
-use smithay::backend::allocator::dmabuf::Dmabuf;
 use smithay::backend::renderer::element::texture::TextureRenderElement;
 use smithay::backend::renderer::element::{Element, Id, Kind, RenderElement, UnderlyingStorage};
 use smithay::backend::renderer::gles::{GlesError, GlesFrame, GlesRenderer, GlesTexture};
+use smithay::backend::renderer::pixman::{PixmanError, PixmanFrame, PixmanRenderer};
 use smithay::backend::renderer::utils::CommitCounter;
-use smithay::backend::renderer::{
-    Bind, ExportMem, ImportAll, ImportMem, Offscreen, Renderer, Texture,
-};
+use smithay::backend::renderer::{ImportAll, ImportMem, Renderer, Texture};
 use smithay::utils::{Buffer, Physical, Rectangle, Scale, Transform};
 
 use crate::backend::tty::{TtyFrame, TtyRenderer, TtyRendererError};
 
 /// Trait with our main renderer requirements to save on the typing.
+///
+/// This is implemented by the GLES-based renderers as well as by the `PixmanRenderer` used in the
+/// headless backend, so it must not require anything GLES-specific.
 pub trait NiriRenderer:
-    ImportAll
-    + ImportMem
-    + ExportMem
-    + Bind<Dmabuf>
-    + Offscreen<GlesTexture>
-    + Renderer<TextureId = Self::NiriTextureId, Error = Self::NiriError>
-    + AsGlesRenderer
+    ImportAll + ImportMem + Renderer<TextureId = Self::NiriTextureId, Error = Self::NiriError>
 {
     // Associated types to work around the instability of associated type bounds.
     type NiriTextureId: Texture + Clone + 'static;
-    type NiriError: std::error::Error
-        + Send
-        + Sync
-        + From<<GlesRenderer as Renderer>::Error>
-        + 'static;
+    type NiriError: std::error::Error + Send + Sync + 'static;
 }
 
 impl<R> NiriRenderer for R
 where
-    R: ImportAll + ImportMem + ExportMem + Bind<Dmabuf> + Offscreen<GlesTexture> + AsGlesRenderer,
+    R: ImportAll + ImportMem,
     R::TextureId: Texture + Clone + 'static,
-    R::Error: std::error::Error + Send + Sync + From<<GlesRenderer as Renderer>::Error> + 'static,
+    R::Error: std::error::Error + Send + Sync + 'static,
 {
     type NiriTextureId = R::TextureId;
     type NiriError = R::Error;
@@ -195,6 +186,28 @@ macro_rules! niri_render_elements {
             }
         }
 
+        impl smithay::backend::renderer::element::RenderElement<smithay::backend::renderer::pixman::PixmanRenderer> for $name<smithay::backend::renderer::pixman::PixmanRenderer> {
+            fn draw(
+                &self,
+                frame: &mut smithay::backend::renderer::pixman::PixmanFrame<'_>,
+                src: smithay::utils::Rectangle<f64, smithay::utils::Buffer>,
+                dst: smithay::utils::Rectangle<i32, smithay::utils::Physical>,
+                damage: &[smithay::utils::Rectangle<i32, smithay::utils::Physical>],
+            ) -> Result<(), smithay::backend::renderer::pixman::PixmanError> {
+                match self {
+                    $($name::$variant(elem) => {
+                        smithay::backend::renderer::element::RenderElement::<smithay::backend::renderer::pixman::PixmanRenderer>::draw(elem, frame, src, dst, damage)
+                    })+
+                }
+            }
+
+            fn underlying_storage(&self, renderer: &mut smithay::backend::renderer::pixman::PixmanRenderer) -> Option<smithay::backend::renderer::element::UnderlyingStorage> {
+                match self {
+                    $($name::$variant(elem) => elem.underlying_storage(renderer)),+
+                }
+            }
+        }
+
         $(impl<R: $crate::render_helpers::NiriRenderer> From<$type> for $name<R> {
             fn from(x: $type) -> Self {
                 Self::$variant(x)
@@ -293,3 +306,21 @@ impl<'render, 'alloc> RenderElement<TtyRenderer<'render, 'alloc>>
         None
     }
 }
+
+impl RenderElement<PixmanRenderer> for PrimaryGpuTextureRenderElement {
+    fn draw(
+        &self,
+        _frame: &mut PixmanFrame<'_>,
+        _src: Rectangle<f64, Buffer>,
+        _dst: Rectangle<i32, Physical>,
+        _damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), PixmanError> {
+        // Primary GPU textures come from the GLES renderer, which the pixman-based headless
+        // backend does not have, so these elements are never created there.
+        Ok(())
+    }
+
+    fn underlying_storage(&self, _renderer: &mut PixmanRenderer) -> Option<UnderlyingStorage> {
+        None
+    }
+}
@@ -12,6 +12,7 @@ use smithay::backend::renderer::element::solid::{SolidColorBuffer, SolidColorRen
 use smithay::backend::renderer::element::texture::{TextureBuffer, TextureRenderElement};
 use smithay::backend::renderer::element::{Element, Id, Kind, RenderElement, UnderlyingStorage};
 use smithay::backend::renderer::gles::{GlesError, GlesFrame, GlesRenderer, GlesTexture};
+use smithay::backend::renderer::pixman::{PixmanError, PixmanFrame, PixmanRenderer};
 use smithay::backend::renderer::utils::CommitCounter;
 use smithay::backend::renderer::ExportMem;
 use smithay::input::keyboard::{Keysym, ModifiersState};
@@ -575,6 +576,29 @@ impl<'render, 'alloc> RenderElement<TtyRenderer<'render, 'alloc>> for Screenshot
     }
 }
 
+impl RenderElement<PixmanRenderer> for ScreenshotUiRenderElement {
+    fn draw(
+        &self,
+        frame: &mut PixmanFrame<'_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+    ) -> Result<(), PixmanError> {
+        match self {
+            Self::Screenshot(elem) => {
+                RenderElement::<PixmanRenderer>::draw(&elem, frame, src, dst, damage)
+            }
+            Self::SolidColor(elem) => {
+                RenderElement::<PixmanRenderer>::draw(&elem, frame, src, dst, damage)
+            }
+        }
+    }
+
+    fn underlying_storage(&self, _renderer: &mut PixmanRenderer) -> Option<UnderlyingStorage> {
+        None
+    }
+}
+
 impl From<SolidColorRenderElement> for ScreenshotUiRenderElement {
     fn from(x: SolidColorRenderElement) -> Self {
         Self::SolidColor(x)
@@ -5,17 +5,62 @@ use std::os::unix::process::CommandExt;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::ptr::null_mut;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread;
 use std::time::Duration;
 
 use anyhow::{ensure, Context};
-use directories::UserDirs;
+use directories::{ProjectDirs, UserDirs};
 use niri_config::Config;
+use smithay::desktop::Window;
+use smithay::input::pointer::CursorIcon;
 use smithay::output::Output;
 use smithay::reexports::rustix::time::{clock_gettime, ClockId};
+use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel;
 use smithay::utils::{Logical, Point, Rectangle, Size};
 
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ResizeEdge: u32 {
+        const TOP          = 0b0001;
+        const BOTTOM       = 0b0010;
+        const LEFT         = 0b0100;
+        const RIGHT        = 0b1000;
+
+        const TOP_LEFT     = Self::TOP.bits() | Self::LEFT.bits();
+        const BOTTOM_LEFT  = Self::BOTTOM.bits() | Self::LEFT.bits();
+
+        const TOP_RIGHT    = Self::TOP.bits() | Self::RIGHT.bits();
+        const BOTTOM_RIGHT = Self::BOTTOM.bits() | Self::RIGHT.bits();
+
+        const LEFT_RIGHT   = Self::LEFT.bits() | Self::RIGHT.bits();
+        const TOP_BOTTOM   = Self::TOP.bits() | Self::BOTTOM.bits();
+    }
+}
+
+impl From<xdg_toplevel::ResizeEdge> for ResizeEdge {
+    #[inline]
+    fn from(x: xdg_toplevel::ResizeEdge) -> Self {
+        Self::from_bits(x as u32).unwrap()
+    }
+}
+
+impl ResizeEdge {
+    pub fn cursor_icon(self) -> CursorIcon {
+        match self {
+            Self::LEFT => CursorIcon::WResize,
+            Self::RIGHT => CursorIcon::EResize,
+            Self::TOP => CursorIcon::NResize,
+            Self::BOTTOM => CursorIcon::SResize,
+            Self::TOP_LEFT => CursorIcon::NwResize,
+            Self::TOP_RIGHT => CursorIcon::NeResize,
+            Self::BOTTOM_RIGHT => CursorIcon::SeResize,
+            Self::BOTTOM_LEFT => CursorIcon::SwResize,
+            _ => CursorIcon::Default,
+        }
+    }
+}
+
 pub fn clone2<T: Clone, U: Clone>(t: (&T, &U)) -> (T, U) {
     (t.0.clone(), t.1.clone())
 }
@@ -39,6 +84,26 @@ pub fn output_size(output: &Output) -> Size<i32, Logical> {
         .to_logical(output_scale)
 }
 
+/// Returns a unique ID of the window, assigning one if the window doesn't have it yet.
+///
+/// The ID does not change for the lifetime of the window, including when it is unmapped and
+/// mapped again.
+pub fn window_id(window: &Window) -> u64 {
+    static NEXT_WINDOW_ID: AtomicU64 = AtomicU64::new(1);
+
+    struct WindowId(u64);
+
+    let user_data = window.user_data();
+    user_data.insert_if_missing(|| WindowId(NEXT_WINDOW_ID.fetch_add(1, Ordering::Relaxed)));
+    user_data.get::<WindowId>().unwrap().0
+}
+
+/// Returns the path where the layout is saved to be restored after a restart.
+pub fn layout_snapshot_path() -> Option<PathBuf> {
+    let dirs = ProjectDirs::from("", "", "niri")?;
+    Some(dirs.state_dir()?.join("layout.json"))
+}
+
 pub fn make_screenshot_path(config: &Config) -> anyhow::Result<Option<PathBuf>> {
     let Some(path) = &config.screenshot_path else {
         return Ok(None);
@@ -1,8 +1,9 @@
 //! File modification watcher.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
@@ -10,6 +11,7 @@ use smithay::reexports::calloop::channel::SyncSender;
 
 pub struct Watcher {
     should_stop: Arc<AtomicBool>,
+    paths: Arc<Mutex<Vec<PathBuf>>>,
 }
 
 impl Drop for Watcher {
@@ -19,13 +21,15 @@ impl Drop for Watcher {
 }
 
 impl Watcher {
-    pub fn new(path: PathBuf, changed: SyncSender<()>) -> Self {
+    pub fn new(paths: Vec<PathBuf>, changed: SyncSender<()>) -> Self {
         let should_stop = Arc::new(AtomicBool::new(false));
+        let paths = Arc::new(Mutex::new(paths));
 
         {
             let should_stop = should_stop.clone();
+            let paths = paths.clone();
             thread::Builder::new()
-                .name(format!("Filesystem Watcher for {}", path.to_string_lossy()))
+                .name(String::from("Filesystem Watcher"))
                 .spawn(move || {
                     // this "should" be as simple as mtime, but it does not quite work in practice;
                     // it doesn't work if the config is a symlink, and its target changes but the
@@ -35,10 +39,15 @@ impl Watcher {
                     // because, on nix practically everything is a symlink to /nix/store
                     // and due to reproducibility, /nix/store keeps no mtime (= 1970-01-01)
                     // so, symlink targets change frequently when mtime doesn't.
-                    let mut last_props = path
-                        .canonicalize()
-                        .and_then(|canon| Ok((canon.metadata()?.modified()?, canon)))
-                        .ok();
+                    let props = |path: &PathBuf| {
+                        path.canonicalize()
+                            .and_then(|canon| Ok((canon.metadata()?.modified()?, canon)))
+                    };
+
+                    let mut last_props = HashMap::new();
+                    for path in paths.lock().unwrap().iter() {
+                        last_props.insert(path.clone(), props(path).ok());
+                    }
 
                     loop {
                         thread::sleep(Duration::from_millis(500));
@@ -47,28 +56,44 @@ impl Watcher {
                             break;
                         }
 
-                        if let Ok(new_props) = path
-                            .canonicalize()
-                            .and_then(|canon| Ok((canon.metadata()?.modified()?, canon)))
-                        {
-                            if last_props.as_ref() != Some(&new_props) {
-                                trace!("file changed: {}", path.to_string_lossy());
+                        let paths = paths.lock().unwrap().clone();
+                        last_props.retain(|path, _| paths.contains(path));
+
+                        let mut is_changed = false;
+                        for path in paths {
+                            let new_props = props(&path).ok();
 
-                                if let Err(err) = changed.send(()) {
-                                    warn!("error sending change notification: {err:?}");
-                                    break;
-                                }
+                            // Files that just started being watched only set the baseline.
+                            let Some(old_props) = last_props.get_mut(&path) else {
+                                last_props.insert(path, new_props);
+                                continue;
+                            };
 
-                                last_props = Some(new_props);
+                            if new_props.is_some() && *old_props != new_props {
+                                trace!("file changed: {}", path.to_string_lossy());
+                                is_changed = true;
+                                *old_props = new_props;
+                            }
+                        }
+
+                        if is_changed {
+                            if let Err(err) = changed.send(()) {
+                                warn!("error sending change notification: {err:?}");
+                                break;
                             }
                         }
                     }
 
-                    debug!("exiting watcher thread for {}", path.to_string_lossy());
+                    debug!("exiting watcher thread");
                 })
                 .unwrap();
         }
 
-        Self { should_stop }
+        Self { should_stop, paths }
+    }
+
+    /// Replaces the set of watched files.
+    pub fn set_paths(&self, paths: Vec<PathBuf>) {
+        *self.paths.lock().unwrap() = paths;
     }
 }
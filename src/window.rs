@@ -0,0 +1,154 @@
+use niri_config::{Match, WindowRule};
+use smithay::desktop::Window;
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::{
+    ToplevelSurface, XdgToplevelSurfaceData, XdgToplevelSurfaceRoleAttributes,
+};
+
+use crate::layout::workspace::ColumnWidth;
+use crate::layout::LayoutElement;
+
+#[derive(Debug)]
+pub struct Unmapped {
+    pub window: Window,
+    pub state: InitialConfigureState,
+}
+
+#[derive(Debug)]
+pub enum InitialConfigureState {
+    /// The window has not been initially configured yet.
+    NotConfigured,
+    /// The window has been configured.
+    Configured {
+        /// Resolved rules for this window, computed right before the initial configure.
+        rules: ResolvedWindowRules,
+    },
+}
+
+/// Rules fully resolved for a window.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ResolvedWindowRules {
+    /// Default width for this window.
+    ///
+    /// - `None`: unset (global default should be used).
+    /// - `Some(None)`: set to empty (window picks its own width).
+    /// - `Some(Some(width))`: set to a particular width.
+    pub default_width: Option<Option<ColumnWidth>>,
+
+    /// Output to open this window on.
+    pub open_on_output: Option<String>,
+
+    /// Workspace to open this window on, 1-based index.
+    pub open_on_workspace: Option<u8>,
+
+    /// Whether the window should open full-screen.
+    pub open_fullscreen: Option<bool>,
+
+    /// Whether the window should be focused when it opens.
+    pub open_focused: Option<bool>,
+}
+
+impl Unmapped {
+    /// Wraps a newly created window that hasn't been initially configured yet.
+    pub fn new(window: Window) -> Self {
+        Self {
+            window,
+            state: InitialConfigureState::NotConfigured,
+        }
+    }
+
+    pub fn needs_initial_configure(&self) -> bool {
+        matches!(self.state, InitialConfigureState::NotConfigured)
+    }
+}
+
+impl ResolvedWindowRules {
+    pub fn compute(rules: &[WindowRule], toplevel: &ToplevelSurface) -> Self {
+        let _span = tracy_client::span!("ResolvedWindowRules::compute");
+
+        let mut resolved = ResolvedWindowRules::default();
+
+        with_states(toplevel.wl_surface(), |states| {
+            let role = states
+                .data_map
+                .get::<XdgToplevelSurfaceData>()
+                .unwrap()
+                .lock()
+                .unwrap();
+
+            for rule in rules {
+                if !(rule.matches.is_empty()
+                    || rule.matches.iter().any(|m| window_matches(&role, m)))
+                {
+                    continue;
+                }
+
+                if rule.excludes.iter().any(|m| window_matches(&role, m)) {
+                    continue;
+                }
+
+                if let Some(x) = rule
+                    .default_column_width
+                    .as_ref()
+                    .map(|d| d.0.first().copied().map(ColumnWidth::from))
+                {
+                    resolved.default_width = Some(x);
+                }
+
+                if let Some(x) = rule.open_on_output.as_deref() {
+                    resolved.open_on_output = Some(x.to_owned());
+                }
+
+                if let Some(x) = rule.open_on_workspace {
+                    resolved.open_on_workspace = Some(x);
+                }
+
+                if let Some(x) = rule.open_fullscreen {
+                    resolved.open_fullscreen = Some(x);
+                }
+
+                if let Some(x) = rule.open_focused {
+                    resolved.open_focused = Some(x);
+                }
+            }
+        });
+
+        resolved
+    }
+}
+
+/// Returns whether a new window should open in the floating layer.
+///
+/// This is the case for dialogs (toplevels with a parent) and for fixed-size windows, which don't
+/// fit into columns well.
+pub fn should_float(window: &Window) -> bool {
+    if window.toplevel().parent().is_some() {
+        return true;
+    }
+
+    let min_size = window.min_size();
+    let max_size = window.max_size();
+    min_size.w > 0 && min_size.h > 0 && min_size == max_size
+}
+
+fn window_matches(role: &XdgToplevelSurfaceRoleAttributes, m: &Match) -> bool {
+    if let Some(app_id_re) = &m.app_id {
+        let Some(app_id) = &role.app_id else {
+            return false;
+        };
+        if !app_id_re.0.is_match(app_id) {
+            return false;
+        }
+    }
+
+    if let Some(title_re) = &m.title {
+        let Some(title) = &role.title else {
+            return false;
+        };
+        if !title_re.0.is_match(title) {
+            return false;
+        }
+    }
+
+    true
+}
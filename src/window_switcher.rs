@@ -0,0 +1,396 @@
+use std::cell::RefCell;
+use std::cmp::{max, min};
+
+use niri_config::{Action, Color};
+use pangocairo::cairo::{self, ImageSurface};
+use pangocairo::pango::{AttrInt, AttrList, EllipsizeMode, FontDescription, Weight};
+use smithay::backend::renderer::element::memory::{
+    MemoryRenderBuffer, MemoryRenderBufferRenderElement,
+};
+use smithay::backend::renderer::element::utils::{
+    Relocate, RelocateRenderElement, RescaleRenderElement,
+};
+use smithay::backend::renderer::element::Kind;
+use smithay::desktop::Window;
+use smithay::input::keyboard::Keysym;
+use smithay::output::Output;
+use smithay::reexports::gbm::Format as Fourcc;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::utils::{IsAlive, Logical, Physical, Point, Rectangle, Scale, Size, Transform};
+use smithay::wayland::compositor::with_states;
+use smithay::wayland::shell::xdg::XdgToplevelSurfaceData;
+
+use crate::layout::{LayoutElement, LayoutElementRenderElement};
+use crate::niri_render_elements;
+use crate::render_helpers::NiriRenderer;
+use crate::utils::output_size;
+
+const THUMBNAIL_WIDTH: i32 = 240;
+const THUMBNAIL_HEIGHT: i32 = 150;
+const LABEL_HEIGHT: i32 = 36;
+const PADDING: i32 = 12;
+const MARGIN: i32 = PADDING * 2;
+const FONT: &str = "sans 12px";
+
+/// Windows in the order they were last focused.
+#[derive(Debug, Default)]
+pub struct FocusHistory {
+    /// Most recently focused window first.
+    windows: Vec<Window>,
+}
+
+/// Overlay for switching between windows in most-recently-used order.
+#[derive(Default)]
+pub struct WindowSwitcher {
+    state: Option<OpenSwitcher>,
+    panel: RefCell<Option<RenderedPanel>>,
+}
+
+struct OpenSwitcher {
+    /// Output where the switcher is shown.
+    output: Output,
+    /// Windows to pick from, most recently focused first.
+    windows: Vec<Window>,
+    /// Index of the selected window.
+    selected: usize,
+    /// Whether releasing the modifiers should confirm the selection.
+    confirm_on_release: bool,
+}
+
+struct RenderedPanel {
+    params: PanelParams,
+    buffer: Option<MemoryRenderBuffer>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PanelParams {
+    /// Title and app ID of every window.
+    labels: Vec<(String, String)>,
+    selected: usize,
+    columns: usize,
+    scale: i32,
+    active_color: Color,
+}
+
+niri_render_elements! {
+    WindowSwitcherRenderElement => {
+        Panel = MemoryRenderBufferRenderElement<R>,
+        Thumbnail = RelocateRenderElement<RescaleRenderElement<LayoutElementRenderElement<R>>>,
+    }
+}
+
+impl FocusHistory {
+    /// Moves the window to the front of the history.
+    pub fn push(&mut self, window: Window) {
+        self.windows.retain(|win| win != &window && win.alive());
+        self.windows.insert(0, window);
+    }
+
+    /// Returns the windows, most recently focused first.
+    pub fn windows(&self) -> impl Iterator<Item = &Window> + '_ {
+        self.windows.iter()
+    }
+}
+
+impl WindowSwitcher {
+    /// Opens the switcher on the output with the given windows.
+    ///
+    /// The first window is selected. Returns `false` if there are no windows to switch between.
+    pub fn open(&mut self, output: Output, windows: Vec<Window>, confirm_on_release: bool) -> bool {
+        if windows.is_empty() {
+            return false;
+        }
+
+        self.state = Some(OpenSwitcher {
+            output,
+            windows,
+            selected: 0,
+            confirm_on_release,
+        });
+        true
+    }
+
+    /// Closes the switcher, returning the selected window.
+    pub fn close(&mut self) -> Option<Window> {
+        let state = self.state.take()?;
+        self.panel.borrow_mut().take();
+        Some(state.windows[state.selected].clone())
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state.is_some()
+    }
+
+    pub fn output(&self) -> Option<&Output> {
+        self.state.as_ref().map(|state| &state.output)
+    }
+
+    pub fn windows(&self) -> impl Iterator<Item = &Window> + '_ {
+        self.state.iter().flat_map(|state| &state.windows)
+    }
+
+    /// Returns whether releasing the modifiers should confirm the selection.
+    pub fn confirm_on_release(&self) -> bool {
+        self.state
+            .as_ref()
+            .is_some_and(|state| state.confirm_on_release)
+    }
+
+    /// Returns the action for a key pressed while the switcher is open.
+    pub fn action(&self, raw: Option<Keysym>) -> Option<Action> {
+        if !self.is_open() {
+            return None;
+        }
+
+        match raw? {
+            Keysym::Escape => Some(Action::CancelWindowSwitcher),
+            Keysym::Return => Some(Action::ConfirmWindowSwitcher),
+            Keysym::Left => Some(Action::WindowSwitcherPrevious),
+            Keysym::Right => Some(Action::WindowSwitcherNext),
+            _ => None,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if let Some(state) = &mut self.state {
+            state.selected = (state.selected + 1) % state.windows.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if let Some(state) = &mut self.state {
+            let len = state.windows.len();
+            state.selected = (state.selected + len - 1) % len;
+        }
+    }
+
+    /// Removes a destroyed window from the switcher, closing it if no windows remain.
+    ///
+    /// Returns `true` if the switcher has changed.
+    pub fn remove_window(&mut self, wl_surface: &WlSurface) -> bool {
+        let Some(state) = &mut self.state else {
+            return false;
+        };
+
+        let Some(idx) = state
+            .windows
+            .iter()
+            .position(|win| win.toplevel().wl_surface() == wl_surface)
+        else {
+            return false;
+        };
+
+        state.windows.remove(idx);
+        if state.windows.is_empty() {
+            self.close();
+            return true;
+        }
+
+        if idx < state.selected || state.selected == state.windows.len() {
+            state.selected -= 1;
+        }
+        true
+    }
+
+    pub fn render<R: NiriRenderer>(
+        &self,
+        renderer: &mut R,
+        output: &Output,
+        active_color: Color,
+    ) -> Vec<WindowSwitcherRenderElement<R>> {
+        let Some(state) = &self.state else {
+            return vec![];
+        };
+        if &state.output != output {
+            return vec![];
+        }
+
+        let scale = Scale::from(output.current_scale().fractional_scale());
+        let output_size = output_size(output);
+        let cell_size = cell_size();
+
+        let count = state.windows.len() as i32;
+        let columns = min(count, max(1, (output_size.w - MARGIN * 2) / cell_size.w));
+        let rows = (count + columns - 1) / columns;
+        let panel_size: Size<i32, Logical> =
+            Size::from((columns * cell_size.w, rows * cell_size.h));
+        let panel_loc: Point<i32, Logical> = Point::from((
+            max(0, (output_size.w - panel_size.w) / 2),
+            max(0, (output_size.h - panel_size.h) / 2),
+        ));
+
+        let mut elements = vec![];
+
+        // Thumbnails go on top of the panel.
+        for (idx, window) in state.windows.iter().enumerate() {
+            let idx = idx as i32;
+            let cell_loc =
+                panel_loc + Point::from((idx % columns * cell_size.w, idx / columns * cell_size.h));
+            let area = Rectangle::from_loc_and_size(
+                cell_loc + Point::from((PADDING, PADDING)),
+                (THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT),
+            );
+
+            let size = window.size();
+            if size.w <= 0 || size.h <= 0 {
+                continue;
+            }
+
+            let zoom = f64::min(
+                THUMBNAIL_WIDTH as f64 / size.w as f64,
+                THUMBNAIL_HEIGHT as f64 / size.h as f64,
+            )
+            .min(1.);
+            // Center the thumbnail in its area.
+            let offset = Point::from((
+                (area.size.w as f64 - size.w as f64 * zoom) / 2.,
+                (area.size.h as f64 - size.h as f64 * zoom) / 2.,
+            ));
+            let loc = area.loc.to_f64() + offset;
+            let loc: Point<i32, Physical> = loc.to_physical_precise_round(scale);
+
+            elements.extend(
+                window
+                    .render(renderer, Point::from((0, 0)), scale)
+                    .into_iter()
+                    .map(|elem| {
+                        let elem =
+                            RescaleRenderElement::from_element(elem, Point::from((0, 0)), zoom);
+                        let elem =
+                            RelocateRenderElement::from_element(elem, loc, Relocate::Relative);
+                        WindowSwitcherRenderElement::Thumbnail(elem)
+                    }),
+            );
+        }
+
+        let params = PanelParams {
+            labels: state.windows.iter().map(labels).collect(),
+            selected: state.selected,
+            columns: columns as usize,
+            scale: output.current_scale().integer_scale(),
+            active_color,
+        };
+
+        let mut rendered = self.panel.borrow_mut();
+        if rendered.as_ref().map_or(true, |r| r.params != params) {
+            let buffer = render_panel(&params)
+                .map_err(|err| warn!("error rendering window switcher: {err:?}"))
+                .ok();
+            *rendered = Some(RenderedPanel { params, buffer });
+        }
+
+        if let Some(buffer) = &rendered.as_ref().unwrap().buffer {
+            let loc: Point<f64, Physical> = panel_loc.to_f64().to_physical_precise_round(scale);
+            if let Ok(elem) = MemoryRenderBufferRenderElement::from_buffer(
+                renderer,
+                loc,
+                buffer,
+                Some(0.9),
+                None,
+                None,
+                Kind::Unspecified,
+            ) {
+                elements.push(WindowSwitcherRenderElement::Panel(elem));
+            }
+        }
+
+        elements
+    }
+}
+
+fn cell_size() -> Size<i32, Logical> {
+    Size::from((
+        THUMBNAIL_WIDTH + PADDING * 2,
+        THUMBNAIL_HEIGHT + LABEL_HEIGHT + PADDING * 2,
+    ))
+}
+
+fn labels(window: &Window) -> (String, String) {
+    with_states(window.toplevel().wl_surface(), |states| {
+        let role = states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .unwrap()
+            .lock()
+            .unwrap();
+        (
+            role.title.clone().unwrap_or_default(),
+            role.app_id.clone().unwrap_or_default(),
+        )
+    })
+}
+
+fn render_panel(params: &PanelParams) -> anyhow::Result<MemoryRenderBuffer> {
+    let _span = tracy_client::span!("window_switcher::render_panel");
+
+    let scale = params.scale;
+    let padding = PADDING * scale;
+    let cell_size = cell_size().upscale(scale);
+
+    let count = params.labels.len();
+    let columns = params.columns;
+    let rows = (count + columns - 1) / columns;
+    let width = cell_size.w * columns as i32;
+    let height = cell_size.h * rows as i32;
+
+    let mut font = FontDescription::from_string(FONT);
+    font.set_absolute_size((font.size() * scale).into());
+
+    let surface = ImageSurface::create(cairo::Format::ARgb32, width, height)?;
+    let cr = cairo::Context::new(&surface)?;
+    cr.set_source_rgb(0.1, 0.1, 0.1);
+    cr.paint()?;
+
+    let layout = pangocairo::create_layout(&cr);
+    layout.set_font_description(Some(&font));
+    layout.set_ellipsize(EllipsizeMode::End);
+    layout.set_width(THUMBNAIL_WIDTH * scale * pangocairo::pango::SCALE);
+
+    let bold = AttrList::new();
+    bold.insert(AttrInt::new_weight(Weight::Bold));
+
+    for (idx, (title, app_id)) in params.labels.iter().enumerate() {
+        let x = (idx % columns) as i32 * cell_size.w;
+        let y = (idx / columns) as i32 * cell_size.h;
+
+        if idx == params.selected {
+            let c = params.active_color;
+            cr.set_source_rgba(
+                c.r as f64 / 255.,
+                c.g as f64 / 255.,
+                c.b as f64 / 255.,
+                c.a as f64 / 255.,
+            );
+            cr.rectangle(x.into(), y.into(), cell_size.w.into(), cell_size.h.into());
+            cr.fill()?;
+        }
+
+        let text_y = y + padding + THUMBNAIL_HEIGHT * scale + padding / 2;
+
+        cr.set_source_rgb(1., 1., 1.);
+        cr.move_to((x + padding).into(), text_y.into());
+        layout.set_attributes(Some(&bold));
+        layout.set_text(title);
+        pangocairo::show_layout(&cr, &layout);
+        let (_, title_height) = layout.pixel_size();
+
+        cr.set_source_rgb(0.7, 0.7, 0.7);
+        cr.move_to((x + padding).into(), (text_y + title_height).into());
+        layout.set_attributes(None);
+        layout.set_text(app_id);
+        pangocairo::show_layout(&cr, &layout);
+    }
+    drop(cr);
+
+    let data = surface.take_data().unwrap();
+    let buffer = MemoryRenderBuffer::from_slice(
+        &data,
+        Fourcc::Argb8888,
+        (width, height),
+        scale,
+        Transform::Normal,
+        None,
+    );
+
+    Ok(buffer)
+}